@@ -6,7 +6,14 @@ use serde::{
 };
 use strum::Display;
 
-use crate::components::{notifications::Notification, pull_request::PullRequest};
+use crate::{
+    components::{
+        diff::{ChangedFile, CommentTarget, PendingReview, ReviewThread, ReviewThreads},
+        notifications::Notification,
+        pull_request::{PullRequest, PullRequestReview, PullRequestReviewEvent},
+    },
+    mode::Mode,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize)]
 pub enum Action {
@@ -31,17 +38,51 @@ pub enum Action {
     PageDn,
     PageUp,
     Sort(usize),
+    NextTab,
+    PreviousTab,
+    Search,
+    SearchPrompt,
+    ToggleGroups,
+    Diff,
+    SplitDiff,
+    FileTree,
+    ToggleViewed,
+    FileViewed(String, usize, String, bool),
+    SelectLines,
+    CommentLines,
     Notify(Notification),
+    SwitchMode(Mode),
+
+    // custom actions for switching between profiles
+    ProfilePicker,
+    SwitchProfile(String),
+    ForProfile(usize, Box<Action>),
+
+    // custom actions for logging in with the device flow
+    LoginCode(String, String),
+    LoginCompleted,
+    LoginFailed(String),
+
+    // custom actions for reviewing
+    Review(PullRequestReviewEvent),
+    ConfirmReview(Box<PullRequest>, PullRequestReviewEvent),
+    ReviewSubmitted(String, usize, PullRequestReview),
+    WriteComment(Box<PullRequest>, CommentTarget),
+    ReviewThreadsLoaded(String, usize, ReviewThreads),
+    ReviewCommentAdded(String, usize, String, ReviewThread),
+    PendingReviewLoaded(String, usize, PendingReview),
 
     // custom actions for fetching data
-    GetRepos,
-    GetReposResult(Vec<PullRequest>),
     GetCurrentUserResult(String),
     GetCurrentUser,
     PullRequestDetailsLoaded(Box<PullRequest>),
     PullRequestDetailsLoadError,
+    PullRequestFilesLoaded(String, usize, Vec<ChangedFile>),
+    PullRequestFilesLoadError,
     LoadMorePullRequests,
-    LoadMorePullRequestsResult(Vec<PullRequest>, bool, Option<String>),
+    /// The query and `after` cursor a page was requested with, its pull requests, and where the next page starts.
+    LoadMorePullRequestsResult(String, Option<String>, Vec<PullRequest>, bool, Option<String>),
+    LoadMorePullRequestsError(String, Option<String>),
     Left,
     Right,
 }
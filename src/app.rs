@@ -1,4 +1,6 @@
-use color_eyre::eyre::Result;
+use std::sync::Arc;
+
+use color_eyre::eyre::{eyre, Result};
 use crossterm::event::KeyEvent;
 use ratatui::prelude::Rect;
 use serde::{Deserialize, Serialize};
@@ -7,14 +9,16 @@ use tracing::{debug, info};
 
 use crate::{
     action::Action,
+    cache::PullRequestCache,
     components::{
         keystrokes::Keystrokes,
         notifications::{Notification, Notifications},
-        pull_request_info_overlay::PullRequestInfoOverlay,
+        profiles::Profiles,
         pull_request_list::PullRequestList,
         Component,
     },
-    config::Config,
+    config::{Config, Profile},
+    github::{client::GraphQLGithubClient, host::GithubHost, token::resolve_token},
     mode::Mode,
     tui,
 };
@@ -31,16 +35,57 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(tick_rate: f64, frame_rate: f64) -> Result<Self> {
-        let pr_list = PullRequestList::new();
+    pub fn new(tick_rate: f64, frame_rate: f64, host: Option<String>, profile: Option<String>) -> Result<Self> {
+        let mut config = Config::new()?;
+        let mut profiles = config.config.profiles();
+        let mut active = 0;
+        if let Some(host) = host {
+            let host = GithubHost::new(&host);
+            config.config.host = host.clone();
+            active = match profiles.iter().position(|profile| profile.host == host) {
+                Some(index) => index,
+                None => {
+                    profiles.push(Profile { name: host.to_string(), host, ..config.config.default_profile() });
+                    profiles.len() - 1
+                },
+            };
+        }
+        if let Some(name) = profile {
+            active = profiles
+                .iter()
+                .position(|profile| profile.name == name)
+                .ok_or_else(|| eyre!("Unknown profile {name}"))?;
+        }
+        let profiles = profiles
+            .into_iter()
+            .enumerate()
+            .map(|(index, profile)| {
+                // the other profiles' tokens are resolved once they are switched to
+                let resolved =
+                    if index == active { resolve_token(&profile, &config.config._data_dir) } else { Err(Vec::new()) };
+                let (token, token_attempts) = match resolved {
+                    Ok((token, source)) => {
+                        info!("Using token from {source} for profile {}", profile.name);
+                        (Some(token), Vec::new())
+                    },
+                    Err(attempts) => (None, attempts),
+                };
+                let client = Arc::new(GraphQLGithubClient::new(token, profile.host.clone())?);
+                let pull_request_list = PullRequestList::new(client)
+                    .with_oauth_client_id(profile.oauth_client_id.clone())
+                    .with_token_attempts(token_attempts)
+                    .with_cache(PullRequestCache::new(&config.config._data_dir, &profile.name));
+                Ok((profile, pull_request_list))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let profiles = Profiles::new(profiles, active).with_lazy_tokens(config.config._data_dir.clone());
         let keystrokes = Keystrokes::default();
-        let config = Config::new()?;
         let notifications = Notifications::default();
         let mode = Mode::Normal;
         Ok(Self {
             tick_rate,
             frame_rate,
-            components: vec![Box::new(keystrokes), Box::new(pr_list), Box::new(notifications)],
+            components: vec![Box::new(keystrokes), Box::new(profiles), Box::new(notifications)],
             should_quit: false,
             should_suspend: false,
             config,
@@ -111,6 +156,7 @@ impl App {
                     Action::Quit => self.should_quit = true,
                     Action::Suspend => self.should_suspend = true,
                     Action::Resume => self.should_suspend = false,
+                    Action::SwitchMode(mode) => self.mode = mode,
                     Action::Resize(w, h) => {
                         tui.resize(Rect::new(0, 0, w, h))?;
                         tui.draw(|f| {
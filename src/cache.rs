@@ -0,0 +1,134 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::components::pull_request::PullRequest;
+
+/// The last fetched pull requests and their details, kept on disk per profile so they can be shown before (or
+/// without) reaching GitHub.
+#[derive(Debug, Clone)]
+pub struct PullRequestCache {
+    dir: PathBuf,
+}
+
+/// Pull requests as they were when last fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedPullRequests {
+    pub fetched_at: DateTime<Utc>,
+    pub sections: Vec<CachedSection>,
+}
+
+/// The pull requests found by a section's search, keyed by the query so that renaming a section keeps them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedSection {
+    pub query: String,
+    pub pull_requests: Vec<PullRequest>,
+}
+
+impl CachedPullRequests {
+    pub fn section(&self, query: &str) -> Option<&[PullRequest]> {
+        self.sections.iter().find(|section| section.query == query).map(|section| section.pull_requests.as_slice())
+    }
+}
+
+impl PullRequestCache {
+    pub fn new(data_dir: &Path, profile: &str) -> Self {
+        Self { dir: data_dir.join("cache").join(file_name(profile)) }
+    }
+
+    pub fn load_pull_requests(&self) -> Option<CachedPullRequests> {
+        read(&self.dir.join("pull_requests.json"))
+    }
+
+    pub fn save_pull_requests(&self, sections: Vec<CachedSection>) -> Result<()> {
+        let cached = CachedPullRequests { fetched_at: Utc::now(), sections };
+        write(&self.dir.join("pull_requests.json"), &cached)
+    }
+
+    pub fn load_details(&self, repository: &str, number: usize) -> Option<PullRequest> {
+        read(&self.details_path(repository, number))
+    }
+
+    pub fn save_details(&self, pull_request: &PullRequest) -> Result<()> {
+        write(&self.details_path(&pull_request.repository, pull_request.number), pull_request)
+    }
+
+    fn details_path(&self, repository: &str, number: usize) -> PathBuf {
+        self.dir.join("details").join(file_name(repository)).join(format!("{number}.json"))
+    }
+}
+
+/// Profile and repository names contain characters such as `/` and `:` that are not safe in file names.
+fn file_name(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' }).collect()
+}
+
+/// A missing or unreadable cache entry is treated like an empty cache, e.g. after the format changed.
+fn read<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Writes JSON to a temporary file first, so an interrupted write never leaves a truncated file behind.
+pub(crate) fn write<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(value)?)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::components::pull_request::tests::pull_request;
+
+    #[test]
+    fn test_pull_requests_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ghtui-cache-{}", std::process::id()));
+        let cache = PullRequestCache::new(&dir, "https://github.example.com");
+
+        assert_eq!(cache.load_pull_requests(), None);
+        cache
+            .save_pull_requests(vec![CachedSection {
+                query: String::from("is:pr author:{user}"),
+                pull_requests: vec![pull_request("owner/repo", 1), pull_request("owner/repo", 2)],
+            }])
+            .unwrap();
+        let cached = cache.load_pull_requests().unwrap();
+        assert_eq!(
+            cached.section("is:pr author:{user}"),
+            Some([pull_request("owner/repo", 1), pull_request("owner/repo", 2)].as_slice())
+        );
+        assert_eq!(cached.section("is:pr assignee:{user}"), None);
+
+        assert_eq!(cache.load_details("owner/repo", 1), None);
+        let mut detailed = pull_request("owner/repo", 1);
+        detailed.body = String::from("Detailed description");
+        cache.save_details(&detailed).unwrap();
+        assert_eq!(cache.load_details("owner/repo", 1).unwrap().body, "Detailed description");
+        assert!(dir.join("cache/https___github.example.com/details/owner_repo/1.json").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_entry_is_ignored() {
+        let dir = std::env::temp_dir().join(format!("ghtui-cache-corrupt-{}", std::process::id()));
+        let cache = PullRequestCache::new(&dir, "default");
+        fs::create_dir_all(dir.join("cache/default")).unwrap();
+        fs::write(dir.join("cache/default/pull_requests.json"), "{\"fetched_at\":").unwrap();
+
+        assert_eq!(cache.load_pull_requests(), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -18,4 +18,14 @@ pub struct Cli {
     default_value_t = 4.0
   )]
   pub frame_rate: f64,
+
+  #[arg(
+    long,
+    value_name = "HOST",
+    help = "GitHub host to connect to, e.g. github.example.com for GitHub Enterprise Server [default: github.com]"
+  )]
+  pub host: Option<String>,
+
+  #[arg(short, long, value_name = "NAME", help = "Name of the profile from the config file to start with")]
+  pub profile: Option<String>,
 }
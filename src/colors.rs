@@ -17,4 +17,12 @@ pub(crate) const PEACH: Color = Color::from_u32(0x00fab387);
 pub(crate) const TEAL: Color = Color::from_u32(0x0094e2d5);
 pub(crate) const SAPPHIRE: Color = Color::from_u32(0x0074c7ec);
 pub(crate) const LAVENDER: Color = Color::from_u32(0x00b4befe);
+pub(crate) const MAUVE: Color = Color::from_u32(0x00cba6f7);
 pub(crate) const CRUST: Color = Color::from_u32(0x0011111b);
+
+// GREEN and RED blended into BASE, as backgrounds of added and removed lines that keep highlighted code readable
+pub(crate) const GREEN_TINT: Color = Color::from_u32(0x002b3b34);
+pub(crate) const RED_TINT: Color = Color::from_u32(0x003e2a37);
+// stronger blends, behind the words that changed within a line
+pub(crate) const GREEN_EMPHASIS: Color = Color::from_u32(0x003f5c48);
+pub(crate) const RED_EMPHASIS: Color = Color::from_u32(0x00613a4f);
@@ -9,11 +9,24 @@ use crate::{
     tui::{Event, Frame},
 };
 
+pub mod column;
+pub mod comment_prompt;
+pub mod diff;
+pub mod diff_view;
+pub mod file_tree;
+pub mod filter_bar;
+pub mod fuzzy;
+pub mod highlight;
 pub mod keystrokes;
+pub mod login;
 pub mod notifications;
+pub mod profiles;
 pub mod pull_request;
 pub mod pull_request_info_overlay;
 pub mod pull_request_list;
+pub mod pull_request_section;
+pub mod review_prompt;
+pub mod search_prompt;
 pub mod thing_list;
 pub mod utils;
 
@@ -0,0 +1,350 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use ratatui::{
+    layout::Constraint,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Cell,
+};
+use serde::Deserialize;
+
+use crate::{
+    colors::{BLUE, GREEN, OVERLAY0, PEACH, RED, TEAL, YELLOW},
+    components::{
+        fuzzy::PullRequestMatch,
+        pull_request::{CheckStatus, Mergeable, PullRequest, PullRequestReviewState, PullRequestState},
+    },
+};
+
+/// A column of the pull request table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Number,
+    Repository,
+    Title,
+    Author,
+    Created,
+    Updated,
+    Changes,
+    State,
+    Reviews,
+    Labels,
+    Checks,
+    BaseBranch,
+    Age,
+    Comments,
+    Mergeable,
+}
+
+/// How much of the table's width a column takes, e.g. `{ "percent": 20 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnWidth {
+    /// Exactly this many cells.
+    Fixed(u16),
+    /// This share of the table's width.
+    Percent(u16),
+    /// At least this many cells, growing into the space the other columns leave.
+    Min(u16),
+}
+
+/// A column to show, with the width to show it in, e.g. `{ "column": "title", "width": { "min": 40 } }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ColumnConfig {
+    pub column: Column,
+    /// Defaults to [`Column::default_width`].
+    #[serde(default)]
+    pub width: Option<ColumnWidth>,
+}
+
+impl ColumnConfig {
+    pub fn new(column: Column) -> Self {
+        Self { column, width: None }
+    }
+
+    pub fn width(&self) -> ColumnWidth {
+        self.width.unwrap_or_else(|| self.column.default_width())
+    }
+
+    pub fn constraint(&self) -> Constraint {
+        match self.width() {
+            ColumnWidth::Fixed(width) => Constraint::Length(width),
+            ColumnWidth::Percent(percent) => Constraint::Percentage(percent),
+            ColumnWidth::Min(width) => Constraint::Min(width),
+        }
+    }
+
+    /// The fewest cells the column can be shown in when the table is `table_width` wide.
+    fn min_width(&self, table_width: u16) -> u16 {
+        match self.width() {
+            ColumnWidth::Fixed(width) | ColumnWidth::Min(width) => width,
+            ColumnWidth::Percent(percent) => (table_width as u32 * percent as u32 / 100) as u16,
+        }
+    }
+}
+
+impl Column {
+    /// The columns shown unless others are configured.
+    pub fn defaults() -> Vec<ColumnConfig> {
+        [
+            Column::Number,
+            Column::Repository,
+            Column::Title,
+            Column::Author,
+            Column::Created,
+            Column::Updated,
+            Column::Changes,
+            Column::State,
+            Column::Reviews,
+        ]
+        .into_iter()
+        .map(ColumnConfig::new)
+        .collect()
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Number => "#",
+            Column::Repository => "Repository",
+            Column::Title => "Title",
+            Column::Author => "Author",
+            Column::Created => "Created",
+            Column::Updated => "Updated",
+            Column::Changes => "Changes",
+            Column::State => "State",
+            Column::Reviews => "Reviews",
+            Column::Labels => "Labels",
+            Column::Checks => "Checks",
+            Column::BaseBranch => "Base",
+            Column::Age => "Age",
+            Column::Comments => "Comments",
+            Column::Mergeable => "Mergeable",
+        }
+    }
+
+    pub fn default_width(&self) -> ColumnWidth {
+        match self {
+            Column::Number => ColumnWidth::Fixed(5),
+            Column::Repository => ColumnWidth::Percent(20),
+            Column::Title => ColumnWidth::Min(30),
+            Column::Author => ColumnWidth::Fixed(12),
+            Column::Created | Column::Updated => ColumnWidth::Fixed(10),
+            Column::Changes => ColumnWidth::Fixed(11),
+            Column::State => ColumnWidth::Fixed(6),
+            Column::Reviews | Column::Labels => ColumnWidth::Percent(15),
+            Column::Checks => ColumnWidth::Fixed(7),
+            Column::BaseBranch => ColumnWidth::Fixed(12),
+            Column::Age => ColumnWidth::Fixed(4),
+            Column::Comments => ColumnWidth::Fixed(8),
+            Column::Mergeable => ColumnWidth::Fixed(9),
+        }
+    }
+
+    /// How long the column is kept when the terminal is too narrow for all of them, lower is kept longer.
+    fn priority(&self) -> u8 {
+        match self {
+            Column::Title => 0,
+            Column::Number => 1,
+            Column::Repository => 2,
+            Column::Checks => 3,
+            Column::Reviews => 4,
+            Column::Author => 5,
+            Column::State => 6,
+            Column::Updated => 7,
+            Column::Age => 8,
+            Column::Mergeable => 9,
+            Column::Labels => 10,
+            Column::Changes => 11,
+            Column::Comments => 12,
+            Column::BaseBranch => 13,
+            Column::Created => 14,
+        }
+    }
+
+    pub fn compare(&self, a: &PullRequest, b: &PullRequest) -> Ordering {
+        match self {
+            Column::Number => a.number.cmp(&b.number),
+            Column::Repository => a.repository.cmp(&b.repository),
+            Column::Title => a.title.cmp(&b.title),
+            Column::Author => a.author.cmp(&b.author),
+            Column::Created => a.created_at.cmp(&b.created_at),
+            Column::Updated => a.updated_at.cmp(&b.updated_at),
+            Column::Changes => (a.additions + a.deletions).cmp(&(b.additions + b.deletions)),
+            Column::State => (&a.state, a.is_draft).cmp(&(&b.state, b.is_draft)),
+            Column::Reviews => a.reviews.len().cmp(&b.reviews.len()),
+            Column::Labels => a.labels.cmp(&b.labels),
+            Column::Checks => a.checks.cmp(&b.checks),
+            Column::BaseBranch => a.base_branch.cmp(&b.base_branch),
+            // the youngest first, like the ages shown
+            Column::Age => b.created_at.cmp(&a.created_at),
+            Column::Comments => a.comment_count.cmp(&b.comment_count),
+            Column::Mergeable => a.mergeable.cmp(&b.mergeable),
+        }
+    }
+
+    /// The cell showing this column of `pr`, highlighting the chars the filter matched.
+    pub fn cell(&self, pr: &PullRequest, matched: &PullRequestMatch, now: DateTime<Utc>) -> Cell<'static> {
+        match self {
+            Column::Number => Cell::from(highlighted(&pr.number.to_string(), &matched.number)),
+            Column::Repository => Cell::from(highlighted(&pr.repository, &matched.repository)),
+            Column::Title => Cell::from(highlighted(&pr.title, &matched.title)),
+            Column::Author => Cell::from(highlighted(&pr.author, &matched.author)),
+            Column::Created => Cell::from(pr.created_at.format("%Y-%m-%d").to_string()),
+            Column::Updated => Cell::from(pr.updated_at.format("%Y-%m-%d").to_string()),
+            Column::Changes => {
+                Cell::from(Line::from(vec![
+                    Span::styled(format!("{:+}", pr.additions), Style::new().fg(GREEN)),
+                    Span::styled(format!("{:+}", (0 - pr.deletions as isize)), Style::new().fg(RED)),
+                ]))
+            },
+            Column::State => {
+                Cell::from(match pr.state {
+                    PullRequestState::Open if pr.is_draft => "DRAFT",
+                    PullRequestState::Open => "OPEN",
+                    PullRequestState::Closed => "CLOSED",
+                    PullRequestState::Merged => "MERGED",
+                })
+            },
+            Column::Reviews => {
+                Cell::from(Line::from(
+                    pr.reviews
+                        .iter()
+                        .flat_map(|prr| {
+                            vec![
+                                Span::styled(prr.author.clone(), match prr.state {
+                                    PullRequestReviewState::Commented => Style::new().fg(BLUE),
+                                    PullRequestReviewState::Approved => Style::new().fg(GREEN),
+                                    PullRequestReviewState::ChangesRequested => Style::new().fg(YELLOW),
+                                    _ => Style::new().fg(Color::Gray),
+                                }),
+                                Span::raw(" "),
+                            ]
+                        })
+                        .collect::<Vec<Span>>(),
+                ))
+            },
+            Column::Labels => Cell::from(pr.labels.join(", ")).style(Style::new().fg(TEAL)),
+            Column::Checks => {
+                match pr.checks {
+                    Some(CheckStatus::Success) => Cell::from("✓ pass").style(Style::new().fg(GREEN)),
+                    Some(CheckStatus::Pending) => Cell::from("● run").style(Style::new().fg(YELLOW)),
+                    Some(CheckStatus::Failure) => Cell::from("✗ fail").style(Style::new().fg(RED)),
+                    None => Cell::from(""),
+                }
+            },
+            Column::BaseBranch => Cell::from(pr.base_branch.clone()),
+            Column::Age => Cell::from(age(now - pr.created_at)),
+            Column::Comments => Cell::from(pr.comment_count.to_string()),
+            Column::Mergeable => {
+                match pr.mergeable {
+                    Mergeable::Mergeable => Cell::from("yes").style(Style::new().fg(GREEN)),
+                    Mergeable::Conflicting => Cell::from("conflict").style(Style::new().fg(RED)),
+                    Mergeable::Unknown => Cell::from("?").style(Style::new().fg(OVERLAY0)),
+                }
+            },
+        }
+    }
+}
+
+/// The columns that fit into `width` cells, dropping the least important ones first while the terminal is too narrow
+/// for all of them. The most important configured column is always kept, the title unless it is left out.
+pub fn fit_columns(columns: &[ColumnConfig], width: u16) -> Vec<ColumnConfig> {
+    let mut shown = columns.to_vec();
+    // each column is followed by a space; summed as u32 so that large configured widths cannot overflow
+    let needed = |shown: &[ColumnConfig]| shown.iter().map(|c| u32::from(c.min_width(width)) + 1).sum::<u32>();
+    while needed(&shown) > u32::from(width) && shown.len() > 1 {
+        let Some(least_important) =
+            shown.iter().enumerate().max_by_key(|(_, c)| c.column.priority()).map(|(index, _)| index)
+        else {
+            break;
+        };
+        shown.remove(least_important);
+    }
+    shown
+}
+
+/// Highlights the chars at the given indices, e.g. those matched by the filter.
+pub fn highlighted(text: &str, indices: &[usize]) -> Line<'static> {
+    let highlight = Style::new().fg(PEACH).add_modifier(Modifier::BOLD);
+    let mut spans: Vec<Span<'static>> = vec![];
+    let mut current = String::new();
+    let mut is_highlighted = false;
+    for (index, c) in text.chars().enumerate() {
+        let highlight_char = indices.binary_search(&index).is_ok();
+        if highlight_char != is_highlighted && !current.is_empty() {
+            let content = std::mem::take(&mut current);
+            spans.push(if is_highlighted { Span::styled(content, highlight) } else { Span::raw(content) });
+        }
+        is_highlighted = highlight_char;
+        current.push(c);
+    }
+    spans.push(if is_highlighted { Span::styled(current, highlight) } else { Span::raw(current) });
+    Line::from(spans)
+}
+
+/// A short age like `5m`, `3h`, `12d`, `4mo` or `2y`.
+fn age(age: chrono::Duration) -> String {
+    let days = age.num_days();
+    if age.num_hours() < 1 {
+        format!("{}m", age.num_minutes().max(0))
+    } else if days < 1 {
+        format!("{}h", age.num_hours())
+    } else if days < 30 {
+        format!("{days}d")
+    } else if days < 365 {
+        format!("{}mo", days / 30)
+    } else {
+        format!("{}y", days / 365)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn columns(columns: &[ColumnConfig]) -> Vec<Column> {
+        columns.iter().map(|c| c.column).collect()
+    }
+
+    #[test]
+    fn test_narrow_tables_drop_the_least_important_columns() {
+        let defaults = Column::defaults();
+        assert_eq!(fit_columns(&defaults, 200), defaults);
+        assert_eq!(columns(&fit_columns(&defaults, 100)), vec![
+            Column::Number,
+            Column::Repository,
+            Column::Title,
+            Column::Author,
+            Column::State,
+            Column::Reviews,
+        ]);
+        assert_eq!(columns(&fit_columns(&defaults, 10)), vec![Column::Title]);
+
+        let wide_title = [ColumnConfig::new(Column::Number), ColumnConfig {
+            column: Column::Title,
+            width: Some(ColumnWidth::Fixed(60)),
+        }];
+        assert_eq!(columns(&fit_columns(&wide_title, 62)), vec![Column::Title]);
+
+        let huge = [ColumnConfig { column: Column::Number, width: Some(ColumnWidth::Fixed(u16::MAX)) }, ColumnConfig {
+            column: Column::Title,
+            width: Some(ColumnWidth::Min(u16::MAX)),
+        }];
+        assert_eq!(columns(&fit_columns(&huge, 100)), vec![Column::Title]);
+    }
+
+    #[test]
+    fn test_age() {
+        assert_eq!(age(Duration::seconds(30)), "0m");
+        assert_eq!(age(Duration::minutes(59)), "59m");
+        assert_eq!(age(Duration::hours(23)), "23h");
+        assert_eq!(age(Duration::days(29)), "29d");
+        assert_eq!(age(Duration::days(95)), "3mo");
+        assert_eq!(age(Duration::days(800)), "2y");
+    }
+}
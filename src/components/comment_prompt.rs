@@ -0,0 +1,112 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{diff::CommentTarget, pull_request::PullRequest, utils::centered_rect, Component};
+use crate::{
+    action::Action,
+    colors::{BASE, LAVENDER, OVERLAY0, TEXT},
+    tui::Frame,
+};
+
+/// Prompt for a review comment on lines of a pull request's diff, which is added to the signed in user's pending
+/// review rather than published right away.
+///
+/// Like [`super::review_prompt::ReviewPrompt`], it relies on the owning component to switch the app into
+/// [`crate::mode::Mode::Insert`] while it is open.
+pub struct CommentPrompt {
+    pull_request: PullRequest,
+    target: CommentTarget,
+    body: String,
+}
+
+impl CommentPrompt {
+    pub fn new(pull_request: PullRequest, target: CommentTarget) -> Self {
+        Self { pull_request, target, body: String::new() }
+    }
+
+    pub fn pull_request(&self) -> &PullRequest {
+        &self.pull_request
+    }
+
+    pub fn target(&self) -> &CommentTarget {
+        &self.target
+    }
+
+    /// Returns the comment if one was written.
+    pub fn confirm(&self) -> Option<String> {
+        let body = self.body.trim();
+        (!body.is_empty()).then(|| body.to_string())
+    }
+}
+
+impl Component for CommentPrompt {
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.body.push(c),
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => self.body.push('\n'),
+            KeyCode::Backspace => {
+                self.body.pop();
+            },
+            _ => return Ok(None),
+        }
+        Ok(Some(Action::Render))
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let area = centered_rect(area, 60, 40);
+        let block = Block::default()
+            .title(
+                Span::styled(
+                    format!(" Comment on {} ", self.target),
+                    Style::default().fg(LAVENDER).add_modifier(Modifier::BOLD),
+                )
+                .into_centered_line(),
+            )
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(LAVENDER).bg(BASE))
+            .bg(BASE);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let layout = Layout::new(Direction::Vertical, [Constraint::Min(1), Constraint::Length(1)])
+            .split(area.inner(&Margin { horizontal: 2, vertical: 1 }));
+
+        let body =
+            Paragraph::new(format!("{}_", self.body)).style(Style::default().fg(TEXT)).wrap(Wrap { trim: false });
+        let hint = Paragraph::new("enter: add to pending review · alt-enter: new line · esc: cancel")
+            .style(Style::default().fg(OVERLAY0))
+            .alignment(Alignment::Center);
+
+        f.render_widget(body, layout[0]);
+        f.render_widget(hint, layout[1]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{diff::DiffSide, pull_request::tests::pull_request};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_comment_requires_body() {
+        let target = CommentTarget { path: String::from("src/main.rs"), side: DiffSide::Right, line: 3, start: None };
+        let mut prompt = CommentPrompt::new(pull_request("owner/repo", 1), target);
+        assert_eq!(prompt.confirm(), None);
+
+        for c in " nit: typo".chars() {
+            prompt.handle_key_events(key(KeyCode::Char(c))).unwrap();
+        }
+        prompt.handle_key_events(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)).unwrap();
+        prompt.handle_key_events(key(KeyCode::Char('x'))).unwrap();
+        prompt.handle_key_events(key(KeyCode::Backspace)).unwrap();
+        assert_eq!(prompt.confirm(), Some(String::from("nit: typo")));
+        assert_eq!(prompt.target().to_string(), "src/main.rs:3");
+    }
+}
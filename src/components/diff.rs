@@ -0,0 +1,388 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::pull_request::PullRequestComment;
+
+/// How a file was changed by a pull request, as reported by the REST API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Added,
+    Removed,
+    Modified,
+    Renamed,
+    Copied,
+    Changed,
+    Unchanged,
+}
+
+/// A file changed by a pull request, as returned by `GET /repos/{owner}/{repo}/pulls/{number}/files`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedFile {
+    pub filename: String,
+    /// The path before a rename.
+    #[serde(default)]
+    pub previous_filename: Option<String>,
+    pub status: FileStatus,
+    pub additions: usize,
+    pub deletions: usize,
+    /// The unified diff of the file, missing for binary files and diffs GitHub considers too large to show.
+    #[serde(default)]
+    pub patch: Option<String>,
+    /// Whether the signed in user marked the file as viewed, which only the GraphQL API tells.
+    #[serde(default)]
+    pub viewed: bool,
+}
+
+impl ChangedFile {
+    /// The hunks of the file's patch, empty if there is none.
+    pub fn hunks(&self) -> Vec<Hunk> {
+        self.patch.as_deref().map(parse_patch).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// A line of a hunk, with its number in the old and the new version of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    /// `None` for added lines.
+    pub old_line: Option<usize>,
+    /// `None` for removed lines.
+    pub new_line: Option<usize>,
+    /// The line without its `+`, `-` or ` ` prefix.
+    pub content: String,
+}
+
+impl DiffLine {
+    /// The side and number a review comment on the line refers to it by: removed lines are on the old side, the others
+    /// on the new one.
+    pub fn position(&self) -> (DiffSide, usize) {
+        match (self.kind, self.old_line, self.new_line) {
+            (LineKind::Removed, Some(old), _) => (DiffSide::Left, old),
+            (_, _, Some(new)) => (DiffSide::Right, new),
+            (_, old, _) => (DiffSide::Left, old.unwrap_or_default()),
+        }
+    }
+
+    /// Whether the line is the one numbered `line` on `side`.
+    pub fn is_at(&self, side: DiffSide, line: usize) -> bool {
+        match side {
+            DiffSide::Left => self.old_line == Some(line),
+            DiffSide::Right => self.new_line == Some(line),
+        }
+    }
+}
+
+/// The version of a file a review comment is on: the old one (`Left`) or the new one (`Right`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffSide {
+    Left,
+    Right,
+}
+
+/// The lines of a file a review comment is attached to, numbered in the version of the file on their side.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommentTarget {
+    pub path: String,
+    pub side: DiffSide,
+    /// The last line of a range.
+    pub line: usize,
+    /// The first line of a range of several lines.
+    pub start: Option<(DiffSide, usize)>,
+}
+
+impl std::fmt::Display for CommentTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.start {
+            Some((_, start)) => write!(f, "{}:{}-{}", self.path, start, self.line),
+            None => write!(f, "{}:{}", self.path, self.line),
+        }
+    }
+}
+
+/// Review comments on lines of a file, replying to each other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewThread {
+    pub target: CommentTarget,
+    pub is_resolved: bool,
+    /// Part of the signed in user's pending review, which nobody else sees until it is submitted.
+    pub is_pending: bool,
+    pub comments: Vec<PullRequestComment>,
+}
+
+/// The signed in user's pending review of a pull request, which new review comments are added to and which is
+/// submitted with them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingReview {
+    pub id: String,
+    pub comments: usize,
+}
+
+/// The review threads of a pull request that are still on lines of its diff, and the signed in user's pending review
+/// new comments are added to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewThreads {
+    pub pending_review: Option<PendingReview>,
+    pub threads: Vec<ReviewThread>,
+}
+
+/// A hunk of a unified diff, e.g. `@@ -10,6 +10,8 @@ fn main() {` and the lines following it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// The indices of a line of the old and of the new version shown side by side, `None` for no line.
+pub type LinePair = (Option<usize>, Option<usize>);
+
+/// Pairs the lines of a hunk for showing the old and the new version side by side: context lines with themselves, and
+/// each run of removed lines with the run of added lines following it, line by line. Lines left over on one side are
+/// paired with nothing.
+pub fn pair_lines(lines: &[DiffLine]) -> Vec<LinePair> {
+    let mut pairs = vec![];
+    let (mut removed, mut added): (Vec<usize>, Vec<usize>) = (vec![], vec![]);
+    let flush = |pairs: &mut Vec<_>, removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+        for i in 0..removed.len().max(added.len()) {
+            pairs.push((removed.get(i).copied(), added.get(i).copied()));
+        }
+        removed.clear();
+        added.clear();
+    };
+    for (index, line) in lines.iter().enumerate() {
+        match line.kind {
+            LineKind::Removed if !added.is_empty() => {
+                flush(&mut pairs, &mut removed, &mut added);
+                removed.push(index);
+            },
+            LineKind::Removed => removed.push(index),
+            LineKind::Added => added.push(index),
+            LineKind::Context => {
+                flush(&mut pairs, &mut removed, &mut added);
+                pairs.push((Some(index), Some(index)));
+            },
+        }
+    }
+    flush(&mut pairs, &mut removed, &mut added);
+    pairs
+}
+
+/// Lines with more words than this are not compared word by word.
+const MAX_WORDS: usize = 200;
+
+/// The byte ranges of the words that differ between the old and the new version of a changed line, e.g. a renamed
+/// variable. Words are runs of letters, digits and underscores, runs of whitespace, or single other characters.
+///
+/// Lines with nothing but whitespace in common have no ranges, as emphasizing all of their words would not help.
+pub fn word_diff(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let (old_words, new_words) = (words(old), words(new));
+    if old_words.len() > MAX_WORDS || new_words.len() > MAX_WORDS {
+        return (vec![], vec![]);
+    }
+    // the longest common subsequence of words, the others changed
+    let (n, m) = (old_words.len(), new_words.len());
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[old_words[i].clone()] == new[new_words[j].clone()] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut old_changed, mut new_changed) = (vec![], vec![]);
+    let (mut i, mut j) = (0, 0);
+    let mut in_common = false;
+    while i < n || j < m {
+        if i < n && j < m && old[old_words[i].clone()] == new[new_words[j].clone()] {
+            in_common |= !old[old_words[i].clone()].trim().is_empty();
+            (i, j) = (i + 1, j + 1);
+        } else if j < m && (i == n || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            push_range(&mut new_changed, new_words[j].clone());
+            j += 1;
+        } else {
+            push_range(&mut old_changed, old_words[i].clone());
+            i += 1;
+        }
+    }
+    if !in_common {
+        return (vec![], vec![]);
+    }
+    (old_changed, new_changed)
+}
+
+fn words(line: &str) -> Vec<Range<usize>> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut words: Vec<Range<usize>> = vec![];
+    let mut previous = None;
+    for (index, c) in line.char_indices() {
+        let current = class(c);
+        match words.last_mut() {
+            Some(word) if previous == Some(current) && current != 2 => word.end = index + c.len_utf8(),
+            _ => words.push(index..index + c.len_utf8()),
+        }
+        previous = Some(current);
+    }
+    words
+}
+
+/// Adds `range` to `ranges`, merging it with the last one if they touch.
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+/// Splits the patch of a file into hunks. GitHub's patches start right at the first hunk header, without the `---` and
+/// `+++` file headers of `git diff`.
+pub fn parse_patch(patch: &str) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = vec![];
+    let (mut old_line, mut new_line) = (0, 0);
+    for line in patch.lines() {
+        if let Some((old_start, new_start)) = parse_hunk_header(line) {
+            (old_line, new_line) = (old_start, new_start);
+            hunks.push(Hunk { header: line.to_string(), lines: vec![] });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            continue;
+        };
+        let (kind, content) = match line.chars().next() {
+            Some('+') => (LineKind::Added, &line[1..]),
+            Some('-') => (LineKind::Removed, &line[1..]),
+            Some(' ') => (LineKind::Context, &line[1..]),
+            // `\ No newline at end of file`
+            Some('\\') => continue,
+            _ => (LineKind::Context, line),
+        };
+        let (old, new) = match kind {
+            LineKind::Added => (None, Some(new_line)),
+            LineKind::Removed => (Some(old_line), None),
+            LineKind::Context => (Some(old_line), Some(new_line)),
+        };
+        old_line += old.is_some() as usize;
+        new_line += new.is_some() as usize;
+        hunk.lines.push(DiffLine { kind, old_line: old, new_line: new, content: content.to_string() });
+    }
+    hunks
+}
+
+/// The first line numbers of the old and the new version in a hunk header like `@@ -10,6 +12,8 @@`.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let ranges = line.strip_prefix("@@ -")?;
+    let (old, rest) = ranges.split_once(" +")?;
+    let (new, _) = rest.split_once(" @@")?;
+    let start = |range: &str| range.split(',').next()?.parse().ok();
+    Some((start(old)?, start(new)?))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Builds a modified file for component tests, with its additions and deletions counted from `patch`.
+    pub(crate) fn changed_file(filename: &str, patch: Option<&str>) -> ChangedFile {
+        let count = |prefix: char| patch.map_or(0, |patch| patch.lines().filter(|l| l.starts_with(prefix)).count());
+        ChangedFile {
+            filename: filename.to_string(),
+            previous_filename: None,
+            status: FileStatus::Modified,
+            additions: count('+'),
+            deletions: count('-'),
+            patch: patch.map(String::from),
+            viewed: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_patch() {
+        let patch = [
+            "@@ -1,3 +1,3 @@",
+            " use std::fs;",
+            "-fn old() {}",
+            "+fn new() {}",
+            " ",
+            "@@ -10 +10,2 @@ impl Foo {",
+            "+    bar();",
+            "     baz();",
+            "\\ No newline at end of file",
+        ]
+        .join("\n");
+        let hunks = parse_patch(&patch);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].lines, vec![
+            DiffLine { kind: LineKind::Context, old_line: Some(1), new_line: Some(1), content: "use std::fs;".into() },
+            DiffLine { kind: LineKind::Removed, old_line: Some(2), new_line: None, content: "fn old() {}".into() },
+            DiffLine { kind: LineKind::Added, old_line: None, new_line: Some(2), content: "fn new() {}".into() },
+            DiffLine { kind: LineKind::Context, old_line: Some(3), new_line: Some(3), content: "".into() },
+        ]);
+        assert_eq!(hunks[1].header, "@@ -10 +10,2 @@ impl Foo {");
+        assert_eq!(hunks[1].lines[1], DiffLine {
+            kind: LineKind::Context,
+            old_line: Some(10),
+            new_line: Some(11),
+            content: "    baz();".into()
+        });
+    }
+
+    #[test]
+    fn test_pair_lines() {
+        let hunk = &parse_patch("@@ -1,4 +1,4 @@\n a\n-b\n-c\n+B\n d\n+e\n-f")[0];
+        assert_eq!(pair_lines(&hunk.lines), vec![
+            (Some(0), Some(0)),
+            (Some(1), Some(3)),
+            (Some(2), None),
+            (Some(4), Some(4)),
+            (None, Some(5)),
+            (Some(6), None),
+        ]);
+    }
+
+    #[test]
+    fn test_word_diff() {
+        let (old, new) = word_diff("let count = items.len();", "let total = items.len() + 1;");
+        assert_eq!(old, vec![4..9]);
+        assert_eq!(new, vec![4..9, 23..27]);
+        assert_eq!(word_diff("same", "same"), (vec![], vec![]));
+        assert_eq!(word_diff("old words", "new text"), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_changed_file_from_rest() {
+        let file: ChangedFile = serde_json::from_value(serde_json::json!({
+            "sha": "bbcd538c8e72b8c175046e27cc8f907076331401",
+            "filename": "assets/logo.png",
+            "previous_filename": "logo.png",
+            "status": "renamed",
+            "additions": 0,
+            "deletions": 0,
+            "changes": 0,
+        }))
+        .unwrap();
+
+        assert_eq!(file.status, FileStatus::Renamed);
+        assert_eq!(file.previous_filename.as_deref(), Some("logo.png"));
+        assert!(file.hunks().is_empty());
+    }
+}
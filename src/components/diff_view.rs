@@ -0,0 +1,959 @@
+use std::{collections::HashMap, ops::Range};
+
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use super::{
+    diff::{
+        pair_lines, word_diff, ChangedFile, CommentTarget, DiffLine, FileStatus, Hunk, LineKind, LinePair, ReviewThread,
+    },
+    file_tree::FileTree,
+    highlight::{language_for, Highlighter},
+    Component,
+};
+use crate::{
+    action::Action,
+    colors::{
+        BASE, GREEN, GREEN_EMPHASIS, GREEN_TINT, LAVENDER, OVERLAY0, RED, RED_EMPHASIS, RED_TINT, SAPPHIRE, SURFACE0,
+        TEXT, YELLOW,
+    },
+    config::DEFAULT_SPLIT_DIFF_MIN_WIDTH,
+    tui::Frame,
+};
+
+/// A row of the diff view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffRow {
+    /// The status, name and counts of a file.
+    File(usize),
+    /// Shown instead of the hunks of binary files and diffs too large to show, by file.
+    NoPatch(usize),
+    /// The header of a hunk, by file and hunk.
+    HunkHeader(usize, usize),
+    /// A line of a hunk in the unified diff, by file, hunk and line.
+    Line(usize, usize, usize),
+    /// Lines of the old and the new version side by side, by file, hunk and pair, see [`pair_lines`].
+    Pair(usize, usize, usize),
+    /// A line of a review thread below the line it is on, by thread and line.
+    Comment(usize, usize),
+    /// Between files, by the file above.
+    Blank(usize),
+}
+
+/// The rows of one way to show the diff.
+#[derive(Debug, Default)]
+struct Rows {
+    rows: Vec<DiffRow>,
+    /// The index of each file's header in `rows`.
+    file_starts: Vec<usize>,
+}
+
+/// The lines of a hunk with their line numbers and highlighted code, for both ways to show it.
+struct RenderedHunk {
+    unified: Vec<Line<'static>>,
+    /// The old and the new side of each pair of lines.
+    split: Vec<(Line<'static>, Line<'static>)>,
+}
+
+/// A row as shown, across the whole width or split in the old and the new version.
+#[derive(Debug, Clone, PartialEq)]
+enum ShownRow {
+    Full(Line<'static>),
+    Split(Line<'static>, Line<'static>),
+}
+
+/// Shows the files changed by a pull request one after the other, with the code highlighted by the language of each
+/// file. The diffs are unified, or split in the old and the new version side by side when the terminal is wide enough,
+/// with the words that changed within a line emphasized.
+///
+/// Moves a cursor with up/down and page up/down, and jumps to the next or previous file with the keys switching tabs in
+/// the pull request list. A tree of the changed files is shown next to the diff when there is room, and takes up/down
+/// and left/right while focused, jumping to the selected file.
+///
+/// Review threads are shown below the lines they are on. The line at the cursor, or the lines selected from where
+/// selecting started, are what a new review comment goes on.
+pub struct DiffView {
+    files: Vec<ChangedFile>,
+    /// The hunks of each file.
+    hunks: Vec<Vec<Hunk>>,
+    /// The pairs of lines of each hunk of each file.
+    pairs: Vec<Vec<Vec<LinePair>>>,
+    unified: Rows,
+    split: Rows,
+    /// The hunks shown so far, by file and hunk, so that scrolling does not highlight them again.
+    rendered: HashMap<(usize, usize), RenderedHunk>,
+    /// Whether the diff should be split, if the terminal is at least `min_split_width` wide.
+    split_requested: bool,
+    min_split_width: u16,
+    /// The width of the terminal the view was last drawn in, rather than of the view itself, which loses columns to
+    /// the overlay's margins and the file tree.
+    width: u16,
+    /// The height the view was last drawn with.
+    height: u16,
+    /// The first row shown.
+    scroll_offset: usize,
+    /// The row at the cursor.
+    cursor: usize,
+    /// The row selecting started at, if lines are being selected.
+    anchor: Option<usize>,
+    threads: Vec<ReviewThread>,
+    /// The lines of each thread, see [`DiffView::render_thread`].
+    thread_lines: Vec<Vec<Line<'static>>>,
+    tree: FileTree,
+    tree_focused: bool,
+    /// The number of files the pull request changes, more than listed if GitHub does not list them all.
+    changed_files: usize,
+}
+
+/// The narrowest view the file tree is shown in without being focused.
+const MIN_TREE_VIEW_WIDTH: u16 = 100;
+
+impl DiffView {
+    pub fn new(files: Vec<ChangedFile>) -> Self {
+        let hunks: Vec<Vec<Hunk>> = files.iter().map(ChangedFile::hunks).collect();
+        let pairs: Vec<Vec<_>> =
+            hunks.iter().map(|file_hunks| file_hunks.iter().map(|hunk| pair_lines(&hunk.lines)).collect()).collect();
+        let mut view = Self {
+            hunks,
+            pairs,
+            unified: Rows::default(),
+            split: Rows::default(),
+            rendered: HashMap::new(),
+            split_requested: false,
+            min_split_width: DEFAULT_SPLIT_DIFF_MIN_WIDTH,
+            width: 0,
+            height: 0,
+            scroll_offset: 0,
+            cursor: 0,
+            anchor: None,
+            threads: vec![],
+            thread_lines: vec![],
+            tree: FileTree::new(&files),
+            tree_focused: false,
+            changed_files: files.len(),
+            files,
+        };
+        view.layout();
+        view
+    }
+
+    /// Lays out the rows of both ways to show the diff, with the review threads below the lines they are on.
+    fn layout(&mut self) {
+        let threads_at = |file: usize, lines: &[&DiffLine]| -> Vec<DiffRow> {
+            self.threads
+                .iter()
+                .enumerate()
+                .filter(|(_, thread)| {
+                    thread.target.path == self.files[file].filename
+                        && lines.iter().any(|line| line.is_at(thread.target.side, thread.target.line))
+                })
+                .flat_map(|(thread, _)| {
+                    (0..self.thread_lines[thread].len()).map(move |line| DiffRow::Comment(thread, line))
+                })
+                .collect()
+        };
+        let rows = |lines: &dyn Fn(usize, usize) -> Vec<DiffRow>| {
+            let mut rows = Rows::default();
+            for (file, file_hunks) in self.hunks.iter().enumerate() {
+                rows.file_starts.push(rows.rows.len());
+                rows.rows.push(DiffRow::File(file));
+                if file_hunks.is_empty() {
+                    rows.rows.push(DiffRow::NoPatch(file));
+                }
+                for hunk in 0..file_hunks.len() {
+                    rows.rows.push(DiffRow::HunkHeader(file, hunk));
+                    rows.rows.extend(lines(file, hunk));
+                }
+                rows.rows.push(DiffRow::Blank(file));
+            }
+            rows
+        };
+        let unified = rows(&|file, hunk| {
+            let lines = &self.hunks[file][hunk].lines;
+            (0..lines.len())
+                .flat_map(|line| [vec![DiffRow::Line(file, hunk, line)], threads_at(file, &[&lines[line]])].concat())
+                .collect()
+        });
+        let split = rows(&|file, hunk| {
+            let lines = &self.hunks[file][hunk].lines;
+            self.pairs[file][hunk]
+                .iter()
+                .enumerate()
+                .flat_map(|(pair, &(old, new))| {
+                    let paired: Vec<&DiffLine> = [old, new].into_iter().flatten().map(|line| &lines[line]).collect();
+                    [vec![DiffRow::Pair(file, hunk, pair)], threads_at(file, &paired)].concat()
+                })
+                .collect()
+        });
+        (self.unified, self.split) = (unified, split);
+    }
+
+    pub fn with_changed_files(mut self, changed_files: usize) -> Self {
+        self.changed_files = changed_files;
+        self
+    }
+
+    pub fn with_min_split_width(mut self, width: u16) -> Self {
+        self.min_split_width = width;
+        self
+    }
+
+    pub fn files(&self) -> &[ChangedFile] {
+        &self.files
+    }
+
+    /// The file to mark as viewed: the one selected in the tree while it is focused, the one at the cursor otherwise.
+    pub fn viewed_target(&self) -> Option<&ChangedFile> {
+        let file = if self.tree_focused { self.tree.selected_file()? } else { self.current_file() };
+        self.files.get(file)
+    }
+
+    pub fn set_viewed(&mut self, path: &str, viewed: bool) {
+        for file in self.files.iter_mut().filter(|file| file.filename == path) {
+            file.viewed = viewed;
+        }
+    }
+
+    /// Whether the diff is split, which it is only if requested and the terminal is wide enough.
+    pub fn is_split(&self) -> bool {
+        self.split_requested && self.width >= self.min_split_width
+    }
+
+    fn rows(&self) -> &Rows {
+        if self.is_split() {
+            &self.split
+        } else {
+            &self.unified
+        }
+    }
+
+    /// The index of the file at the cursor.
+    pub fn current_file(&self) -> usize {
+        self.rows().file_starts.iter().rposition(|&start| start <= self.cursor).unwrap_or_default()
+    }
+
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll_offset
+    }
+
+    fn move_cursor(&mut self, rows: isize) {
+        self.cursor = self.cursor.saturating_add_signed(rows).min(self.rows().rows.len().saturating_sub(1));
+        self.scroll_to_cursor();
+    }
+
+    /// Scrolls just enough to show the cursor.
+    fn scroll_to_cursor(&mut self) {
+        let height = (self.height as usize).max(1);
+        self.scroll_offset = self.scroll_offset.clamp(self.cursor.saturating_sub(height - 1), self.cursor);
+    }
+
+    /// Moves the cursor to a row and scrolls it to the top.
+    fn jump_to(&mut self, row: usize) {
+        (self.cursor, self.scroll_offset) = (row, row);
+    }
+
+    fn jump_to_file(&mut self, file: usize) {
+        if let Some(&start) = self.rows().file_starts.get(file) {
+            self.jump_to(start);
+        }
+    }
+
+    fn next_file(&mut self) {
+        self.jump_to_file(self.current_file() + 1);
+    }
+
+    /// Jumps to the start of the current file, or to the previous file if already there.
+    fn previous_file(&mut self) {
+        let current = self.current_file();
+        let file_starts = &self.rows().file_starts;
+        match file_starts.get(current) {
+            Some(&start) if start < self.cursor => self.jump_to(start),
+            _ => self.jump_to(file_starts.get(current.saturating_sub(1)).copied().unwrap_or_default()),
+        }
+    }
+
+    /// The rows selected, or the row at the cursor if lines are not being selected.
+    fn selection(&self) -> Range<usize> {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        anchor.min(self.cursor)..anchor.max(self.cursor) + 1
+    }
+
+    /// The selected lines, or the line at the cursor, as what a review comment goes on. The lines must be in one hunk.
+    pub fn comment_target(&self) -> Result<CommentTarget, String> {
+        let rows = &self.rows().rows;
+        // the new version of split lines, unless they were removed
+        let line_at = |row: usize| {
+            match rows.get(row) {
+                Some(&DiffRow::Line(file, hunk, line)) => Some((file, hunk, line)),
+                Some(&DiffRow::Pair(file, hunk, pair)) => {
+                    let (old, new) = self.pairs[file][hunk][pair];
+                    Some((file, hunk, new.or(old)?))
+                },
+                _ => None,
+            }
+        };
+        let selection = self.selection();
+        let (Some((file, hunk, first)), Some(last)) = (line_at(selection.start), line_at(selection.end - 1)) else {
+            return Err(String::from("Review comments go on lines of the diff"));
+        };
+        if (file, hunk) != (last.0, last.1) {
+            return Err(String::from("Review comments can only span lines of one hunk"));
+        }
+        let lines = &self.hunks[file][hunk].lines;
+        let (side, line) = lines[last.2].position();
+        Ok(CommentTarget {
+            path: self.files[file].filename.clone(),
+            side,
+            line,
+            start: (first != last.2).then(|| lines[first].position()),
+        })
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.anchor = None;
+    }
+
+    pub fn set_threads(&mut self, threads: Vec<ReviewThread>) {
+        self.threads = threads;
+        self.relayout_threads();
+    }
+
+    pub fn add_thread(&mut self, thread: ReviewThread) {
+        self.threads.push(thread);
+        self.relayout_threads();
+    }
+
+    /// Shows the pending comments as submitted with the review they are part of.
+    pub fn submit_pending(&mut self) {
+        for thread in &mut self.threads {
+            thread.is_pending = false;
+        }
+        self.relayout_threads();
+    }
+
+    /// The number of comments in the signed in user's pending review.
+    pub fn pending_comments(&self) -> usize {
+        self.threads.iter().filter(|thread| thread.is_pending).map(|thread| thread.comments.len()).sum()
+    }
+
+    /// Renders the threads and lays the rows out again, keeping the cursor on the same line.
+    fn relayout_threads(&mut self) {
+        let (row, above) = (self.cursor_row(), self.cursor - self.scroll_offset);
+        self.thread_lines = self.threads.iter().map(Self::render_thread).collect();
+        self.layout();
+        self.place_cursor(row, above);
+    }
+
+    /// The row at the cursor, or the line above it if it is on a thread.
+    fn cursor_row(&self) -> Option<DiffRow> {
+        let rows = &self.rows().rows;
+        rows[..(self.cursor + 1).min(rows.len())].iter().rev().find(|row| !matches!(row, DiffRow::Comment(..))).copied()
+    }
+
+    /// Moves the cursor to `row`, `above` rows below the top of the view.
+    fn place_cursor(&mut self, row: Option<DiffRow>, above: usize) {
+        self.cursor = row.and_then(|row| self.rows().rows.iter().position(|&other| other == row)).unwrap_or_default();
+        self.scroll_offset = self.cursor.saturating_sub(above);
+        self.anchor = None;
+    }
+
+    fn toggle_split(&mut self) {
+        let split_requested = !self.split_requested;
+        self.relayout(|view| view.split_requested = split_requested);
+    }
+
+    fn set_width(&mut self, width: u16) {
+        self.relayout(|view| view.width = width);
+    }
+
+    /// Applies a change that may switch between the unified and the split diff, keeping the cursor on the same line and
+    /// as far from the top.
+    fn relayout(&mut self, change: impl FnOnce(&mut Self)) {
+        let was_split = self.is_split();
+        let (row, above) = (self.cursor_row(), self.cursor - self.scroll_offset);
+        change(self);
+        if self.is_split() == was_split {
+            return;
+        }
+        let row = row.map(|row| {
+            match row {
+                DiffRow::Line(file, hunk, line) => {
+                    let pair =
+                        self.pairs[file][hunk].iter().position(|&(old, new)| old == Some(line) || new == Some(line));
+                    DiffRow::Pair(file, hunk, pair.unwrap_or_default())
+                },
+                DiffRow::Pair(file, hunk, pair) => {
+                    let (old, new) = self.pairs[file][hunk][pair];
+                    DiffRow::Line(file, hunk, old.or(new).unwrap_or_default())
+                },
+                row => row,
+            }
+        });
+        self.place_cursor(row, above);
+    }
+
+    /// The `height` rows from the scroll offset on, rendering the hunks they show for the first time.
+    fn visible_rows(&mut self, height: usize) -> Vec<ShownRow> {
+        let rows = self.rows();
+        let end = (self.scroll_offset + height).min(rows.rows.len());
+        let rows = rows.rows[self.scroll_offset..end].to_vec();
+        rows.into_iter().map(|row| self.shown_row(row)).collect()
+    }
+
+    fn shown_row(&mut self, row: DiffRow) -> ShownRow {
+        let line = match row {
+            DiffRow::File(file) => Self::file_header(&self.files[file]),
+            DiffRow::NoPatch(_) => Line::styled("Binary file or diff too large to show", Style::new().fg(OVERLAY0)),
+            DiffRow::HunkHeader(file, hunk) => {
+                Line::styled(self.hunks[file][hunk].header.clone(), Style::new().fg(SAPPHIRE))
+            },
+            DiffRow::Line(file, hunk, line) => self.rendered_hunk(file, hunk).unified[line].clone(),
+            DiffRow::Pair(file, hunk, pair) => {
+                let (old, new) = self.rendered_hunk(file, hunk).split[pair].clone();
+                return ShownRow::Split(old, new);
+            },
+            DiffRow::Comment(thread, line) => self.thread_lines[thread][line].clone(),
+            DiffRow::Blank(_) => Line::default(),
+        };
+        ShownRow::Full(line)
+    }
+
+    /// The lines of a review thread: the lines it is on, then each comment with its author and when it was written.
+    fn render_thread(thread: &ReviewThread) -> Vec<Line<'static>> {
+        let (state, color) = match (thread.is_pending, thread.is_resolved) {
+            (true, _) => (" · pending", YELLOW),
+            (false, true) => (" · resolved", OVERLAY0),
+            (false, false) => ("", LAVENDER),
+        };
+        // in line with the code of unified lines
+        let border = || Span::styled(format!("{:12}┃ ", ""), Style::new().fg(color));
+        let mut lines = vec![Line::from(vec![
+            border(),
+            Span::styled(thread.target.to_string(), Style::new().fg(color).add_modifier(Modifier::BOLD)),
+            Span::styled(state, Style::new().fg(OVERLAY0)),
+        ])];
+        for comment in &thread.comments {
+            lines.push(Line::from(vec![
+                border(),
+                Span::styled(comment.author.clone(), Style::new().fg(TEXT).add_modifier(Modifier::BOLD)),
+                Span::styled(comment.created_at.format(" · %Y-%m-%d %H:%M").to_string(), Style::new().fg(OVERLAY0)),
+            ]));
+            lines.extend(
+                comment
+                    .body
+                    .lines()
+                    .map(|line| Line::from(vec![border(), Span::styled(line.to_string(), Style::new().fg(TEXT))])),
+            );
+        }
+        lines
+    }
+
+    fn rendered_hunk(&mut self, file: usize, hunk: usize) -> &RenderedHunk {
+        let (files, hunks, pairs) = (&self.files, &self.hunks, &self.pairs);
+        self.rendered
+            .entry((file, hunk))
+            .or_insert_with(|| Self::render_hunk(&files[file].filename, &hunks[file][hunk], &pairs[file][hunk]))
+    }
+
+    fn file_header(file: &ChangedFile) -> Line<'static> {
+        let status = match file.status {
+            FileStatus::Added => "added",
+            FileStatus::Removed => "deleted",
+            FileStatus::Renamed => "renamed",
+            FileStatus::Copied => "copied",
+            FileStatus::Modified | FileStatus::Changed | FileStatus::Unchanged => "modified",
+        };
+        let name = match &file.previous_filename {
+            Some(previous) => format!("{previous} → {}", file.filename),
+            None => file.filename.clone(),
+        };
+        let mut spans = vec![
+            Span::styled(format!("{status:<9}"), Style::new().fg(OVERLAY0)),
+            Span::styled(name, Style::new().fg(LAVENDER).add_modifier(Modifier::BOLD)),
+            Span::styled(format!(" +{}", file.additions), Style::new().fg(GREEN)),
+            Span::styled(format!(" -{}", file.deletions), Style::new().fg(RED)),
+        ];
+        if file.viewed {
+            spans.push(Span::styled("  ✓ viewed", Style::new().fg(OVERLAY0)));
+        }
+        Line::from(spans).style(Style::new().bg(SURFACE0))
+    }
+
+    /// The lines of a hunk with their line numbers, the code highlighted on top of a tint of the added and removed
+    /// lines, and the words that changed within paired lines emphasized. Code in languages without highlighting is
+    /// colored like the tint instead.
+    fn render_hunk(filename: &str, hunk: &Hunk, pairs: &[LinePair]) -> RenderedHunk {
+        let mut emphasized = vec![vec![]; hunk.lines.len()];
+        for &(old, new) in pairs {
+            if let (Some(old), Some(new)) = (old, new) {
+                if old != new {
+                    (emphasized[old], emphasized[new]) = word_diff(&hunk.lines[old].content, &hunk.lines[new].content);
+                }
+            }
+        }
+
+        // each version of the file is highlighted on its own, so that e.g. a comment opened on a removed line does
+        // not carry over to the added lines
+        let language = language_for(filename);
+        let (mut old_side, mut new_side) = (language.map(Highlighter::new), language.map(Highlighter::new));
+        let number = |line: Option<usize>| line.map(|n| format!("{n:>5}")).unwrap_or_else(|| " ".repeat(5));
+        let mut code = vec![];
+        let mut tints = vec![];
+        for (line, emphasized) in hunk.lines.iter().zip(&emphasized) {
+            let (prefix, color, tint, emphasis) = match line.kind {
+                LineKind::Added => ("+", GREEN, Some(GREEN_TINT), GREEN_EMPHASIS),
+                LineKind::Removed => ("-", RED, Some(RED_TINT), RED_EMPHASIS),
+                LineKind::Context => (" ", TEXT, None, SURFACE0),
+            };
+            if let (LineKind::Context, Some(highlighter)) = (line.kind, &mut old_side) {
+                // keeps the old side's state up to date, the new side's spans are shown
+                highlighter.highlight_line(&line.content);
+            }
+            let highlighter = if line.kind == LineKind::Removed { &mut old_side } else { &mut new_side };
+            let spans = match highlighter {
+                Some(highlighter) => highlighter.highlight_line(&line.content),
+                None => vec![Span::styled(line.content.clone(), Style::new().fg(color))],
+            };
+            let mut spans = emphasize(spans, emphasized, emphasis);
+            spans.insert(0, Span::styled(prefix, Style::new().fg(color)));
+            code.push(spans);
+            tints.push(tint);
+        }
+        let line = |gutter: String, code: &Vec<Span<'static>>, tint: Option<Color>| {
+            let line = Line::from([vec![Span::styled(gutter, Style::new().fg(OVERLAY0))], code.clone()].concat());
+            match tint {
+                Some(tint) => line.style(Style::new().bg(tint)),
+                None => line,
+            }
+        };
+
+        let unified = hunk
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(index, diff_line)| {
+                let gutter = format!("{} {} ", number(diff_line.old_line), number(diff_line.new_line));
+                line(gutter, &code[index], tints[index])
+            })
+            .collect();
+        let side = |index: Option<usize>, line_number: fn(&DiffLine) -> Option<usize>| {
+            match index {
+                Some(index) => {
+                    line(format!("{} ", number(line_number(&hunk.lines[index]))), &code[index], tints[index])
+                },
+                None => Line::default(),
+            }
+        };
+        let split = pairs
+            .iter()
+            .map(|&(old, new)| (side(old, |line| line.old_line), side(new, |line| line.new_line)))
+            .collect();
+        RenderedHunk { unified, split }
+    }
+
+    /// Handles the keys for the diff itself, while the tree is not focused or does not take them.
+    fn update_diff(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::FileTree => {
+                self.tree_focused = true;
+                self.tree.select_file(self.current_file());
+                return Ok(Some(Action::Render));
+            },
+            Action::Up => self.move_cursor(-1),
+            Action::Down => self.move_cursor(1),
+            Action::PageUp => self.move_cursor(-10),
+            Action::PageDn => self.move_cursor(10),
+            Action::NextTab => self.next_file(),
+            Action::PreviousTab => self.previous_file(),
+            Action::SplitDiff => self.toggle_split(),
+            Action::SelectLines => self.anchor = if self.anchor.is_some() { None } else { Some(self.cursor) },
+            _ => return Ok(None),
+        }
+        if !self.tree_focused {
+            self.tree.select_file(self.current_file());
+        }
+        Ok(Some(Action::Render))
+    }
+
+    fn draw_diff(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let [status_area, diff_area] =
+            *Layout::new(Direction::Vertical, [Constraint::Length(1), Constraint::Min(0)]).split(area)
+        else {
+            return Ok(());
+        };
+        self.height = diff_area.height;
+        self.scroll_to_cursor();
+
+        let status = match self.files.get(self.current_file()) {
+            Some(file) => format!("File {}/{}: {}", self.current_file() + 1, self.files.len(), file.filename),
+            None => String::from("No files changed"),
+        };
+        let select = if self.anchor.is_some() { "space: stop selecting" } else { "space: select" };
+        let pending = match self.pending_comments() {
+            0 => String::new(),
+            1 => String::from(" · 1 pending comment"),
+            n => format!(" · {n} pending comments"),
+        };
+        let toggle = match (self.split_requested, self.is_split()) {
+            (_, true) => String::from("v: unified"),
+            (true, false) => format!("v: unified (split needs {} columns)", self.min_split_width),
+            (false, false) => String::from("v: split"),
+        };
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(status, Style::new().fg(TEXT)),
+                Span::styled(pending, Style::new().fg(YELLOW)),
+                Span::styled(
+                    format!(
+                        "  ]/[: next/previous file · f: files · m: viewed · {select} · n: comment · {toggle} · d: \
+                         description"
+                    ),
+                    Style::new().fg(OVERLAY0),
+                ),
+            ])),
+            status_area,
+        );
+
+        let half = diff_area.width.saturating_sub(1) / 2;
+        let selection = self.selection();
+        for (index, row) in self.visible_rows(diff_area.height as usize).into_iter().enumerate() {
+            let row_area = Rect { y: diff_area.y + index as u16, height: 1, ..diff_area };
+            let row = if selection.contains(&(self.scroll_offset + index)) { row.marked() } else { row };
+            match row {
+                ShownRow::Full(line) => f.render_widget(Paragraph::new(line), row_area),
+                ShownRow::Split(old, new) => {
+                    f.render_widget(Paragraph::new(old), Rect { width: half, ..row_area });
+                    f.render_widget(Paragraph::new("│").style(Style::new().fg(SURFACE0)), Rect {
+                        x: row_area.x + half,
+                        width: 1,
+                        ..row_area
+                    });
+                    f.render_widget(Paragraph::new(new), Rect {
+                        x: row_area.x + half + 1,
+                        width: row_area.width.saturating_sub(half + 1),
+                        ..row_area
+                    });
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ShownRow {
+    /// The row with its line numbers marked as at the cursor or selected.
+    fn marked(self) -> Self {
+        let mark = |mut line: Line<'static>| {
+            if line.spans.is_empty() {
+                line.spans.push(Span::raw(" "));
+            }
+            line.spans[0].style = line.spans[0].style.fg(BASE).bg(LAVENDER);
+            line
+        };
+        match self {
+            ShownRow::Full(line) => ShownRow::Full(mark(line)),
+            ShownRow::Split(old, new) => ShownRow::Split(mark(old), mark(new)),
+        }
+    }
+}
+
+/// Sets the background of the parts of `spans` in `ranges`, byte ranges of the text they make up, splitting spans
+/// where needed.
+fn emphasize(spans: Vec<Span<'static>>, ranges: &[Range<usize>], background: Color) -> Vec<Span<'static>> {
+    if ranges.is_empty() {
+        return spans;
+    }
+    let mut emphasized = vec![];
+    let mut start = 0;
+    for span in spans {
+        let end = start + span.content.len();
+        let mut bounds: Vec<usize> = ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .filter(|&bound| start < bound && bound < end)
+            .chain([start, end])
+            .collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+        for part in bounds.windows(2) {
+            let text = span.content[part[0] - start..part[1] - start].to_string();
+            let style = if ranges.iter().any(|range| range.start <= part[0] && part[1] <= range.end) {
+                span.style.bg(background)
+            } else {
+                span.style
+            };
+            emphasized.push(Span::styled(text, style));
+        }
+        start = end;
+    }
+    emphasized
+}
+
+impl Component for DiffView {
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if self.tree_focused {
+            match action {
+                Action::Up => self.tree.select_previous(),
+                Action::Down => self.tree.select_next(),
+                Action::Left => self.tree.collapse(),
+                Action::Right => self.tree.expand(),
+                Action::FileTree => self.tree_focused = false,
+                _ => return self.update_diff(action),
+            }
+            if let Some(file) = self.tree.selected_file() {
+                self.jump_to_file(file);
+            }
+            return Ok(Some(Action::Render));
+        }
+        self.update_diff(action)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        self.set_width(f.size().width);
+        let tree_width =
+            if self.tree_focused || area.width >= MIN_TREE_VIEW_WIDTH { (area.width / 4).clamp(24, 48) } else { 0 };
+        let [tree_area, diff_area] =
+            *Layout::new(Direction::Horizontal, [Constraint::Length(tree_width), Constraint::Min(0)]).split(area)
+        else {
+            return Ok(());
+        };
+        if tree_width > 0 {
+            self.tree.draw(f, tree_area, &self.files, self.changed_files, self.tree_focused);
+        }
+        let diff_area = if tree_width > 0 {
+            Rect { x: diff_area.x + 1, width: diff_area.width.saturating_sub(1), ..diff_area }
+        } else {
+            diff_area
+        };
+        self.draw_diff(f, diff_area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::components::{
+        diff::{tests::changed_file, DiffSide},
+        pull_request::PullRequestComment,
+    };
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_jumps_between_files() {
+        let mut view = DiffView::new(vec![
+            changed_file("a.rs", Some("@@ -1,2 +1,2 @@\n-old\n+new\n same")),
+            changed_file("logo.png", None),
+            changed_file("b.rs", Some("@@ -1 +1 @@\n-old\n+new")),
+        ]);
+        // header, hunk header, 3 lines and a blank line
+        assert_eq!(view.unified.file_starts, vec![0, 6, 9]);
+
+        view.update(Action::NextTab).unwrap();
+        assert_eq!((view.current_file(), view.scroll_offset()), (1, 6));
+        view.update(Action::Down).unwrap();
+        view.update(Action::PreviousTab).unwrap();
+        assert_eq!((view.current_file(), view.scroll_offset()), (1, 6));
+        view.update(Action::PreviousTab).unwrap();
+        assert_eq!(view.current_file(), 0);
+
+        view.update(Action::PageDn).unwrap();
+        view.update(Action::PageDn).unwrap();
+        assert_eq!(view.scroll_offset(), view.unified.rows.len() - 1);
+        view.update(Action::NextTab).unwrap();
+        assert_eq!(view.current_file(), 2);
+    }
+
+    #[test]
+    fn test_hunks_are_highlighted_when_shown() {
+        let mut view = DiffView::new(vec![
+            changed_file("a.rs", Some("@@ -1 +1 @@\n-let a = 1;\n+let a = 2;")),
+            changed_file("b.rs", Some("@@ -1 +1 @@\n-old\n+new")),
+        ]);
+
+        let rows = view.visible_rows(4);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(view.rendered.keys().collect::<Vec<_>>(), vec![&(0, 0)]);
+        // the tint of the removed line stays under the highlighted keyword
+        let ShownRow::Full(removed) = &rows[2] else {
+            panic!("unified rows span the whole width");
+        };
+        assert_eq!(removed.style.bg, Some(RED_TINT));
+        assert_eq!(removed.spans[2].content, "let");
+        assert_eq!(removed.spans[2].style.fg, Some(crate::colors::MAUVE));
+        // only the changed number is emphasized
+        let emphasized: Vec<_> =
+            removed.spans.iter().filter(|span| span.style.bg == Some(RED_EMPHASIS)).map(|span| &span.content).collect();
+        assert_eq!(emphasized, vec!["1"]);
+
+        view.update(Action::NextTab).unwrap();
+        view.visible_rows(10);
+        assert_eq!(view.rendered.len(), 2);
+    }
+
+    #[test]
+    fn test_old_and_new_lines_are_highlighted_apart() {
+        let mut view = DiffView::new(vec![changed_file("a.rs", Some("@@ -1,2 +1,2 @@\n-/* old\n+let a = 1;\n x();"))]);
+        let rows = view.visible_rows(5);
+        let ShownRow::Full(added) = &rows[3] else {
+            panic!("unified rows span the whole width");
+        };
+        // the comment opened on the removed line does not cover the added one
+        assert_eq!(added.spans[2].content, "let");
+        assert_eq!(added.spans[2].style.fg, Some(crate::colors::MAUVE));
+    }
+
+    #[test]
+    fn test_split_aligns_old_and_new_lines() {
+        let mut view =
+            DiffView::new(vec![changed_file("notes.txt", Some("@@ -1,3 +1,2 @@\n same\n-gone\n-old\n+new\n end"))]);
+        view.set_width(DEFAULT_SPLIT_DIFF_MIN_WIDTH);
+        view.update(Action::Down).unwrap();
+        view.update(Action::Down).unwrap();
+        view.update(Action::Down).unwrap();
+        assert_eq!(view.scroll_offset(), 3);
+
+        // the removed line is still at the top once split
+        view.update(Action::SplitDiff).unwrap();
+        assert!(view.is_split());
+        assert_eq!(view.scroll_offset(), 3);
+        let sides: Vec<_> = view
+            .visible_rows(3)
+            .iter()
+            .map(|row| {
+                match row {
+                    ShownRow::Split(old, new) => (text(old), text(new)),
+                    ShownRow::Full(line) => (text(line), String::new()),
+                }
+            })
+            .collect();
+        assert_eq!(sides, vec![
+            (String::from("    2 -gone"), String::from("    2 +new")),
+            (String::from("    3 -old"), String::new()),
+            (String::from("    4  end"), String::from("    3  end")),
+        ]);
+
+        view.update(Action::SplitDiff).unwrap();
+        assert_eq!(view.scroll_offset(), 3);
+    }
+
+    #[test]
+    fn test_file_tree_jumps_to_the_selected_file() {
+        let mut view = DiffView::new(vec![
+            changed_file("src/b.rs", Some("@@ -1 +1 @@\n-old\n+new")),
+            changed_file("README.md", None),
+            changed_file("src/a.rs", Some("@@ -1 +1 @@\n-old\n+new")),
+        ]);
+        view.update(Action::NextTab).unwrap();
+        assert_eq!(view.viewed_target().unwrap().filename, "README.md");
+
+        // the tree starts at the file shown and lists src/a.rs before src/b.rs
+        view.update(Action::FileTree).unwrap();
+        assert_eq!(view.tree.selected_file(), Some(1));
+        view.update(Action::Up).unwrap();
+        assert_eq!((view.current_file(), view.viewed_target().unwrap().filename.as_str()), (0, "src/b.rs"));
+        view.update(Action::Up).unwrap();
+        assert_eq!(view.current_file(), 2);
+        // directories are not viewed
+        view.update(Action::Up).unwrap();
+        assert_eq!((view.current_file(), view.viewed_target()), (2, None));
+
+        view.update(Action::FileTree).unwrap();
+        view.update(Action::Down).unwrap();
+        assert_eq!(view.scroll_offset(), view.unified.file_starts[2] + 1);
+        view.set_viewed("src/a.rs", true);
+        assert!(view.viewed_target().unwrap().viewed);
+    }
+
+    #[test]
+    fn test_review_threads_are_shown_below_their_lines() {
+        let mut view = DiffView::new(vec![changed_file("a.rs", Some("@@ -1,2 +1,2 @@\n-old\n+new\n same"))]);
+        // on `same`
+        view.update(Action::Down).unwrap();
+        view.update(Action::Down).unwrap();
+        view.update(Action::Down).unwrap();
+        view.update(Action::Down).unwrap();
+        let thread = |side, line, is_pending| {
+            ReviewThread {
+                target: CommentTarget { path: String::from("a.rs"), side, line, start: None },
+                is_resolved: false,
+                is_pending,
+                comments: vec![PullRequestComment {
+                    author: String::from("octocat"),
+                    body: String::from("why?"),
+                    created_at: Default::default(),
+                }],
+            }
+        };
+        view.set_threads(vec![thread(DiffSide::Left, 1, false), thread(DiffSide::Right, 2, true)]);
+
+        // a line for what the thread is on, then the author and the body of each comment
+        assert_eq!(view.unified.rows, vec![
+            DiffRow::File(0),
+            DiffRow::HunkHeader(0, 0),
+            DiffRow::Line(0, 0, 0),
+            DiffRow::Comment(0, 0),
+            DiffRow::Comment(0, 1),
+            DiffRow::Comment(0, 2),
+            DiffRow::Line(0, 0, 1),
+            DiffRow::Line(0, 0, 2),
+            DiffRow::Comment(1, 0),
+            DiffRow::Comment(1, 1),
+            DiffRow::Comment(1, 2),
+            DiffRow::Blank(0),
+        ]);
+        assert_eq!(view.scroll_offset(), 7);
+        assert_eq!(view.pending_comments(), 1);
+        assert_eq!(
+            view.comment_target(),
+            Ok(CommentTarget { path: String::from("a.rs"), side: DiffSide::Right, line: 2, start: None })
+        );
+
+        // from the removed line to `same`
+        view.update(Action::Up).unwrap();
+        view.update(Action::Up).unwrap();
+        view.update(Action::Up).unwrap();
+        view.update(Action::Up).unwrap();
+        view.update(Action::Up).unwrap();
+        view.update(Action::SelectLines).unwrap();
+        for _ in 0..5 {
+            view.update(Action::Down).unwrap();
+        }
+        assert_eq!(view.comment_target().unwrap().to_string(), "a.rs:1-2");
+        assert_eq!(view.comment_target().unwrap().start, Some((DiffSide::Left, 1)));
+        view.update(Action::Down).unwrap();
+        assert!(view.comment_target().is_err());
+
+        view.submit_pending();
+        assert_eq!(view.pending_comments(), 0);
+    }
+
+    #[test]
+    fn test_split_falls_back_to_unified_when_narrow() {
+        let mut view =
+            DiffView::new(vec![changed_file("a.rs", Some("@@ -1 +1 @@\n-old\n+new"))]).with_min_split_width(100);
+        view.update(Action::SplitDiff).unwrap();
+        view.set_width(99);
+        assert!(!view.is_split());
+        assert!(matches!(view.visible_rows(5)[2], ShownRow::Full(_)));
+
+        view.set_width(100);
+        assert!(view.is_split());
+        assert!(matches!(view.visible_rows(5)[2], ShownRow::Split(_, _)));
+    }
+
+    #[test]
+    fn test_split_width_is_the_terminal_width() {
+        let mut view =
+            DiffView::new(vec![changed_file("a.rs", Some("@@ -1 +1 @@\n-old\n+new"))]).with_min_split_width(100);
+        view.update(Action::SplitDiff).unwrap();
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 20)).unwrap();
+        // drawn within margins, like in the overlay
+        terminal.draw(|f| view.draw(f, f.size().inner(&Margin::new(4, 4))).unwrap()).unwrap();
+        assert!(view.is_split());
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, HashSet};
+
+use ratatui::{prelude::*, widgets::*};
+
+use super::diff::{ChangedFile, FileStatus};
+use crate::{
+    colors::{BLUE, GREEN, LAVENDER, OVERLAY0, RED, SURFACE0, TEXT, YELLOW},
+    tui::Frame,
+};
+
+/// A directory of the changed files, with the directories and files in it.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Directory {
+    /// The path of the directory, e.g. `src/components`, empty for the root.
+    path: String,
+    /// The part of the path shown, several directories deep if they contain nothing but the next one, like GitHub.
+    name: String,
+    directories: Vec<Directory>,
+    /// The indices of the files in the directory.
+    files: Vec<usize>,
+}
+
+impl Directory {
+    /// Builds a directory from the paths of its files relative to it, with the indices of the files.
+    fn new(path: String, name: String, files: Vec<(&str, usize)>) -> Self {
+        let mut directories: BTreeMap<&str, Vec<(&str, usize)>> = BTreeMap::new();
+        let mut direct = vec![];
+        for (relative, index) in files {
+            match relative.split_once('/') {
+                Some((directory, rest)) => directories.entry(directory).or_default().push((rest, index)),
+                None => direct.push((relative, index)),
+            }
+        }
+        direct.sort();
+        let directories = directories
+            .into_iter()
+            .map(|(name, files)| {
+                let path = if path.is_empty() { name.to_string() } else { format!("{path}/{name}") };
+                let mut directory = Directory::new(path, name.to_string(), files);
+                if directory.files.is_empty() && directory.directories.len() == 1 {
+                    let only = directory.directories.remove(0);
+                    directory = Directory { name: format!("{}/{}", directory.name, only.name), ..only };
+                }
+                directory
+            })
+            .collect();
+        Self { path, name, directories, files: direct.into_iter().map(|(_, index)| index).collect() }
+    }
+}
+
+/// A row of the file tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeRow<'a> {
+    Directory { path: &'a str, name: &'a str, depth: usize, collapsed: bool },
+    File { file: usize, depth: usize },
+}
+
+impl TreeRow<'_> {
+    fn depth(&self) -> usize {
+        match self {
+            TreeRow::Directory { depth, .. } | TreeRow::File { depth, .. } => *depth,
+        }
+    }
+}
+
+/// The changed files of a pull request as a tree of directories that fold, directories before files and both sorted
+/// by name.
+#[derive(Debug, Default)]
+pub struct FileTree {
+    root: Directory,
+    /// The path of each file.
+    paths: Vec<String>,
+    /// The paths of the folded directories.
+    collapsed: HashSet<String>,
+    /// The index of the selected row.
+    selected: usize,
+}
+
+impl FileTree {
+    pub fn new(files: &[ChangedFile]) -> Self {
+        let root = Directory::new(
+            String::new(),
+            String::new(),
+            files.iter().enumerate().map(|(index, file)| (file.filename.as_str(), index)).collect(),
+        );
+        Self { root, paths: files.iter().map(|file| file.filename.clone()).collect(), ..Self::default() }
+    }
+
+    /// The rows of the directories and files that are not folded away.
+    pub fn rows(&self) -> Vec<TreeRow<'_>> {
+        let mut rows = vec![];
+        self.push_rows(&self.root, 0, &mut rows);
+        rows
+    }
+
+    fn push_rows<'a>(&'a self, directory: &'a Directory, depth: usize, rows: &mut Vec<TreeRow<'a>>) {
+        for child in &directory.directories {
+            let collapsed = self.collapsed.contains(&child.path);
+            rows.push(TreeRow::Directory { path: &child.path, name: &child.name, depth, collapsed });
+            if !collapsed {
+                self.push_rows(child, depth + 1, rows);
+            }
+        }
+        rows.extend(directory.files.iter().map(|&file| TreeRow::File { file, depth }));
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// The index of the selected file, `None` if a directory is selected.
+    pub fn selected_file(&self) -> Option<usize> {
+        match self.rows().get(self.selected) {
+            Some(TreeRow::File { file, .. }) => Some(*file),
+            _ => None,
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.rows().len().saturating_sub(1));
+    }
+
+    /// Selects the row of a file, or of the folded directory it is in.
+    pub fn select_file(&mut self, file: usize) {
+        let Some(path) = self.paths.get(file) else {
+            return;
+        };
+        let position = self.rows().iter().position(|row| {
+            match row {
+                TreeRow::File { file: index, .. } => *index == file,
+                TreeRow::Directory { path: directory, collapsed, .. } => {
+                    *collapsed && path.strip_prefix(directory).is_some_and(|rest| rest.starts_with('/'))
+                },
+            }
+        });
+        if let Some(position) = position {
+            self.selected = position;
+        }
+    }
+
+    /// Folds the selected directory, or selects the directory the selected row is in.
+    pub fn collapse(&mut self) {
+        let rows = self.rows();
+        let Some(&row) = rows.get(self.selected) else {
+            return;
+        };
+        if let TreeRow::Directory { path, collapsed: false, .. } = row {
+            self.collapsed.insert(path.to_string());
+            return;
+        }
+        if let Some(parent) = rows[..self.selected].iter().rposition(|parent| parent.depth() < row.depth()) {
+            self.selected = parent;
+        }
+    }
+
+    /// Unfolds the selected directory.
+    pub fn expand(&mut self) {
+        if let Some(TreeRow::Directory { path, collapsed: true, .. }) = self.rows().get(self.selected) {
+            let path = path.to_string();
+            self.collapsed.remove(&path);
+        }
+    }
+
+    /// Draws the tree with the status, counts and viewed state of each file, and how many of the `changed_files` of
+    /// the pull request are viewed.
+    pub fn draw(&self, f: &mut Frame<'_>, area: Rect, files: &[ChangedFile], changed_files: usize, focused: bool) {
+        let viewed = files.iter().filter(|file| file.viewed).count();
+        let changed_files = changed_files.max(files.len());
+        let mut title = vec![Span::styled(format!(" Viewed {viewed}/{changed_files} "), Style::new().fg(TEXT))];
+        // the REST API lists at most 3000 files
+        if files.len() < changed_files {
+            title.push(Span::styled(format!("{} not listed ", changed_files - files.len()), Style::new().fg(OVERLAY0)));
+        }
+        let block = Block::default()
+            .borders(Borders::RIGHT)
+            .border_style(Style::new().fg(if focused { LAVENDER } else { SURFACE0 }))
+            .title(Line::from(title));
+
+        let items: Vec<ListItem> = self
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let indent = "  ".repeat(row.depth());
+                let line = match row {
+                    TreeRow::Directory { name, collapsed, .. } => {
+                        Line::styled(
+                            format!("{indent}{} {name}/", if collapsed { "▸" } else { "▾" }),
+                            Style::new().fg(LAVENDER),
+                        )
+                    },
+                    TreeRow::File { file, .. } => {
+                        let file = &files[file];
+                        let (status, color) = match file.status {
+                            FileStatus::Added | FileStatus::Copied => ("A", GREEN),
+                            FileStatus::Removed => ("D", RED),
+                            FileStatus::Renamed => ("R", BLUE),
+                            FileStatus::Modified | FileStatus::Changed | FileStatus::Unchanged => ("M", YELLOW),
+                        };
+                        let name = file.filename.rsplit('/').next().unwrap_or_default();
+                        let name_style = if file.viewed { Style::new().fg(OVERLAY0) } else { Style::new().fg(TEXT) };
+                        Line::from(vec![
+                            Span::raw(indent),
+                            Span::styled(if file.viewed { "✓ " } else { "  " }, Style::new().fg(GREEN)),
+                            Span::styled(format!("{status} "), Style::new().fg(color)),
+                            Span::styled(name.to_string(), name_style),
+                            Span::styled(format!(" +{}", file.additions), Style::new().fg(GREEN)),
+                            Span::styled(format!(" -{}", file.deletions), Style::new().fg(RED)),
+                        ])
+                    },
+                };
+                ListItem::new(line)
+            })
+            .collect();
+        let highlight = if focused { Style::new().bg(SURFACE0).add_modifier(Modifier::BOLD) } else { Style::new() };
+        let list = List::new(items).block(block).highlight_style(highlight);
+        f.render_stateful_widget(list, area, &mut ListState::default().with_selected(Some(self.selected)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::components::diff::tests::changed_file;
+
+    #[test]
+    fn test_directories_come_first_and_single_children_are_merged() {
+        let tree = FileTree::new(&[
+            changed_file("README.md", None),
+            changed_file("src/components/list.rs", None),
+            changed_file("src/components/diff/view.rs", None),
+            changed_file("src/main.rs", None),
+            changed_file("docs/guide/intro.md", None),
+        ]);
+        assert_eq!(tree.rows(), vec![
+            TreeRow::Directory { path: "docs/guide", name: "docs/guide", depth: 0, collapsed: false },
+            TreeRow::File { file: 4, depth: 1 },
+            TreeRow::Directory { path: "src", name: "src", depth: 0, collapsed: false },
+            TreeRow::Directory { path: "src/components", name: "components", depth: 1, collapsed: false },
+            TreeRow::Directory { path: "src/components/diff", name: "diff", depth: 2, collapsed: false },
+            TreeRow::File { file: 2, depth: 3 },
+            TreeRow::File { file: 1, depth: 2 },
+            TreeRow::File { file: 3, depth: 1 },
+            TreeRow::File { file: 0, depth: 0 },
+        ]);
+    }
+
+    #[test]
+    fn test_directories_fold() {
+        let mut tree = FileTree::new(&[
+            changed_file("src/a.rs", None),
+            changed_file("src/b.rs", None),
+            changed_file("README.md", None),
+        ]);
+        tree.select_file(1);
+        assert_eq!(tree.selected(), 2);
+
+        // from a file to its directory, which then folds
+        tree.collapse();
+        assert_eq!((tree.selected(), tree.selected_file()), (0, None));
+        tree.collapse();
+        assert_eq!(tree.rows().len(), 2);
+        tree.select_file(1);
+        assert_eq!(tree.selected(), 0);
+
+        tree.expand();
+        tree.select_next();
+        assert_eq!(tree.selected_file(), Some(0));
+    }
+}
@@ -0,0 +1,117 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::Component;
+use crate::{
+    action::Action,
+    colors::{OVERLAY0, PEACH, TEXT},
+    tui::Frame,
+};
+
+/// The `/` input that filters the pull request table as you type.
+///
+/// Like the [`super::review_prompt::ReviewPrompt`], it relies on the owning component to switch the app into
+/// [`crate::mode::Mode::Search`] while editing, so that typed characters are not interpreted as keybindings. The query
+/// stays applied after editing is done, until it is cleared.
+#[derive(Debug, Default)]
+pub struct FilterBar {
+    query: String,
+    is_editing: bool,
+}
+
+impl FilterBar {
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.is_editing
+    }
+
+    /// Whether the bar is shown: while editing, or while a query is applied.
+    pub fn is_active(&self) -> bool {
+        self.is_editing || !self.query.is_empty()
+    }
+
+    pub fn start_editing(&mut self) {
+        self.is_editing = true;
+    }
+
+    pub fn stop_editing(&mut self) {
+        self.is_editing = false;
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.is_editing = false;
+    }
+}
+
+impl Component for FilterBar {
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_editing {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let kept = self.query.trim_end().rfind(' ').map_or(0, |space| space + 1);
+                self.query.truncate(kept);
+            },
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.query.push(c),
+            KeyCode::Backspace => {
+                self.query.pop();
+            },
+            _ => return Ok(None),
+        }
+        Ok(Some(Action::Render))
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let mut spans = vec![Span::styled("/", Style::new().fg(PEACH).add_modifier(Modifier::BOLD))];
+        spans.push(Span::styled(self.query.clone(), Style::new().fg(TEXT)));
+        if self.is_editing {
+            spans.push(Span::styled("_", Style::new().fg(TEXT)));
+        }
+        let hint = if self.is_editing { "enter: keep · esc: clear" } else { "/: edit · esc: clear" };
+        let [input, hints] =
+            *Layout::new(Direction::Horizontal, [Constraint::Min(1), Constraint::Length(hint.chars().count() as u16)])
+                .split(area)
+        else {
+            return Ok(());
+        };
+        f.render_widget(Paragraph::new(Line::from(spans)), input);
+        f.render_widget(Paragraph::new(hint).style(Style::new().fg(OVERLAY0)).alignment(Alignment::Right), hints);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_typing_edits_the_query() {
+        let mut bar = FilterBar::default();
+        assert_eq!(bar.handle_key_events(key(KeyCode::Char('j'))).unwrap(), None);
+        assert!(!bar.is_active());
+
+        bar.start_editing();
+        for c in "ghtui tabz".chars() {
+            bar.handle_key_events(key(KeyCode::Char(c))).unwrap();
+        }
+        bar.handle_key_events(key(KeyCode::Backspace)).unwrap();
+        assert_eq!(bar.query(), "ghtui tab");
+        bar.handle_key_events(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL)).unwrap();
+        assert_eq!(bar.query(), "ghtui ");
+
+        bar.stop_editing();
+        assert!(bar.is_active());
+        bar.clear();
+        assert!(!bar.is_active());
+    }
+}
@@ -0,0 +1,158 @@
+use super::pull_request::PullRequest;
+
+/// Where a pattern matched a text: the indices of the matched chars, and how well it matched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: usize,
+    pub indices: Vec<usize>,
+}
+
+/// Matches the chars of `pattern` in order, but not necessarily next to each other, ignoring case. Consecutive
+/// chars and chars at the start of a word score higher, so `fix` prefers `Fix pagination` over `fancy mixin`.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().collect();
+    let first = *pattern.first()?;
+    (0..text.len())
+        .filter(|&start| lowercase(text[start]) == first)
+        .filter_map(|start| match_from(&pattern, &text, start))
+        // the leftmost of the best matches
+        .fold(None, |best: Option<FuzzyMatch>, m| {
+            match best {
+                Some(best) if best.score >= m.score => Some(best),
+                _ => Some(m),
+            }
+        })
+}
+
+fn match_from(pattern: &[char], text: &[char], start: usize) -> Option<FuzzyMatch> {
+    let mut indices = Vec::with_capacity(pattern.len());
+    let mut chars = pattern.iter().peekable();
+    for (index, c) in text.iter().enumerate().skip(start) {
+        if chars.next_if(|&&p| p == lowercase(*c)).is_some() {
+            indices.push(index);
+        }
+    }
+    if chars.peek().is_some() {
+        return None;
+    }
+    let score = indices
+        .iter()
+        .enumerate()
+        .map(|(i, &index)| {
+            let consecutive = i > 0 && indices[i - 1] + 1 == index;
+            let word_start = index == 0 || !text[index - 1].is_alphanumeric();
+            1 + 2 * usize::from(consecutive) + 3 * usize::from(word_start)
+        })
+        .sum();
+    Some(FuzzyMatch { score, indices })
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Narrows pull requests down to those matching every whitespace separated term of a query, e.g. `ghtui tabs`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PullRequestFilter {
+    terms: Vec<String>,
+}
+
+/// The chars matched by a [`PullRequestFilter`] in each of the fields it searches, to highlight them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PullRequestMatch {
+    pub number: Vec<usize>,
+    pub repository: Vec<usize>,
+    pub title: Vec<usize>,
+    pub author: Vec<usize>,
+}
+
+impl PullRequestFilter {
+    pub fn new(query: &str) -> Self {
+        Self { terms: query.split_whitespace().map(String::from).collect() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Each term may match the number, repository, title or author; the field it matches best is highlighted.
+    pub fn matches(&self, pull_request: &PullRequest) -> Option<PullRequestMatch> {
+        let number = pull_request.number.to_string();
+        let mut highlights = PullRequestMatch::default();
+        for term in &self.terms {
+            let fields = [
+                (fuzzy_match(term.strip_prefix('#').unwrap_or(term), &number), &mut highlights.number),
+                (fuzzy_match(term, &pull_request.repository), &mut highlights.repository),
+                (fuzzy_match(term, &pull_request.title), &mut highlights.title),
+                (fuzzy_match(term, &pull_request.author), &mut highlights.author),
+            ];
+            let (best, indices) = fields.into_iter().filter_map(|(m, indices)| Some((m?, indices))).fold(
+                None,
+                |best: Option<(FuzzyMatch, &mut Vec<usize>)>, (m, indices)| {
+                    match best {
+                        Some(best) if best.0.score >= m.score => Some(best),
+                        _ => Some((m, indices)),
+                    }
+                },
+            )?;
+            indices.extend(best.indices);
+        }
+        for indices in
+            [&mut highlights.number, &mut highlights.repository, &mut highlights.title, &mut highlights.author]
+        {
+            indices.sort_unstable();
+            indices.dedup();
+        }
+        Some(highlights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::components::pull_request::tests::pull_request;
+
+    #[rstest]
+    #[case("fix", "Fix pagination", Some(vec![0, 1, 2]))]
+    #[case("fp", "Fix pagination", Some(vec![0, 4]))]
+    #[case("PAG", "Fix pagination", Some(vec![4, 5, 6]))]
+    #[case("tabs", "a table of tabs", Some(vec![11, 12, 13, 14]))]
+    #[case("xf", "Fix pagination", None)]
+    #[case("", "Fix pagination", None)]
+    fn test_fuzzy_match(#[case] pattern: &str, #[case] text: &str, #[case] expected: Option<Vec<usize>>) {
+        assert_eq!(fuzzy_match(pattern, text).map(|m| m.indices), expected);
+    }
+
+    #[test]
+    fn test_word_starts_score_higher() {
+        let word_start = fuzzy_match("fix", "Fix pagination").unwrap();
+        let scattered = fuzzy_match("fix", "fancy mixin").unwrap();
+        assert!(word_start.score > scattered.score);
+    }
+
+    #[test]
+    fn test_every_term_must_match() {
+        let mut pr = pull_request("owner/ghtui", 42);
+        pr.title = String::from("Add tabs");
+        pr.author = String::from("octocat");
+
+        assert_eq!(
+            PullRequestFilter::new("ghtui tabs").matches(&pr),
+            Some(PullRequestMatch {
+                repository: vec![6, 7, 8, 9, 10],
+                title: vec![4, 5, 6, 7],
+                ..PullRequestMatch::default()
+            })
+        );
+        assert_eq!(
+            PullRequestFilter::new("#42 octo").matches(&pr),
+            Some(PullRequestMatch { number: vec![0, 1], author: vec![0, 1, 2, 3], ..PullRequestMatch::default() })
+        );
+        assert_eq!(PullRequestFilter::new("ghtui hubot").matches(&pr), None);
+        assert_eq!(PullRequestFilter::new("").matches(&pr), Some(PullRequestMatch::default()));
+    }
+}
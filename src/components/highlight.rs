@@ -0,0 +1,148 @@
+use std::sync::OnceLock;
+
+use ratatui::{
+    style::{Color, Style},
+    text::Span,
+};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{self, StyleModifier, Theme, ThemeItem, ThemeSettings},
+    parsing::{SyntaxReference, SyntaxSet},
+};
+
+use crate::colors::{BLUE, GREEN, MAUVE, OVERLAY0, PEACH, RED, SAPPHIRE, TEAL, TEXT, YELLOW};
+
+/// The syntaxes of syntect and those bat adds, e.g. TypeScript and TOML, loaded the first time a diff is shown.
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(two_face::syntax::extra_newlines)
+}
+
+/// Catppuccin mocha, like the rest of the UI. Only the foreground is set, leaving the background to the tints of
+/// added and removed lines.
+fn theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(|| {
+        let item = |scopes: &str, color: Color| {
+            ThemeItem {
+                scope: scopes.parse().expect("valid scope selectors"),
+                style: StyleModifier { foreground: Some(to_syntect(color)), background: None, font_style: None },
+            }
+        };
+        Theme {
+            name: Some(String::from("Catppuccin Mocha")),
+            author: None,
+            settings: ThemeSettings { foreground: Some(to_syntect(TEXT)), ..ThemeSettings::default() },
+            scopes: vec![
+                item("comment, punctuation.definition.comment", OVERLAY0),
+                item("string, punctuation.definition.string", GREEN),
+                item("constant.character.escape, string.regexp", PEACH),
+                item("constant.numeric, constant.language", PEACH),
+                item("keyword, storage, variable.language", MAUVE),
+                item("keyword.operator", SAPPHIRE),
+                item("entity.name.function, support.function, variable.function", BLUE),
+                item("entity.name.type, entity.name.class, entity.name.struct, entity.name.enum, support.type", YELLOW),
+                item("support.class, entity.other.inherited-class, storage.type.primitive", YELLOW),
+                item("entity.name.tag, meta.mapping.key string, support.type.property-name", BLUE),
+                item("entity.other.attribute-name, variable.parameter", TEAL),
+                item("markup.heading", RED),
+            ],
+        }
+    })
+}
+
+fn to_syntect(color: Color) -> highlighting::Color {
+    match color {
+        Color::Rgb(r, g, b) => highlighting::Color { r, g, b, a: 0xff },
+        _ => highlighting::Color::WHITE,
+    }
+}
+
+/// The syntax of a file, by its extension or, for files like `Makefile`, its name. `None` for plain text.
+pub fn language_for(filename: &str) -> Option<&'static SyntaxReference> {
+    let name = filename.rsplit('/').next().unwrap_or(filename);
+    let extension = name.rsplit_once('.').map_or(name, |(_, extension)| extension);
+    syntaxes()
+        .find_syntax_by_extension(extension)
+        .or_else(|| syntaxes().find_syntax_by_extension(name))
+        .filter(|syntax| syntax.name != "Plain Text")
+}
+
+/// Highlights the lines of a file one after the other, remembering e.g. whether a block comment is still open.
+pub struct Highlighter {
+    lines: HighlightLines<'static>,
+}
+
+impl Highlighter {
+    pub fn new(syntax: &'static SyntaxReference) -> Self {
+        Self { lines: HighlightLines::new(syntax, theme()) }
+    }
+
+    /// The line split into spans with a foreground color each, leaving the background to the caller.
+    pub fn highlight_line(&mut self, line: &str) -> Vec<Span<'static>> {
+        // the syntaxes are loaded for lines that end with a newline
+        let line = format!("{line}\n");
+        let Ok(ranges) = self.lines.highlight_line(&line, syntaxes()) else {
+            return vec![Span::styled(line.trim_end_matches('\n').to_string(), Style::new().fg(TEXT))];
+        };
+        let mut spans: Vec<Span<'static>> = vec![];
+        for (style, text) in ranges {
+            let text = text.strip_suffix('\n').unwrap_or(text);
+            let color = Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
+            match spans.last_mut() {
+                Some(last) if last.style.fg == Some(color) => last.content.to_mut().push_str(text),
+                _ if text.is_empty() => {},
+                _ => spans.push(Span::styled(text.to_string(), Style::new().fg(color))),
+            }
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn tokens(highlighter: &mut Highlighter, line: &str) -> Vec<(String, Color)> {
+        highlighter
+            .highlight_line(line)
+            .into_iter()
+            .map(|span| (span.content.into_owned(), span.style.fg.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_language_by_extension_or_name() {
+        assert_eq!(language_for("src/main.rs").unwrap().name, "Rust");
+        assert_eq!(language_for("web/app.test.tsx").unwrap().name, "TypeScriptReact");
+        assert_eq!(language_for("Cargo.toml").unwrap().name, "TOML");
+        assert_eq!(language_for("build/Makefile").unwrap().name, "Makefile");
+        assert!(language_for("notes.txt").is_none());
+    }
+
+    #[test]
+    fn test_highlight_line() {
+        let mut highlighter = Highlighter::new(language_for("main.rs").unwrap());
+        let tokens = tokens(&mut highlighter, r#"fn answer() -> u32 { let s = "a \"b\""; 42 } // done"#);
+        assert_eq!(
+            tokens.iter().map(|(text, _)| text.as_str()).collect::<String>(),
+            r#"fn answer() -> u32 { let s = "a \"b\""; 42 } // done"#
+        );
+        for token in [("fn", MAUVE), ("answer", BLUE), ("let", MAUVE), ("42", PEACH), ("// done", OVERLAY0)] {
+            assert!(tokens.contains(&(token.0.to_string(), token.1)), "{token:?} in {tokens:?}");
+        }
+    }
+
+    #[test]
+    fn test_block_comments_span_lines() {
+        let mut highlighter = Highlighter::new(language_for("main.go").unwrap());
+        assert_eq!(tokens(&mut highlighter, "x /* start").last(), Some(&(String::from("/* start"), OVERLAY0)));
+        assert_eq!(tokens(&mut highlighter, "end */ return"), vec![
+            (String::from("end */"), OVERLAY0),
+            (String::from(" "), TEXT),
+            (String::from("return"), MAUVE),
+        ]);
+    }
+}
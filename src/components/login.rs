@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::error;
+
+use super::{notifications::Notification, Component};
+use crate::{
+    action::Action,
+    colors::{OVERLAY0, PEACH, RED, TEXT},
+    config::Config,
+    github::{
+        auth::{DeviceFlow, TokenStore},
+        traits::GithubClient,
+    },
+    tui::Frame,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LoginState {
+    Idle,
+    Starting,
+    WaitingForUser { user_code: String, verification_uri: String },
+    Failed(String),
+}
+
+/// Device flow login offered while the client has no token.
+///
+/// The token is stored in the data directory and handed to the client, after which the owning list receives
+/// [`Action::LoginCompleted`] and can load its pull requests.
+pub struct Login<C: GithubClient> {
+    client: Arc<C>,
+    command_tx: Option<UnboundedSender<Action>>,
+    oauth_client_id: Option<String>,
+    token_store: Option<Arc<TokenStore>>,
+    state: LoginState,
+}
+
+impl<C: GithubClient> Login<C> {
+    pub fn new(client: Arc<C>, oauth_client_id: Option<String>) -> Self {
+        Self { client, command_tx: None, oauth_client_id, token_store: None, state: LoginState::Idle }
+    }
+
+    fn start(&mut self) -> Result<()> {
+        let (Some(client_id), Some(tx)) = (self.oauth_client_id.clone(), self.command_tx.clone()) else {
+            return Ok(());
+        };
+        if matches!(self.state, LoginState::Starting | LoginState::WaitingForUser { .. }) {
+            return Ok(());
+        }
+        self.state = LoginState::Starting;
+        let client = self.client.clone();
+        let token_store = self.token_store.clone();
+        tokio::spawn(async move {
+            let result = async {
+                let flow = DeviceFlow::new(client.host(), &client_id)?;
+                let details = flow.start().await?;
+                let _ = tx.send(Action::LoginCode(
+                    details.user_code().secret().clone(),
+                    details.verification_uri().to_string(),
+                ));
+                flow.poll(&details).await
+            }
+            .await;
+            let token = match result {
+                Ok(token) => token,
+                Err(err) => {
+                    error!("Device login failed: {:?}", err);
+                    let _ = tx.send(Action::LoginFailed(err.to_string()));
+                    return;
+                },
+            };
+            if let Some(store) = token_store {
+                if let Err(err) = store.save(client.host(), &token) {
+                    error!("Failed to store token: {:?}", err);
+                    let _ = tx.send(Action::Notify(Notification::Warning(format!("Could not save the token: {err}"))));
+                }
+            }
+            match client.authenticate(token) {
+                Ok(()) => {
+                    let _ = tx.send(Action::LoginCompleted);
+                },
+                Err(err) => {
+                    let _ = tx.send(Action::LoginFailed(err.to_string()));
+                },
+            }
+        });
+        Ok(())
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let hint = |text: String| Line::styled(text, Style::default().fg(OVERLAY0));
+        if self.oauth_client_id.is_none() {
+            return vec![hint(String::from(
+                "Set `oauth_client_id` in the config file to log in with your browser instead",
+            ))];
+        }
+        match &self.state {
+            LoginState::Idle => {
+                vec![Line::styled("Press 'enter' to log in with your browser", Style::default().fg(TEXT))]
+            },
+            LoginState::Starting => vec![hint(String::from("Requesting a login code..."))],
+            LoginState::WaitingForUser { user_code, verification_uri } => {
+                vec![
+                    Line::from(vec![
+                        Span::styled("Enter the code ", Style::default().fg(TEXT)),
+                        Span::styled(user_code.clone(), Style::default().fg(PEACH).add_modifier(Modifier::BOLD)),
+                        Span::styled(format!(" at {verification_uri}"), Style::default().fg(TEXT)),
+                    ]),
+                    hint(String::from("Press 'o' to open the page in your browser. Waiting for authorization...")),
+                ]
+            },
+            LoginState::Failed(message) => {
+                vec![
+                    Line::styled(format!("Login failed: {message}"), Style::default().fg(RED)),
+                    hint(String::from("Press 'enter' to try again")),
+                ]
+            },
+        }
+    }
+}
+
+impl<C: GithubClient> Component for Login<C> {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.token_store = Some(Arc::new(TokenStore::new(&config.config._data_dir)));
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Enter => self.start()?,
+            Action::Open => {
+                if let LoginState::WaitingForUser { verification_uri, .. } = &self.state {
+                    let _ = open::that(verification_uri);
+                }
+            },
+            Action::LoginCode(user_code, verification_uri) => {
+                self.state = LoginState::WaitingForUser { user_code, verification_uri };
+            },
+            Action::LoginFailed(message) => self.state = LoginState::Failed(message),
+            Action::LoginCompleted => self.state = LoginState::Idle,
+            _ => return Ok(None),
+        }
+        Ok(Some(Action::Render))
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        f.render_widget(Paragraph::new(self.lines()).alignment(Alignment::Center), area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::github::{auth::tests::device_flow_server, fake::FakeGithubClient};
+
+    #[tokio::test]
+    async fn test_login_stores_token_and_authenticates_client() {
+        let host = device_flow_server(1).await;
+        let client = FakeGithubClient::new("octocat").with_host(host.clone()).unauthenticated().into_arc();
+        let data_dir = std::env::temp_dir().join(format!("ghtui-login-{}", std::process::id()));
+        let mut config = Config::default();
+        config.config._data_dir = data_dir.clone();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut login = Login::new(client.clone(), Some(String::from("client-id")));
+        login.register_action_handler(tx).unwrap();
+        login.register_config_handler(config).unwrap();
+
+        login.update(Action::Enter).unwrap();
+        assert_eq!(login.state, LoginState::Starting);
+        loop {
+            let action = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+            let done = action == Action::LoginCompleted;
+            login.update(action).unwrap();
+            if done {
+                break;
+            }
+            assert_eq!(login.state, LoginState::WaitingForUser {
+                user_code: String::from("ABCD-1234"),
+                verification_uri: String::from("https://github.com/login/device"),
+            });
+        }
+
+        assert!(client.is_authenticated());
+        assert_eq!(TokenStore::new(&data_dir).load(&host), Some(String::from("gho_test")));
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_login_requires_client_id() {
+        let mut login = Login::new(FakeGithubClient::new("octocat").unauthenticated().into_arc(), None);
+        login.update(Action::Enter).unwrap();
+        assert_eq!(login.state, LoginState::Idle);
+    }
+}
@@ -13,6 +13,7 @@ use super::Component;
 use crate::{
     action::Action,
     colors::{BASE, BLUE, PINK, YELLOW},
+    github::retry::RetryAttempt,
     tui::Frame,
 };
 
@@ -32,6 +33,12 @@ pub enum Notification {
     Error(String),
 }
 
+impl From<&RetryAttempt> for Notification {
+    fn from(attempt: &RetryAttempt) -> Self {
+        Notification::Warning(attempt.to_string())
+    }
+}
+
 impl Notifications {
     fn app_tick(&mut self) -> Result<()> {
         let now = Instant::now();
@@ -0,0 +1,439 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tracing::info;
+
+use super::{pull_request_list::PullRequestList, utils::centered_rect, Component};
+use crate::{
+    action::Action,
+    colors::{BASE, OVERLAY0, PEACH, ROSEWATER, SURFACE0, TEXT},
+    config::{Config, Profile},
+    github::{token::resolve_token, traits::GithubClient},
+    tui::Frame,
+};
+
+struct ProfileEntry<C: GithubClient> {
+    profile: Profile,
+    pull_request_list: PullRequestList<C>,
+    initialized: bool,
+}
+
+/// Keeps a [`PullRequestList`] per profile and forwards actions to the active one.
+///
+/// Each list gets its own action channel whose actions are tagged with [`Action::ForProfile`], so results of requests
+/// started by a profile end up in that profile's list even if the user switched away in the meantime. Only app-level
+/// actions like errors, notifications and mode switches are sent back untagged.
+pub struct Profiles<C: GithubClient> {
+    command_tx: Option<UnboundedSender<Action>>,
+    entries: Vec<ProfileEntry<C>>,
+    active: usize,
+    picker: Option<ListState>,
+    area: Rect,
+    /// Where the tokens of profiles first switched to are looked up, if they were not resolved up front.
+    data_dir: Option<PathBuf>,
+}
+
+impl<C: GithubClient> Profiles<C> {
+    pub fn new(profiles: Vec<(Profile, PullRequestList<C>)>, active: usize) -> Self {
+        let entries = profiles
+            .into_iter()
+            .map(|(profile, pull_request_list)| ProfileEntry { profile, pull_request_list, initialized: false })
+            .collect::<Vec<_>>();
+        Self {
+            command_tx: None,
+            active: active.min(entries.len().saturating_sub(1)),
+            entries,
+            picker: None,
+            area: Rect::default(),
+            data_dir: None,
+        }
+    }
+
+    /// Resolves the token of a profile other than the active one only once it is switched to, so that a slow or
+    /// interactive `token_command` does not hold up the start for profiles that are never opened.
+    pub fn with_lazy_tokens(mut self, data_dir: PathBuf) -> Self {
+        self.data_dir = Some(data_dir);
+        self
+    }
+
+    pub fn active_profile(&self) -> &Profile {
+        &self.entries[self.active].profile
+    }
+
+    pub fn pull_request_list(&self, name: &str) -> Option<&PullRequestList<C>> {
+        self.entries.iter().find(|entry| entry.profile.name == name).map(|entry| &entry.pull_request_list)
+    }
+
+    fn active_list(&mut self) -> &mut PullRequestList<C> {
+        &mut self.entries[self.active].pull_request_list
+    }
+
+    fn switch_to(&mut self, index: usize) -> Result<()> {
+        if index >= self.entries.len() || index == self.active {
+            return Ok(());
+        }
+        self.active = index;
+        let entry = &mut self.entries[index];
+        if !entry.initialized {
+            entry.initialized = true;
+            if let Some(data_dir) = &self.data_dir {
+                let token = resolve_token(&entry.profile, data_dir).map(|(token, source)| {
+                    info!("Using token from {source} for profile {}", entry.profile.name);
+                    token
+                });
+                entry.pull_request_list.set_token(token)?;
+            }
+            entry.pull_request_list.init(self.area)?;
+        }
+        Ok(())
+    }
+
+    /// Handles actions while the picker is open. Returns `None` if the action should be processed as usual.
+    fn update_picker(&mut self, action: &Action) -> Result<Option<Option<Action>>> {
+        let Some(picker) = &mut self.picker else {
+            return Ok(None);
+        };
+        let selected = picker.selected().unwrap_or(self.active);
+        match action {
+            Action::Up => picker.select(Some(selected.saturating_sub(1))),
+            Action::Down => picker.select(Some((selected + 1).min(self.entries.len() - 1))),
+            Action::Enter => {
+                self.picker = None;
+                self.switch_to(selected)?;
+            },
+            Action::Escape | Action::Back | Action::ProfilePicker => self.picker = None,
+            Action::Left | Action::Right | Action::PageUp | Action::PageDn | Action::Info | Action::Open => {},
+            _ => return Ok(None),
+        }
+        Ok(Some(Some(Action::Render)))
+    }
+
+    fn render_picker(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let Some(picker) = &mut self.picker else {
+            return;
+        };
+        let items = self
+            .entries
+            .iter()
+            .map(|entry| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(entry.profile.name.clone(), Style::default().fg(TEXT)),
+                    Span::styled(format!(" ({})", entry.profile.host), Style::default().fg(OVERLAY0)),
+                ]))
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(" Profiles ")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(ROSEWATER))
+                    .bg(BASE),
+            )
+            .highlight_style(Style::new().bg(SURFACE0).fg(TEXT).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+        let area = centered_rect(area, 40, 40);
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, picker);
+    }
+}
+
+impl<C: GithubClient> Component for Profiles<C> {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            let (profile_tx, mut profile_rx) = mpsc::unbounded_channel();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                while let Some(action) = profile_rx.recv().await {
+                    if tx.send(Action::ForProfile(index, Box::new(action))).is_err() {
+                        break;
+                    }
+                }
+            });
+            entry.pull_request_list.register_action_handler(profile_tx)?;
+        }
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        for entry in self.entries.iter_mut() {
+            entry.pull_request_list.register_config_handler(config.clone())?;
+        }
+        Ok(())
+    }
+
+    fn init(&mut self, area: Rect) -> Result<()> {
+        self.area = area;
+        let entry = &mut self.entries[self.active];
+        entry.initialized = true;
+        entry.pull_request_list.init(area)
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if self.picker.is_some() {
+            return Ok(None);
+        }
+        self.active_list().handle_key_events(key)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ForProfile(index, action) => {
+                match *action {
+                    Action::Error(_) | Action::Notify(_) | Action::Render | Action::SwitchMode(_) => {
+                        return Ok(Some(*action))
+                    },
+                    action => {
+                        // handled here rather than replayed untagged, so a switch in between cannot hand it to
+                        // another profile's list
+                        if let Some(entry) = self.entries.get_mut(index) {
+                            let result = entry.pull_request_list.update(action)?;
+                            if index == self.active {
+                                return Ok(result);
+                            }
+                        }
+                    },
+                }
+                return Ok(None);
+            },
+            Action::ProfilePicker if self.picker.is_none() && self.entries.len() > 1 => {
+                self.picker = Some(ListState::default().with_selected(Some(self.active)));
+                return Ok(Some(Action::Render));
+            },
+            Action::SwitchProfile(ref name) => {
+                if let Some(index) = self.entries.iter().position(|entry| &entry.profile.name == name) {
+                    self.switch_to(index)?;
+                } else if let Some(tx) = &self.command_tx {
+                    tx.send(Action::Error(format!("Unknown profile {name}")))?;
+                }
+                return Ok(Some(Action::Render));
+            },
+            Action::Resize(width, height) => self.area = Rect::new(0, 0, width, height),
+            _ => {},
+        }
+
+        if let Some(result) = self.update_picker(&action)? {
+            return Ok(result);
+        }
+        self.active_list().update(action)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        self.active_list().draw(f, area)?;
+        if self.entries.len() > 1 {
+            let profile = self.active_profile();
+            let title = Line::from(vec![
+                Span::styled(format!(" {} ", profile.name), Style::default().fg(PEACH).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("({}) ", profile.host), Style::default().fg(OVERLAY0)),
+            ]);
+            let width = (title.width() as u16).min(area.width.saturating_sub(4));
+            f.render_widget(title, Rect::new(area.x + 2, area.y, width, 1));
+        }
+        self.render_picker(f, area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    use super::*;
+    use crate::{
+        components::pull_request::tests::pull_request,
+        github::{fake::FakeGithubClient, host::GithubHost},
+    };
+
+    fn profile(name: &str, host: &str) -> Profile {
+        Profile { name: name.to_string(), host: GithubHost::new(host), ..Profile::default() }
+    }
+
+    fn profiles() -> (Profiles<FakeGithubClient>, UnboundedReceiver<Action>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut profiles = Profiles::new(
+            vec![
+                (
+                    profile("oss", "github.com"),
+                    PullRequestList::new(
+                        FakeGithubClient::new("octocat")
+                            .with_page(None, vec![pull_request("rust-lang/rust", 1)], None)
+                            .into_arc(),
+                    ),
+                ),
+                (
+                    profile("work", "github.example.com"),
+                    PullRequestList::new(
+                        FakeGithubClient::new("employee")
+                            .with_page(None, vec![pull_request("acme/app", 2), pull_request("acme/app", 3)], None)
+                            .into_arc(),
+                    ),
+                ),
+            ],
+            0,
+        );
+        profiles.register_action_handler(tx).unwrap();
+        (profiles, rx)
+    }
+
+    /// Feeds actions back into the component like the app loop does, until one matching `until` arrives.
+    async fn pump(
+        profiles: &mut Profiles<FakeGithubClient>,
+        rx: &mut UnboundedReceiver<Action>,
+        until: impl Fn(&Action) -> bool,
+    ) {
+        loop {
+            let action = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timed out waiting for action")
+                .expect("action channel closed");
+            let done = until(&action);
+            if let Some(replayed) = profiles.update(action).unwrap() {
+                profiles.update(replayed).unwrap();
+            }
+            if done {
+                return;
+            }
+        }
+    }
+
+    /// Feeds actions back into the component until the profile at `index` gets the first page of `query`, which is
+    /// returned unhandled.
+    async fn first_page(
+        profiles: &mut Profiles<FakeGithubClient>,
+        rx: &mut UnboundedReceiver<Action>,
+        index: usize,
+        query: &str,
+    ) -> Action {
+        loop {
+            let action = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timed out waiting for action")
+                .expect("action channel closed");
+            if let Action::ForProfile(i, ref inner) = action {
+                if i == index && matches!(&**inner, Action::LoadMorePullRequestsResult(q, ..) if q == query) {
+                    return action;
+                }
+            }
+            if let Some(replayed) = profiles.update(action).unwrap() {
+                profiles.update(replayed).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_switching_keeps_state_per_profile() {
+        let (mut profiles, mut rx) = profiles();
+
+        profiles.update(Action::Refresh).unwrap();
+        pump(
+            &mut profiles,
+            &mut rx,
+            |a| matches!(a, Action::ForProfile(0, a) if matches!(**a, Action::LoadMorePullRequestsResult(..))),
+        )
+        .await;
+
+        profiles.update(Action::SwitchProfile(String::from("work"))).unwrap();
+        assert_eq!(profiles.active_profile().name, "work");
+        profiles.update(Action::Refresh).unwrap();
+        pump(
+            &mut profiles,
+            &mut rx,
+            |a| matches!(a, Action::ForProfile(1, a) if matches!(**a, Action::LoadMorePullRequestsResult(..))),
+        )
+        .await;
+
+        assert_eq!(profiles.pull_request_list("oss").unwrap().pull_requests().len(), 1);
+        assert_eq!(profiles.pull_request_list("work").unwrap().pull_requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_results_reach_the_profile_that_requested_them() {
+        let (mut profiles, mut rx) = profiles();
+
+        // start loading "oss", then switch away before the results arrive
+        profiles.update(Action::Refresh).unwrap();
+        profiles.update(Action::SwitchProfile(String::from("work"))).unwrap();
+        pump(
+            &mut profiles,
+            &mut rx,
+            |a| matches!(a, Action::ForProfile(0, a) if matches!(**a, Action::LoadMorePullRequestsResult(..))),
+        )
+        .await;
+
+        assert_eq!(profiles.pull_request_list("oss").unwrap().pull_requests().len(), 1);
+        assert!(profiles.pull_request_list("work").unwrap().pull_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_switching_between_a_result_and_its_handling() {
+        let (mut profiles, mut rx) = profiles();
+        // the section shown first, which both profiles search with the same query
+        let query = Config::default().config.sections()[0].query.clone();
+
+        profiles.update(Action::SwitchProfile(String::from("work"))).unwrap();
+        profiles.update(Action::Refresh).unwrap();
+        let work_result = first_page(&mut profiles, &mut rx, 1, &query).await;
+        profiles.update(Action::SwitchProfile(String::from("oss"))).unwrap();
+        profiles.update(Action::Refresh).unwrap();
+        let oss_result = first_page(&mut profiles, &mut rx, 0, &query).await;
+
+        // the app loop handles what the result turns into only after the user switched back to "work", whose list
+        // still awaits its first page of the same query
+        let replayed = profiles.update(oss_result).unwrap();
+        profiles.update(Action::SwitchProfile(String::from("work"))).unwrap();
+        for action in replayed.into_iter().chain([work_result]) {
+            if let Some(replayed) = profiles.update(action).unwrap() {
+                profiles.update(replayed).unwrap();
+            }
+        }
+
+        assert_eq!(profiles.pull_request_list("oss").unwrap().pull_requests().len(), 1);
+        assert_eq!(profiles.pull_request_list("work").unwrap().pull_requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tokens_are_resolved_when_first_switched_to() {
+        let dir = std::env::temp_dir().join(format!("ghtui-lazy-tokens-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let work = Profile { token_command: Some(String::from("echo work-token")), ..profile("work", "github.com") };
+        let client = FakeGithubClient::new("employee").unauthenticated().into_arc();
+        let mut profiles = Profiles::new(
+            vec![
+                (profile("oss", "github.com"), PullRequestList::new(FakeGithubClient::new("octocat").into_arc())),
+                (work, PullRequestList::new(client.clone())),
+            ],
+            0,
+        )
+        .with_lazy_tokens(dir.clone());
+        let (tx, _rx) = mpsc::unbounded_channel();
+        profiles.register_action_handler(tx).unwrap();
+        profiles.init(Rect::default()).unwrap();
+        assert!(!client.is_authenticated());
+
+        profiles.update(Action::SwitchProfile(String::from("work"))).unwrap();
+
+        // the token may come from the environment instead, but the profile is signed in either way
+        assert!(client.is_authenticated());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_picker() {
+        let (mut profiles, _rx) = profiles();
+
+        profiles.update(Action::ProfilePicker).unwrap();
+        assert!(profiles.picker.is_some());
+        profiles.update(Action::Down).unwrap();
+        profiles.update(Action::Enter).unwrap();
+
+        assert!(profiles.picker.is_none());
+        assert_eq!(profiles.active_profile().name, "work");
+    }
+}
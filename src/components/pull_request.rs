@@ -1,15 +1,12 @@
 #![allow(clippy::upper_case_acronyms)]
 type URI = String;
+type ID = String;
 type DateTime = chrono::DateTime<chrono::Utc>;
 
 use std::fmt::Debug;
 
 use graphql_client::GraphQLQuery;
-use ratatui::{
-    style::{Color, Style},
-    text::{Line, Span},
-    widgets::{Cell, Row},
-};
+use ratatui::widgets::Row;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
@@ -24,7 +21,7 @@ use self::{
     },
 };
 use crate::{
-    colors::{BLUE, GREEN, RED, YELLOW},
+    components::{column::Column, diff::DiffSide, fuzzy::PullRequestMatch},
     thing::Thing,
 };
 
@@ -55,8 +52,103 @@ pub struct PullRequestsSummaryQuery;
 )]
 pub struct PullRequestDetailQuery;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/add_pull_request_review.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct AddPullRequestReviewMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/pull_request_viewed_files.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct PullRequestViewedFilesQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/mark_file_as_viewed.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct MarkFileAsViewedMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/unmark_file_as_viewed.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct UnmarkFileAsViewedMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/pull_request_review_threads.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct PullRequestReviewThreadsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/review_thread_comments.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct ReviewThreadCommentsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/pull_request_pending_review.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct PullRequestPendingReviewQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/start_pending_review.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct StartPendingReviewMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/add_pull_request_review_thread.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct AddPullRequestReviewThreadMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/queries/submit_pull_request_review.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct SubmitPullRequestReviewMutation;
+
+/// GitHub reports `null` authors for deleted accounts and shows them as the `ghost` user in the web UI.
+pub const GHOST_LOGIN: &str = "ghost";
+
 #[derive(Clone, Serialize, Deserialize, Eq)]
 pub struct PullRequest {
+    #[serde(default)]
+    pub id: String,
     pub number: usize,
     pub title: String,
     pub repository: String,
@@ -73,6 +165,16 @@ pub struct PullRequest {
     pub base_branch: String,
     pub body: String,
     pub comments: Vec<PullRequestComment>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// `None` if no checks ran on the head commit.
+    #[serde(default)]
+    pub checks: Option<CheckStatus>,
+    /// Comments on the conversation and in reviews.
+    #[serde(default)]
+    pub comment_count: usize,
+    #[serde(default)]
+    pub mergeable: Mergeable,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -91,6 +193,101 @@ pub enum PullRequestReviewState {
     Pending,
 }
 
+/// The combined state of the checks and commit statuses of a pull request's head commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CheckStatus {
+    Failure,
+    Pending,
+    Success,
+}
+
+/// Whether a pull request can be merged; GitHub computes it in the background, so it may not be known yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Mergeable {
+    Conflicting,
+    #[default]
+    Unknown,
+    Mergeable,
+}
+
+/// The verdict of a review submitted through [`crate::github::traits::GithubClient::submit_pull_request_review`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PullRequestReviewEvent {
+    Approve,
+    RequestChanges,
+    Comment,
+}
+
+impl PullRequestReviewEvent {
+    /// GitHub rejects `REQUEST_CHANGES` and `COMMENT` reviews without a body.
+    pub fn requires_body(&self) -> bool {
+        !matches!(self, PullRequestReviewEvent::Approve)
+    }
+}
+
+/// The state of a review once submitted with an event.
+impl From<PullRequestReviewEvent> for PullRequestReviewState {
+    fn from(event: PullRequestReviewEvent) -> Self {
+        match event {
+            PullRequestReviewEvent::Approve => PullRequestReviewState::Approved,
+            PullRequestReviewEvent::RequestChanges => PullRequestReviewState::ChangesRequested,
+            PullRequestReviewEvent::Comment => PullRequestReviewState::Commented,
+        }
+    }
+}
+
+impl std::fmt::Display for PullRequestReviewEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PullRequestReviewEvent::Approve => write!(f, "Approve"),
+            PullRequestReviewEvent::RequestChanges => write!(f, "Request changes"),
+            PullRequestReviewEvent::Comment => write!(f, "Comment"),
+        }
+    }
+}
+
+impl From<PullRequestReviewEvent> for add_pull_request_review_mutation::PullRequestReviewEvent {
+    fn from(event: PullRequestReviewEvent) -> Self {
+        match event {
+            PullRequestReviewEvent::Approve => add_pull_request_review_mutation::PullRequestReviewEvent::APPROVE,
+            PullRequestReviewEvent::RequestChanges => {
+                add_pull_request_review_mutation::PullRequestReviewEvent::REQUEST_CHANGES
+            },
+            PullRequestReviewEvent::Comment => add_pull_request_review_mutation::PullRequestReviewEvent::COMMENT,
+        }
+    }
+}
+
+impl From<PullRequestReviewEvent> for submit_pull_request_review_mutation::PullRequestReviewEvent {
+    fn from(event: PullRequestReviewEvent) -> Self {
+        match event {
+            PullRequestReviewEvent::Approve => submit_pull_request_review_mutation::PullRequestReviewEvent::APPROVE,
+            PullRequestReviewEvent::RequestChanges => {
+                submit_pull_request_review_mutation::PullRequestReviewEvent::REQUEST_CHANGES
+            },
+            PullRequestReviewEvent::Comment => submit_pull_request_review_mutation::PullRequestReviewEvent::COMMENT,
+        }
+    }
+}
+
+impl From<DiffSide> for add_pull_request_review_thread_mutation::DiffSide {
+    fn from(side: DiffSide) -> Self {
+        match side {
+            DiffSide::Left => add_pull_request_review_thread_mutation::DiffSide::LEFT,
+            DiffSide::Right => add_pull_request_review_thread_mutation::DiffSide::RIGHT,
+        }
+    }
+}
+
+impl From<pull_request_review_threads_query::DiffSide> for DiffSide {
+    fn from(side: pull_request_review_threads_query::DiffSide) -> Self {
+        match side {
+            pull_request_review_threads_query::DiffSide::LEFT => DiffSide::Left,
+            _ => DiffSide::Right,
+        }
+    }
+}
+
 impl From<PrQueryState> for PullRequestState {
     fn from(state: PrQueryState) -> Self {
         match state {
@@ -113,6 +310,50 @@ impl From<PrSummaryState> for PullRequestState {
     }
 }
 
+impl From<&pull_requests_summary_query::StatusState> for CheckStatus {
+    fn from(state: &pull_requests_summary_query::StatusState) -> Self {
+        use pull_requests_summary_query::StatusState;
+        match state {
+            StatusState::SUCCESS => CheckStatus::Success,
+            StatusState::ERROR | StatusState::FAILURE => CheckStatus::Failure,
+            _ => CheckStatus::Pending,
+        }
+    }
+}
+
+impl From<&pull_request_detail_query::StatusState> for CheckStatus {
+    fn from(state: &pull_request_detail_query::StatusState) -> Self {
+        use pull_request_detail_query::StatusState;
+        match state {
+            StatusState::SUCCESS => CheckStatus::Success,
+            StatusState::ERROR | StatusState::FAILURE => CheckStatus::Failure,
+            _ => CheckStatus::Pending,
+        }
+    }
+}
+
+impl From<&pull_requests_summary_query::MergeableState> for Mergeable {
+    fn from(state: &pull_requests_summary_query::MergeableState) -> Self {
+        use pull_requests_summary_query::MergeableState;
+        match state {
+            MergeableState::MERGEABLE => Mergeable::Mergeable,
+            MergeableState::CONFLICTING => Mergeable::Conflicting,
+            _ => Mergeable::Unknown,
+        }
+    }
+}
+
+impl From<&pull_request_detail_query::MergeableState> for Mergeable {
+    fn from(state: &pull_request_detail_query::MergeableState) -> Self {
+        use pull_request_detail_query::MergeableState;
+        match state {
+            MergeableState::MERGEABLE => Mergeable::Mergeable,
+            MergeableState::CONFLICTING => Mergeable::Conflicting,
+            _ => Mergeable::Unknown,
+        }
+    }
+}
+
 impl From<PrQueryReviewState> for PullRequestReviewState {
     fn from(state: PrQueryReviewState) -> Self {
         match state {
@@ -139,6 +380,45 @@ impl From<PrSummaryReviewState> for PullRequestReviewState {
     }
 }
 
+impl From<add_pull_request_review_mutation::PullRequestReviewState> for PullRequestReviewState {
+    fn from(state: add_pull_request_review_mutation::PullRequestReviewState) -> Self {
+        use add_pull_request_review_mutation::PullRequestReviewState as MutationReviewState;
+        match state {
+            MutationReviewState::APPROVED => PullRequestReviewState::Approved,
+            MutationReviewState::CHANGES_REQUESTED => PullRequestReviewState::ChangesRequested,
+            MutationReviewState::COMMENTED => PullRequestReviewState::Commented,
+            MutationReviewState::DISMISSED => PullRequestReviewState::Dismissed,
+            MutationReviewState::PENDING => PullRequestReviewState::Pending,
+            _ => PullRequestReviewState::Commented,
+        }
+    }
+}
+
+impl From<submit_pull_request_review_mutation::PullRequestReviewState> for PullRequestReviewState {
+    fn from(state: submit_pull_request_review_mutation::PullRequestReviewState) -> Self {
+        use submit_pull_request_review_mutation::PullRequestReviewState as MutationReviewState;
+        match state {
+            MutationReviewState::APPROVED => PullRequestReviewState::Approved,
+            MutationReviewState::CHANGES_REQUESTED => PullRequestReviewState::ChangesRequested,
+            MutationReviewState::COMMENTED => PullRequestReviewState::Commented,
+            MutationReviewState::DISMISSED => PullRequestReviewState::Dismissed,
+            MutationReviewState::PENDING => PullRequestReviewState::Pending,
+            _ => PullRequestReviewState::Commented,
+        }
+    }
+}
+
+impl PullRequest {
+    /// Replaces the latest review by the same author, mirroring how GitHub's `latestReviews` only keeps one review
+    /// per reviewer.
+    pub fn upsert_review(&mut self, review: PullRequestReview) {
+        match self.reviews.iter_mut().find(|r| r.author == review.author) {
+            Some(existing) => *existing = review,
+            None => self.reviews.push(review),
+        }
+    }
+}
+
 impl PartialEq for PullRequest {
     fn eq(&self, other: &Self) -> bool {
         debug!("{:?} == {:?}", self.number, other.number);
@@ -185,9 +465,10 @@ pub struct PullRequestReview {
 impl From<&PullRequestsQuerySearchEdgesNodeOnPullRequest> for PullRequest {
     fn from(value: &PullRequestsQuerySearchEdgesNodeOnPullRequest) -> Self {
         Self {
+            id: value.id.clone(),
             number: value.number as usize,
             title: value.title.clone(),
-            author: value.author.as_ref().unwrap().login.clone(),
+            author: value.author.as_ref().map_or_else(|| GHOST_LOGIN.to_string(), |a| a.login.clone()),
             repository: value.repository.name_with_owner.clone(),
             created_at: value.created_at,
             updated_at: value.updated_at,
@@ -199,16 +480,13 @@ impl From<&PullRequestsQuerySearchEdgesNodeOnPullRequest> for PullRequest {
             is_draft: value.is_draft,
             reviews: value
                 .latest_reviews
-                .as_ref()
-                .unwrap()
-                .edges
-                .as_ref()
-                .unwrap()
                 .iter()
-                .map(|v| {
+                .flat_map(|reviews| reviews.edges.iter().flatten())
+                .filter_map(|edge| edge.as_ref()?.node.as_ref())
+                .map(|node| {
                     PullRequestReview {
-                        author: v.as_ref().unwrap().node.as_ref().unwrap().author.as_ref().unwrap().login.clone(),
-                        state: v.as_ref().unwrap().node.as_ref().unwrap().state.clone().into(),
+                        author: node.author.as_ref().map_or_else(|| GHOST_LOGIN.to_string(), |a| a.login.clone()),
+                        state: node.state.clone().into(),
                     }
                 })
                 .collect(),
@@ -216,6 +494,11 @@ impl From<&PullRequestsQuerySearchEdgesNodeOnPullRequest> for PullRequest {
             base_branch: value.base_ref_name.clone(),
             body: value.body.clone(),
             comments: vec![],
+            // not part of this query
+            labels: vec![],
+            checks: None,
+            comment_count: 0,
+            mergeable: Mergeable::Unknown,
         }
     }
 }
@@ -223,9 +506,10 @@ impl From<&PullRequestsQuerySearchEdgesNodeOnPullRequest> for PullRequest {
 impl From<&PullRequestsSummaryQuerySearchEdgesNodeOnPullRequest> for PullRequest {
     fn from(value: &PullRequestsSummaryQuerySearchEdgesNodeOnPullRequest) -> Self {
         Self {
+            id: value.id.clone(),
             number: value.number as usize,
             title: value.title.clone(),
-            author: value.author.as_ref().unwrap().login.clone(),
+            author: value.author.as_ref().map_or_else(|| GHOST_LOGIN.to_string(), |a| a.login.clone()),
             repository: value.repository.name_with_owner.clone(),
             created_at: value.created_at,
             updated_at: value.updated_at,
@@ -237,22 +521,28 @@ impl From<&PullRequestsSummaryQuerySearchEdgesNodeOnPullRequest> for PullRequest
             is_draft: value.is_draft,
             reviews: value
                 .latest_reviews
-                .as_ref()
-                .unwrap()
-                .edges
-                .as_ref()
-                .unwrap()
                 .iter()
-                .map(|v| {
+                .flat_map(|reviews| reviews.edges.iter().flatten())
+                .filter_map(|edge| edge.as_ref()?.node.as_ref())
+                .map(|node| {
                     PullRequestReview {
-                        author: v.as_ref().unwrap().node.as_ref().unwrap().author.as_ref().unwrap().login.clone(),
-                        state: v.as_ref().unwrap().node.as_ref().unwrap().state.clone().into(),
+                        author: node.author.as_ref().map_or_else(|| GHOST_LOGIN.to_string(), |a| a.login.clone()),
+                        state: node.state.clone().into(),
                     }
                 })
                 .collect(),
-            base_branch: String::new(), // Will be loaded on-demand
-            body: String::new(),        // Will be loaded on-demand
-            comments: vec![],           // Will be loaded on-demand
+            base_branch: value.base_ref_name.clone(),
+            body: String::new(), // Will be loaded on-demand
+            comments: vec![],    // Will be loaded on-demand
+            labels: value
+                .labels
+                .iter()
+                .flat_map(|labels| labels.nodes.iter().flatten().flatten())
+                .map(|label| label.name.clone())
+                .collect(),
+            checks: value.status_check_rollup.as_ref().map(|rollup| (&rollup.state).into()),
+            comment_count: value.total_comments_count.unwrap_or_default() as usize,
+            mergeable: (&value.mergeable).into(),
         }
     }
 }
@@ -266,44 +556,96 @@ pub struct PullRequestComment {
 
 impl Thing for PullRequest {
     fn render_row(&self) -> Row<'_> {
-        Row::new(vec![
-            Cell::from(format!("{:}", self.number)),
-            Cell::from(self.repository.clone()),
-            Cell::from(self.title.clone()),
-            Cell::from(self.author.clone()),
-            Cell::from(format!("{}", self.created_at.format("%Y-%m-%d"))),
-            Cell::from(format!("{}", self.updated_at.format("%Y-%m-%d"))),
-            Cell::from(Line::from(vec![
-                Span::styled(format!("{:+}", self.additions), Style::new().fg(GREEN)),
-                Span::styled(format!("{:+}", (0 - self.deletions as isize)), Style::new().fg(RED)),
-            ])),
-            Cell::from(match self.state {
-                PullRequestState::Open => {
-                    if self.is_draft {
-                        "DRAFT"
-                    } else {
-                        "OPEN"
-                    }
-                },
-                PullRequestState::Closed => "CLOSED",
-                PullRequestState::Merged => "MERGED",
-            }),
-            Cell::from(Line::from(
-                self.reviews
-                    .iter()
-                    .flat_map(|prr| {
-                        vec![
-                            Span::styled(prr.author.clone(), match prr.state {
-                                PullRequestReviewState::Commented => Style::new().fg(BLUE),
-                                PullRequestReviewState::Approved => Style::new().fg(GREEN),
-                                PullRequestReviewState::ChangesRequested => Style::new().fg(YELLOW),
-                                _ => Style::new().fg(Color::Gray),
-                            }),
-                            Span::raw(" "),
-                        ]
-                    })
-                    .collect::<Vec<Span>>(),
-            )),
-        ])
+        let now = chrono::Utc::now();
+        Row::new(Column::defaults().iter().map(|c| c.column.cell(self, &PullRequestMatch::default(), now)))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    /// Builds a minimal pull request for component tests.
+    pub(crate) fn pull_request(repository: &str, number: usize) -> PullRequest {
+        PullRequest {
+            id: format!("PR_{repository}_{number}"),
+            number,
+            title: format!("Pull request #{number}"),
+            repository: repository.to_string(),
+            created_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            url: format!("https://github.com/{repository}/pull/{number}"),
+            changed_files: 1,
+            additions: 1,
+            deletions: 1,
+            state: PullRequestState::Open,
+            is_draft: false,
+            reviews: vec![],
+            author: String::from("octocat"),
+            base_branch: String::from("main"),
+            body: String::new(),
+            comments: vec![],
+            labels: vec![],
+            checks: None,
+            comment_count: 0,
+            mergeable: Mergeable::Unknown,
+        }
+    }
+
+    #[test]
+    fn test_summary_from_deleted_users() {
+        let node: PullRequestsSummaryQuerySearchEdgesNodeOnPullRequest = serde_json::from_value(serde_json::json!({
+            "id": "PR_1",
+            "number": 1,
+            "title": "Ghosted",
+            "repository": { "nameWithOwner": "owner/repo" },
+            "author": null,
+            "createdAt": "2024-01-01T00:00:00Z",
+            "updatedAt": "2024-01-01T00:00:00Z",
+            "additions": 1,
+            "deletions": 1,
+            "state": "OPEN",
+            "isDraft": false,
+            "baseRefName": "main",
+            "mergeable": "CONFLICTING",
+            "totalCommentsCount": null,
+            "statusCheckRollup": { "state": "ERROR" },
+            "labels": { "nodes": [null, { "name": "bug" }] },
+            "latestReviews": {
+                "edges": [
+                    null,
+                    { "node": null },
+                    { "node": { "state": "APPROVED", "author": null } },
+                    { "node": { "state": "COMMENTED", "author": { "__typename": "User", "login": "alice" } } },
+                ]
+            }
+        }))
+        .unwrap();
+
+        let pr = PullRequest::from(&node);
+        assert_eq!(pr.author, GHOST_LOGIN);
+        assert_eq!(pr.reviews, vec![
+            PullRequestReview { author: GHOST_LOGIN.into(), state: PullRequestReviewState::Approved },
+            PullRequestReview { author: "alice".into(), state: PullRequestReviewState::Commented },
+        ]);
+        assert_eq!(pr.labels, vec!["bug"]);
+        assert_eq!(pr.checks, Some(CheckStatus::Failure));
+        assert_eq!(pr.comment_count, 0);
+        assert_eq!(pr.mergeable, Mergeable::Conflicting);
+    }
+
+    #[test]
+    fn test_upsert_review_replaces_existing_review_by_author() {
+        let mut pr = pull_request("owner/repo", 1);
+        pr.upsert_review(PullRequestReview { author: "alice".into(), state: PullRequestReviewState::Commented });
+        pr.upsert_review(PullRequestReview { author: "bob".into(), state: PullRequestReviewState::Commented });
+        pr.upsert_review(PullRequestReview { author: "alice".into(), state: PullRequestReviewState::Approved });
+
+        assert_eq!(pr.reviews, vec![
+            PullRequestReview { author: "alice".into(), state: PullRequestReviewState::Approved },
+            PullRequestReview { author: "bob".into(), state: PullRequestReviewState::Commented },
+        ]);
     }
 }
@@ -1,71 +1,269 @@
-use color_eyre::{eyre::Result, owo_colors::OwoColorize};
+use std::sync::Arc;
+
+use color_eyre::eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 use graphql_client::GraphQLQuery;
-use oauth2::{
-    basic::BasicClient, reqwest::async_http_client, AuthUrl, ClientId, DeviceAuthorizationUrl, Scope,
-    StandardDeviceAuthorizationResponse, TokenUrl,
-};
-use octocrab::Octocrab;
 use ratatui::{prelude::*, widgets::*};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use super::pull_request::PullRequestState;
 use crate::{
     action::Action,
-    colors::{BASE, TEXT},
+    cache::PullRequestCache,
+    colors::{BASE, BLUE, GREEN, TEXT, YELLOW},
     components::{
+        diff::{CommentTarget, PendingReview, ReviewThreads},
+        diff_view::DiffView,
+        notifications::Notification,
         pull_request::{PullRequest, PullRequestReviewState},
         Component, Frame,
     },
     config::{Config, KeyBindings},
-    github::{client::GraphQLGithubClient, traits::GithubClient},
+    github::traits::GithubClient,
 };
 
-#[derive(Default)]
-pub struct PullRequestInfoOverlay {
+pub struct PullRequestInfoOverlay<C: GithubClient> {
+    client: Arc<C>,
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
     pull_request: Option<PullRequest>,
     detailed_pull_request: Option<PullRequest>,
     scroll_offset: u16,
     is_loading_details: bool,
+    /// Whether the changed files are shown instead of the description.
+    show_diff: bool,
+    /// `None` until the changed files have been loaded.
+    diff: Option<DiffView>,
+    is_loading_files: bool,
+    /// The signed in user's pending review of the pull request, which new review comments are added to.
+    pending_review: Option<PendingReview>,
+    cache: Option<PullRequestCache>,
 }
 
-impl PullRequestInfoOverlay {
-    pub fn new() -> Self {
-        Self::default()
+impl<C: GithubClient> PullRequestInfoOverlay<C> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            command_tx: None,
+            config: Config::default(),
+            pull_request: None,
+            detailed_pull_request: None,
+            scroll_offset: 0,
+            is_loading_details: false,
+            show_diff: false,
+            diff: None,
+            is_loading_files: false,
+            pending_review: None,
+            cache: None,
+        }
+    }
+
+    /// Shows the details cached when the pull request was last opened while the fresh ones load.
+    pub fn with_cache(mut self, cache: Option<PullRequestCache>) -> Self {
+        self.cache = cache;
+        self
     }
 
     pub fn with_pull_request(mut self, pull_request: PullRequest) -> Self {
         self.pull_request = Some(pull_request);
         self.detailed_pull_request = None;
         self.is_loading_details = false; // Will be set to true when we start loading
+        self.show_diff = false;
+        self.diff = None;
+        self.is_loading_files = false;
+        self.pending_review = None;
         self
     }
 
+    /// Keeps adding to a pending review known from before the overlay was opened, e.g. by an overlay that was closed
+    /// since.
+    pub fn with_pending_review(mut self, pending_review: Option<PendingReview>) -> Self {
+        self.pending_review = pending_review;
+        self
+    }
+
+    /// Whether the changed files are shown instead of the description.
+    pub fn is_showing_diff(&self) -> bool {
+        self.show_diff
+    }
+
+    pub fn diff(&self) -> Option<&DiffView> {
+        self.diff.as_ref()
+    }
+
+    /// The signed in user's pending review of `pull_request`, if the overlay shows it and knows of one.
+    pub fn pending_review(&self, pull_request: &PullRequest) -> Option<PendingReview> {
+        let pr = self.pull_request.as_ref()?;
+        if pr.repository != pull_request.repository || pr.number != pull_request.number {
+            return None;
+        }
+        self.pending_review.clone()
+    }
+
+    /// Switches between the description and the changed files, loading the files the first time they are shown.
+    fn toggle_diff(&mut self) {
+        self.show_diff = !self.show_diff;
+        if self.show_diff && self.diff.is_none() {
+            self.load_pull_request_files();
+        }
+    }
+
+    fn load_pull_request_files(&mut self) {
+        let (Some(pr), Some(tx)) = (&self.pull_request, &self.command_tx) else {
+            return;
+        };
+        if self.is_loading_files {
+            return;
+        }
+        self.is_loading_files = true;
+        let (repository, number) = (pr.repository.clone(), pr.number);
+        let tx = tx.clone();
+        let client = self.client.clone();
+        let retry = self.config.config.retry.clone();
+        tokio::spawn(async move {
+            let Some((owner, repo)) = repository.split_once('/') else {
+                return;
+            };
+            let request = || client.get_pull_request_files(owner.to_string(), repo.to_string(), number);
+            let notify_retry = |attempt: &_| {
+                let _ = tx.send(Action::Notify(Notification::from(attempt)));
+            };
+            match retry.run(request, notify_retry).await {
+                Ok(mut files) => {
+                    // the diff is worth showing without the viewed state
+                    let request = || client.get_viewed_files(owner.to_string(), repo.to_string(), number);
+                    match retry.run(request, notify_retry).await {
+                        Ok(viewed) => files.iter_mut().for_each(|file| file.viewed = viewed.contains(&file.filename)),
+                        Err(e) => warn!("Could not load viewed files: {}", e),
+                    }
+                    let _ = tx.send(Action::PullRequestFilesLoaded(repository.clone(), number, files));
+                    // as are the review threads
+                    let request = || client.get_review_threads(owner.to_string(), repo.to_string(), number);
+                    match retry.run(request, notify_retry).await {
+                        Ok(threads) => {
+                            let _ = tx.send(Action::ReviewThreadsLoaded(repository.clone(), number, threads));
+                        },
+                        Err(e) => warn!("Could not load review threads: {}", e),
+                    }
+                },
+                Err(e) => {
+                    debug!("Failed to load changed files: {}", e);
+                    let _ = tx.send(Action::Error(e.to_string()));
+                    let _ = tx.send(Action::PullRequestFilesLoadError);
+                },
+            }
+        });
+    }
+
+    /// Marks the file targeted in the diff as viewed, or no longer viewed, right away and on GitHub in the background,
+    /// undoing it if GitHub refuses.
+    fn toggle_viewed(&mut self) {
+        let (Some(pr), Some(tx)) = (self.pull_request().cloned(), self.command_tx.clone()) else {
+            return;
+        };
+        let Some(diff) = &mut self.diff else {
+            return;
+        };
+        let Some(file) = diff.viewed_target() else {
+            return;
+        };
+        let (path, viewed) = (file.filename.clone(), !file.viewed);
+        diff.set_viewed(&path, viewed);
+        let client = self.client.clone();
+        let retry = self.config.config.retry.clone();
+        // retried unlike the other mutations: marking a file as viewed twice does no harm
+        tokio::spawn(async move {
+            let request = || client.set_file_viewed(&pr, path.clone(), viewed);
+            let notify_retry = |attempt: &_| {
+                let _ = tx.send(Action::Notify(Notification::from(attempt)));
+            };
+            if let Err(e) = retry.run(request, notify_retry).await {
+                debug!("Failed to mark {} as viewed: {}", path, e);
+                let _ = tx.send(Action::Error(e.to_string()));
+                let _ = tx.send(Action::FileViewed(pr.repository, pr.number, path, !viewed));
+            }
+        });
+    }
+
+    /// Asks for a review comment on the lines selected in the diff, or tells why they cannot be commented on.
+    fn comment_lines(&mut self) {
+        let (Some(pr), Some(tx), Some(diff)) = (self.pull_request(), &self.command_tx, &self.diff) else {
+            return;
+        };
+        let action = match diff.comment_target() {
+            Ok(target) => Action::WriteComment(Box::new(pr.clone()), target),
+            Err(e) => Action::Notify(Notification::Warning(e)),
+        };
+        let _ = tx.send(action);
+    }
+
+    /// Adds a review comment to the pending review in the background, starting the review if there is none yet. The
+    /// comment is shown once GitHub has it.
+    pub fn add_review_comment(&mut self, pull_request: PullRequest, target: CommentTarget, body: String) {
+        let Some(tx) = self.command_tx.clone() else {
+            return;
+        };
+        let client = self.client.clone();
+        let pending_review_id = self.pending_review(&pull_request).map(|review| review.id);
+        if let Some(diff) = &mut self.diff {
+            diff.clear_selection();
+        }
+        // not retried, like reviews: a retry may add the comment twice
+        tokio::spawn(async move {
+            match client.add_review_thread(&pull_request, pending_review_id, target, body).await {
+                Ok((review_id, thread)) => {
+                    let (repository, number) = (pull_request.repository, pull_request.number);
+                    let _ = tx.send(Action::ReviewCommentAdded(repository, number, review_id, thread));
+                },
+                Err(e) => {
+                    debug!("Failed to add review comment: {}", e);
+                    let _ = tx.send(Action::Error(e.to_string()));
+                },
+            }
+        });
+    }
+
+    /// The pull request being shown: the detailed version once loaded, the summary row until then.
+    pub fn pull_request(&self) -> Option<&PullRequest> {
+        self.detailed_pull_request.as_ref().or(self.pull_request.as_ref())
+    }
+
     fn load_pull_request_details(&mut self) {
         if let (Some(pr), Some(tx)) = (&self.pull_request, &self.command_tx) {
             if self.is_loading_details {
                 return; // Already loading
             }
 
+            if let Some(cache) = &self.cache {
+                self.detailed_pull_request = cache.load_details(&pr.repository, pr.number);
+            }
+            // GitHub bumps `updated_at` on every change to a pull request, so unchanged details need no refetch
+            if self.detailed_pull_request.as_ref().is_some_and(|detailed| detailed.updated_at == pr.updated_at) {
+                debug!("Using cached details of {}#{}", pr.repository, pr.number);
+                return;
+            }
             self.is_loading_details = true;
             let pr = pr.clone();
             let tx_clone = tx.clone();
+            let client = self.client.clone();
+            let retry = self.config.config.retry.clone();
 
             tokio::spawn(async move {
                 if let Some(repo_parts) = pr.repository.split_once('/') {
                     let (owner, repo) = repo_parts;
-                    match GraphQLGithubClient::get_pull_request_details(owner.to_string(), repo.to_string(), pr.number)
-                        .await
-                    {
+                    let request = || client.get_pull_request_details(owner.to_string(), repo.to_string(), pr.number);
+                    let notify_retry = |attempt: &_| {
+                        let _ = tx_clone.send(Action::Notify(Notification::from(attempt)));
+                    };
+                    match retry.run(request, notify_retry).await {
                         Ok(detailed_pr) => {
                             let _ = tx_clone.send(Action::PullRequestDetailsLoaded(Box::new(detailed_pr)));
                         },
                         Err(e) => {
                             debug!("Failed to load PR details: {}", e);
+                            let _ = tx_clone.send(Action::Error(e.to_string()));
                             let _ = tx_clone.send(Action::PullRequestDetailsLoadError);
                         },
                     }
@@ -75,7 +273,7 @@ impl PullRequestInfoOverlay {
     }
 }
 
-impl Component for PullRequestInfoOverlay {
+impl<C: GithubClient> Component for PullRequestInfoOverlay<C> {
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
         self.command_tx = Some(tx);
 
@@ -93,8 +291,76 @@ impl Component for PullRequestInfoOverlay {
     }
 
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if self.show_diff {
+            if let Some(diff) = &mut self.diff {
+                if let Some(action) = diff.update(action.clone())? {
+                    return Ok(Some(action));
+                }
+            }
+        }
         match action {
             Action::Tick => {},
+            Action::Diff => {
+                self.toggle_diff();
+                return Ok(Some(Action::Render));
+            },
+            Action::PullRequestFilesLoaded(repository, number, files) => {
+                // files requested by a previously opened overlay may arrive after another pull request was opened
+                if self.pull_request.as_ref().is_some_and(|pr| pr.repository == repository && pr.number == number) {
+                    let changed_files = self.pull_request().map(|pr| pr.changed_files).unwrap_or_default();
+                    self.diff = Some(
+                        DiffView::new(files)
+                            .with_min_split_width(self.config.config.split_diff_min_width())
+                            .with_changed_files(changed_files),
+                    );
+                    self.is_loading_files = false;
+                }
+            },
+            Action::PullRequestFilesLoadError => {
+                self.is_loading_files = false;
+            },
+            Action::ToggleViewed if self.show_diff => {
+                self.toggle_viewed();
+                return Ok(Some(Action::Render));
+            },
+            Action::CommentLines if self.show_diff => {
+                self.comment_lines();
+            },
+            Action::ReviewThreadsLoaded(repository, number, ReviewThreads { pending_review, threads }) => {
+                if self.pull_request.as_ref().is_some_and(|pr| pr.repository == repository && pr.number == number) {
+                    self.pending_review = pending_review;
+                    if let Some(diff) = &mut self.diff {
+                        diff.set_threads(threads);
+                    }
+                }
+            },
+            Action::ReviewCommentAdded(repository, number, review_id, thread) => {
+                if self.pull_request.as_ref().is_some_and(|pr| pr.repository == repository && pr.number == number) {
+                    let comments = self
+                        .pending_review
+                        .take()
+                        .filter(|review| review.id == review_id)
+                        .map_or(0, |review| review.comments);
+                    self.pending_review = Some(PendingReview { id: review_id, comments: comments + 1 });
+                    if let Some(diff) = &mut self.diff {
+                        diff.add_thread(thread);
+                    }
+                    return Ok(Some(Action::Render));
+                }
+            },
+            // looked up for a review prompt opened while the overlay did not know of the pending review
+            Action::PendingReviewLoaded(repository, number, pending_review) => {
+                if self.pull_request.as_ref().is_some_and(|pr| pr.repository == repository && pr.number == number) {
+                    self.pending_review.get_or_insert(pending_review);
+                }
+            },
+            Action::FileViewed(repository, number, path, viewed) => {
+                if self.pull_request.as_ref().is_some_and(|pr| pr.repository == repository && pr.number == number) {
+                    if let Some(diff) = &mut self.diff {
+                        diff.set_viewed(&path, viewed);
+                    }
+                }
+            },
             Action::Up => {
                 self.scroll_offset = self.scroll_offset.saturating_sub(1);
             },
@@ -110,17 +376,51 @@ impl Component for PullRequestInfoOverlay {
             Action::Enter => {},
             Action::Open => {
                 if let Some(pr) = &self.pull_request {
-                    let url = pr.url.clone();
-                    let _ = open::that(url);
+                    let _ = open::that(self.client.host().pull_request_url(&pr.repository, pr.number));
                 }
             },
             Action::PullRequestDetailsLoaded(detailed_pr) => {
-                self.detailed_pull_request = Some(*detailed_pr);
-                self.is_loading_details = false;
+                // details requested by a previously opened overlay may arrive after another pull request was opened
+                if self.pull_request.as_ref().is_some_and(|pr| *pr == *detailed_pr) {
+                    if let Some(cache) = &self.cache {
+                        if let Err(err) = cache.save_details(&detailed_pr) {
+                            warn!("Could not cache pull request details: {:?}", err);
+                        }
+                    }
+                    self.detailed_pull_request = Some(*detailed_pr);
+                    self.is_loading_details = false;
+                }
             },
             Action::PullRequestDetailsLoadError => {
                 self.is_loading_details = false;
             },
+            Action::Review(event) => {
+                if let (Some(pr), Some(tx)) = (self.pull_request(), &self.command_tx) {
+                    tx.send(Action::ConfirmReview(Box::new(pr.clone()), event))?;
+                }
+            },
+            Action::ReviewSubmitted(repository, number, review) => {
+                for pr in self.pull_request.iter_mut().chain(self.detailed_pull_request.iter_mut()) {
+                    if pr.repository == repository && pr.number == number {
+                        pr.upsert_review(review.clone());
+                    }
+                }
+                // the pending review, if any, was submitted with it
+                if self.pull_request.as_ref().is_some_and(|pr| pr.repository == repository && pr.number == number) {
+                    self.pending_review = None;
+                    if let Some(diff) = &mut self.diff {
+                        diff.submit_pending();
+                    }
+                }
+                // keeps the cached details in line with what is shown until the next refresh bumps `updated_at`
+                if let (Some(cache), Some(detailed)) = (&self.cache, &self.detailed_pull_request) {
+                    if detailed.repository == repository && detailed.number == number {
+                        if let Err(err) = cache.save_details(detailed) {
+                            warn!("Could not cache pull request details: {:?}", err);
+                        }
+                    }
+                }
+            },
             _ => {},
         }
         Ok(None)
@@ -142,10 +442,7 @@ impl Component for PullRequestInfoOverlay {
         ])
         .split(area.inner(&Margin { horizontal: 1, vertical: 1 }));
 
-        if let Some(pr) = &self.pull_request {
-            // Use detailed PR if available, otherwise use summary PR
-            let display_pr = self.detailed_pull_request.as_ref().unwrap_or(pr);
-
+        if let Some(display_pr) = self.pull_request() {
             let header = Paragraph::new(vec![
                 Span::styled(
                     format!("#{} in {}", display_pr.number, display_pr.repository),
@@ -160,13 +457,28 @@ impl Component for PullRequestInfoOverlay {
                     PullRequestState::Open => "OPEN".to_string(),
                 })
                 .into(),
+                Line::from(
+                    std::iter::once(Span::raw("Reviews: "))
+                        .chain(display_pr.reviews.iter().flat_map(|prr| {
+                            vec![
+                                Span::styled(prr.author.clone(), match prr.state {
+                                    PullRequestReviewState::Commented => Style::new().fg(BLUE),
+                                    PullRequestReviewState::Approved => Style::new().fg(GREEN),
+                                    PullRequestReviewState::ChangesRequested => Style::new().fg(YELLOW),
+                                    _ => Style::new().fg(Color::Gray),
+                                }),
+                                Span::raw(" "),
+                            ]
+                        }))
+                        .collect::<Vec<Span>>(),
+                ),
             ])
             .style(Style::default().fg(TEXT).add_modifier(Modifier::BOLD))
             .alignment(Alignment::Left);
 
             let horizontal_separator = Paragraph::new("─".repeat(area.width as usize)).style(Style::default().fg(TEXT));
 
-            let body_text = if self.is_loading_details {
+            let body_text = if self.is_loading_details && self.detailed_pull_request.is_none() {
                 "Loading detailed information...".to_string()
             } else if display_pr.body.is_empty() {
                 "No description provided.".to_string()
@@ -181,7 +493,14 @@ impl Component for PullRequestInfoOverlay {
 
             f.render_widget(header, layout[0]);
             f.render_widget(horizontal_separator, layout[1]);
-            f.render_widget(body, layout[2]);
+            match &mut self.diff {
+                Some(diff) if self.show_diff => diff.draw(f, layout[2])?,
+                None if self.show_diff => {
+                    let text = if self.is_loading_files { "Loading changed files..." } else { "No changes loaded." };
+                    f.render_widget(Paragraph::new(text).style(Style::default().fg(TEXT)), layout[2]);
+                },
+                _ => f.render_widget(body, layout[2]),
+            }
         }
         Ok(())
     }
@@ -1,12 +1,10 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
 use color_eyre::{eyre::Result, owo_colors::OwoColorize};
 use crossterm::event::{KeyCode, KeyEvent};
 use derivative::Derivative;
 use graphql_client::GraphQLQuery;
-use oauth2::{
-    basic::BasicClient, reqwest::async_http_client, AuthUrl, ClientId, DeviceAuthorizationUrl, Scope,
-    StandardDeviceAuthorizationResponse, TokenUrl,
-};
-use octocrab::Octocrab;
 use ratatui::{
     prelude::*,
     widgets::{
@@ -16,57 +14,156 @@ use ratatui::{
 };
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
-use tracing::{debug, error, error_span, info};
+use tracing::{debug, error, error_span, info, warn};
 
-use super::{notifications::Notification, pull_request_info_overlay::PullRequestInfoOverlay, utils::centered_rect};
+use super::{
+    column::{fit_columns, Column, ColumnConfig},
+    comment_prompt::CommentPrompt,
+    filter_bar::FilterBar,
+    fuzzy::PullRequestFilter,
+    login::Login,
+    notifications::Notification,
+    pull_request_info_overlay::PullRequestInfoOverlay,
+    review_prompt::ReviewPrompt,
+    search_prompt::SearchPrompt,
+    utils::centered_rect,
+};
 use crate::{
     action::Action,
+    cache::{CachedSection, PullRequestCache},
     colors::{BASE, BLUE, GREEN, LAVENDER, OVERLAY0, PEACH, PINK, RED, ROSEWATER, SURFACE0, TEXT, YELLOW},
     components::{
-        pull_request::{PullRequest, PullRequestReviewState, PullRequestState},
+        diff::CommentTarget,
+        pull_request::{
+            PullRequest, PullRequestReview, PullRequestReviewEvent, PullRequestReviewState, PullRequestState,
+        },
+        pull_request_section::{PullRequestSection, SectionRow},
         Component, Frame,
     },
-    config::{get_keybinding_for_action, key_event_to_string, Config, KeyBindings},
-    github::{client::GraphQLGithubClient, traits::GithubClient},
+    config::{get_keybinding_for_action, key_event_to_string, Config, KeyBindings, Section},
+    github::{error::GithubError, retry::RetryAttempt, token::TokenAttempt, traits::GithubClient},
     mode::Mode,
+    search_history::SearchHistory,
 };
 
-#[derive(Default)]
-pub struct PullRequestList {
+/// How close to the end of the loaded rows the selection gets before the next page of a section is loaded.
+const LOAD_MORE_DISTANCE: usize = 5;
+
+pub struct PullRequestList<C: GithubClient> {
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
-    selected_row: usize,
-    pull_requests: Option<Vec<PullRequest>>,
     username: String,
     show_info_overlay: bool,
-    info_overlay: PullRequestInfoOverlay,
-    client: GraphQLGithubClient,
-    selected_column: usize,
-    // Pagination state
-    has_next_page: bool,
-    end_cursor: Option<String>,
-    is_loading_more: bool,
+    info_overlay: PullRequestInfoOverlay<C>,
+    review_prompt: Option<ReviewPrompt>,
+    comment_prompt: Option<CommentPrompt>,
+    search_prompt: Option<SearchPrompt>,
+    search_history: Option<SearchHistory>,
+    filter_bar: FilterBar,
+    login: Login<C>,
+    /// Token sources that were tried without success, shown while the client is unauthenticated.
+    token_attempts: Vec<TokenAttempt>,
+    cache: Option<PullRequestCache>,
+    /// When the shown pull requests were fetched, if they come from the cache and have not been refreshed yet.
+    stale_since: Option<DateTime<Utc>>,
+    client: Arc<C>,
+    /// The configured searches, each shown in its own tab and paginated on its own.
+    sections: Vec<PullRequestSection>,
+    /// The index of the section whose tab is shown.
+    active_section: usize,
+    /// The columns that fit into the table when it was last drawn, which the sort keys refer to.
+    shown_columns: Vec<Column>,
     initial_load_size: usize,
     page_size: usize,
-    table_state: TableState,
 }
 
-impl PullRequestList {
-    pub fn new() -> Self {
+impl<C: GithubClient> PullRequestList<C> {
+    pub fn new(client: Arc<C>) -> Self {
         Self {
+            command_tx: None,
+            config: Config::default(),
+            username: String::new(),
+            show_info_overlay: false,
+            info_overlay: PullRequestInfoOverlay::new(client.clone()),
+            review_prompt: None,
+            comment_prompt: None,
+            search_prompt: None,
+            search_history: None,
+            filter_bar: FilterBar::default(),
+            login: Login::new(client.clone(), None),
+            token_attempts: Vec::new(),
+            cache: None,
+            stale_since: None,
+            client,
+            sections: PullRequestSection::from_config(&Config::default(), Vec::new()),
+            active_section: 0,
+            shown_columns: Vec::new(),
             initial_load_size: 10,
             page_size: 20,
-            has_next_page: true,
-            selected_column: 1, // sort by repo by default
-            ..Default::default()
         }
     }
 
+    /// Enables logging in with the device flow of the given OAuth app while no token is configured.
+    pub fn with_oauth_client_id(mut self, oauth_client_id: Option<String>) -> Self {
+        self.login = Login::new(self.client.clone(), oauth_client_id);
+        self
+    }
+
+    pub fn with_token_attempts(mut self, token_attempts: Vec<TokenAttempt>) -> Self {
+        self.token_attempts = token_attempts;
+        self
+    }
+
+    /// Signs in with a token resolved after the list was created, or shows where it was looked for.
+    pub fn set_token(&mut self, token: Result<String, Vec<TokenAttempt>>) -> Result<()> {
+        match token {
+            Ok(token) => self.client.authenticate(token)?,
+            Err(token_attempts) => self.token_attempts = token_attempts,
+        }
+        Ok(())
+    }
+
+    /// Shows the pull requests fetched last time right away, and keeps the cache up to date with every refresh.
+    pub fn with_cache(mut self, cache: PullRequestCache) -> Self {
+        self.cache = Some(cache);
+        self.restore_from_cache();
+        self
+    }
+
+    /// Fills the sections that have not been loaded yet with their cached pull requests.
+    fn restore_from_cache(&mut self) {
+        let Some(cached) = self.cache.as_ref().and_then(|cache| cache.load_pull_requests()) else {
+            return;
+        };
+        for section in self.sections.iter_mut().filter(|section| section.pull_requests.is_none()) {
+            if let Some(pull_requests) = cached.section(&section.section.query) {
+                section.pull_requests = Some(pull_requests.to_vec());
+                section.sort();
+                self.stale_since = Some(cached.fetched_at);
+            }
+        }
+    }
+
+    /// The pull requests of the shown tab.
+    pub fn pull_requests(&self) -> &[PullRequest] {
+        self.section().pull_requests()
+    }
+
+    fn section(&self) -> &PullRequestSection {
+        &self.sections[self.active_section]
+    }
+
+    fn section_mut(&mut self) -> &mut PullRequestSection {
+        &mut self.sections[self.active_section]
+    }
+
     fn get_current_user(&mut self) -> Result<()> {
         let tx = self.command_tx.clone().unwrap();
         tx.send(Action::Notify(Notification::Info(String::from("Getting current user..."))))?;
+        let client = self.client.clone();
+        let retry = self.config.config.retry.clone();
         tokio::spawn(async move {
-            match GraphQLGithubClient::get_current_user().await {
+            match retry.run(|| client.get_current_user(), Self::notify_retry(&tx)).await {
                 Ok(username) => {
                     tx.send(Action::Notify(Notification::Info(format!("Got user {username}"))))?;
                     tx.send(Action::GetCurrentUserResult(username))
@@ -83,160 +180,593 @@ impl PullRequestList {
     fn fetch_repos(&mut self) -> Result<()> {
         let tx = self.command_tx.clone().unwrap();
         tx.send(Action::Notify(Notification::Info(String::from("Fetching pull requests..."))))?;
-        let username = self.username.clone();
-        let initial_load_size = self.initial_load_size as i32;
+        for index in 0..self.sections.len() {
+            // Reset pagination state for fresh fetch
+            self.sections[index].reset_pagination();
+            self.load_section(index, self.initial_load_size, None);
+        }
+        Ok(())
+    }
+
+    fn has_next_page(&self) -> bool {
+        self.sections.iter().any(|section| section.has_next_page)
+    }
+
+    /// Clears the stale marker and caches the sections once no section waits for a page, whether it arrived or not.
+    fn finish_refresh(&mut self) {
+        if !self.is_loading_more() {
+            self.stale_since = None;
+            self.save_to_cache();
+        }
+    }
+
+    fn is_loading_more(&self) -> bool {
+        self.sections.iter().any(|section| section.is_loading)
+    }
+
+    fn load_more_pull_requests(&mut self, index: usize) -> Result<()> {
+        let Some(section) = self.sections.get(index) else {
+            return Ok(());
+        };
+        if !section.has_next_page || section.is_loading {
+            return Ok(());
+        }
+
+        debug!("Loading more pull requests of {}...", section.section.name);
+        let tx = self.command_tx.clone().unwrap();
+        tx.send(Action::Notify(Notification::Info(format!("Loading more {} pull requests...", section.section.name))))?;
+
+        // background pagination yields to user-initiated requests when the rate limit runs low
+        let rate_limit = self.client.rate_limit();
+        let delay = rate_limit.and_then(|rate_limit| rate_limit.throttle_delay(chrono::Utc::now()));
+        if let (Some(rate_limit), Some(delay)) = (rate_limit, delay) {
+            let message = if rate_limit.is_exhausted() {
+                Notification::Warning(format!(
+                    "GitHub rate limit reached, loading more pull requests at {}",
+                    rate_limit.reset_at.with_timezone(&chrono::Local).format("%H:%M:%S")
+                ))
+            } else {
+                Notification::Info(format!(
+                    "GitHub rate limit is low ({} left), loading more pull requests every {}s",
+                    rate_limit.remaining,
+                    delay.as_secs()
+                ))
+            };
+            tx.send(Action::Notify(message))?;
+        }
+
+        self.load_section(index, self.page_size, delay);
+        Ok(())
+    }
+
+    /// Loads the next page of the shown section once the selection is close to the end of its rows, if a page with a
+    /// next one has been loaded since the last refresh.
+    fn load_more_near_end(&mut self) {
+        let section = self.section();
+        if section.end_cursor.is_some() && section.selected_row + LOAD_MORE_DISTANCE >= section.rows().len() {
+            let _ = self.load_more_pull_requests(self.active_section);
+        }
+    }
 
-        // Reset pagination state for fresh fetch
-        self.has_next_page = true;
-        self.end_cursor = None;
-        self.is_loading_more = false;
+    /// Requests the next page of a section, after `delay` if given.
+    fn load_section(&mut self, index: usize, first: usize, delay: Option<std::time::Duration>) {
+        let tx = self.command_tx.clone().unwrap();
+        let section = &mut self.sections[index];
+        section.is_loading = true;
+        let key = section.section.query.clone();
+        let query = section.section.query_for(&self.username);
+        let after = section.end_cursor.clone();
+        let first = first as i32;
 
+        let client = self.client.clone();
+        let retry = self.config.config.retry.clone();
         tokio::spawn(async move {
-            match GraphQLGithubClient::get_pull_requests_paginated(username, initial_load_size, None).await {
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+            let request = || client.search_pull_requests(query.clone(), first, after.clone());
+            match retry.run(request, Self::notify_retry(&tx)).await {
                 Ok((pull_requests, has_next_page, end_cursor)) => {
-                    let _ = tx.send(Action::Notify(Notification::Info(format!(
-                        "Got pull requests: {}",
-                        pull_requests.len()
-                    ))));
-                    let _ = tx.send(Action::LoadMorePullRequestsResult(pull_requests, has_next_page, end_cursor));
+                    let _ = tx.send(Action::LoadMorePullRequestsResult(
+                        key,
+                        after,
+                        pull_requests,
+                        has_next_page,
+                        end_cursor,
+                    ));
                 },
                 Err(err) => {
-                    error!("Error getting pull requests: {:?}", err);
-                    let _ = tx.send(Action::Error(err.to_string()));
+                    error!("Error loading pull requests for {:?}: {:?}", query, err);
+                    let _ = tx.send(Self::error_action(err));
+                    let _ = tx.send(Action::LoadMorePullRequestsError(key, after));
                 },
             }
         });
-        Ok(())
     }
 
-    fn load_more_pull_requests(&mut self) -> Result<()> {
-        if !self.has_next_page || self.is_loading_more {
-            return Ok(());
+    /// Keeps the user informed while a request is retried, so a slow load is not mistaken for an empty list.
+    fn notify_retry(tx: &UnboundedSender<Action>) -> impl FnMut(&RetryAttempt) + '_ {
+        move |attempt| {
+            let _ = tx.send(Action::Notify(attempt.into()));
         }
+    }
 
-        debug!("Loading more pull requests...");
-        let tx = self.command_tx.clone().unwrap();
-        tx.send(Action::Notify(Notification::Info(String::from("Loading more pull requests..."))))?;
-        let username = self.username.clone();
-        let page_size = self.page_size as i32;
-        let after = self.end_cursor.clone();
+    /// Running out of rate limit is expected on large accounts and resolves itself, so it is shown as a warning with
+    /// the reset time rather than as an error.
+    fn error_action(err: GithubError) -> Action {
+        match err {
+            GithubError::RateLimited { .. } => Action::Notify(Notification::Warning(err.to_string())),
+            err => Action::Error(err.to_string()),
+        }
+    }
 
-        self.is_loading_more = true;
+    fn save_to_cache(&self) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        let sections = self
+            .sections
+            .iter()
+            .filter(|section| !section.is_ad_hoc)
+            .filter_map(|section| {
+                Some(CachedSection {
+                    query: section.section.query.clone(),
+                    pull_requests: section.pull_requests.clone()?,
+                })
+            })
+            .collect();
+        if let Err(err) = cache.save_pull_requests(sections) {
+            warn!("Could not cache pull requests: {:?}", err);
+        }
+    }
+
+    fn stale_title(&self) -> Option<Title<'static>> {
+        let fetched_at = self.stale_since?.with_timezone(&chrono::Local);
+        let text = format!(" stale · fetched {} ", fetched_at.format("%Y-%m-%d %H:%M"));
+        Some(Title::from(Span::styled(text, Style::new().fg(YELLOW))).alignment(Alignment::Right))
+    }
+
+    fn rate_limit_title(&self) -> Option<Title<'static>> {
+        let rate_limit = self.client.rate_limit()?;
+        let color = if rate_limit.is_exhausted() {
+            RED
+        } else if rate_limit.is_low() {
+            YELLOW
+        } else {
+            OVERLAY0
+        };
+        let text = format!(
+            " API {}/{} · resets {} ",
+            rate_limit.remaining,
+            rate_limit.limit,
+            rate_limit.reset_at.with_timezone(&chrono::Local).format("%H:%M")
+        );
+        Some(
+            Title::from(Span::styled(text, Style::new().fg(color)))
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        )
+    }
+
+    fn open_review_prompt(&mut self, pull_request: PullRequest, event: PullRequestReviewEvent) {
+        let pending_comments = match self.info_overlay.pending_review(&pull_request) {
+            Some(pending_review) => pending_review.comments,
+            None => {
+                self.load_pending_review(&pull_request);
+                0
+            },
+        };
+        let prompt = ReviewPrompt::new(pull_request, event).with_pending_comments(pending_comments);
+        if prompt.requires_body() {
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Action::SwitchMode(Mode::Insert));
+            }
+        }
+        self.review_prompt = Some(prompt);
+    }
 
+    /// Looks up a pending review the overlay does not know of, e.g. one started in a diff that was closed since, so
+    /// that the review prompt knows about its comments.
+    fn load_pending_review(&self, pull_request: &PullRequest) {
+        let Some(tx) = self.command_tx.clone() else {
+            return;
+        };
+        let client = self.client.clone();
+        let retry = self.config.config.retry.clone();
+        let (repository, number) = (pull_request.repository.clone(), pull_request.number);
         tokio::spawn(async move {
-            match GraphQLGithubClient::get_pull_requests_paginated(username, page_size, after).await {
-                Ok((pull_requests, has_next_page, end_cursor)) => {
-                    let _ = tx.send(Action::LoadMorePullRequestsResult(pull_requests, has_next_page, end_cursor));
+            let Some((owner, repo)) = repository.split_once('/') else {
+                return;
+            };
+            let request = || client.get_pending_review(owner.to_string(), repo.to_string(), number);
+            match retry.run(request, Self::notify_retry(&tx)).await {
+                Ok(Some(pending_review)) => {
+                    let _ = tx.send(Action::PendingReviewLoaded(repository.clone(), number, pending_review));
+                },
+                Ok(None) => {},
+                // the review is still submitted with the pending review, which is looked up again then
+                Err(e) => debug!("Failed to load the pending review: {}", e),
+            }
+        });
+    }
+
+    fn close_review_prompt(&mut self) {
+        if let Some(prompt) = self.review_prompt.take() {
+            if prompt.requires_body() {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Action::SwitchMode(Mode::Normal));
+                }
+            }
+        }
+    }
+
+    fn open_comment_prompt(&mut self, pull_request: PullRequest, target: CommentTarget) {
+        self.comment_prompt = Some(CommentPrompt::new(pull_request, target));
+        self.switch_mode(Mode::Insert);
+    }
+
+    fn close_comment_prompt(&mut self) {
+        if self.comment_prompt.take().is_some() {
+            self.switch_mode(Mode::Normal);
+        }
+    }
+
+    /// Applies the filter bar's query to every section, so switching tabs keeps filtering.
+    fn apply_filter(&mut self) {
+        let filter = PullRequestFilter::new(self.filter_bar.query());
+        for section in &mut self.sections {
+            section.set_filter(filter.clone());
+        }
+    }
+
+    fn switch_mode(&self, mode: Mode) {
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::SwitchMode(mode));
+        }
+    }
+
+    /// Handles the actions that start, finish and clear filtering. Returns `None` if the action should be processed as
+    /// usual, e.g. moving the selection while typing.
+    fn update_filter_bar(&mut self, action: &Action) -> Option<Option<Action>> {
+        match action {
+            Action::Search if !self.show_info_overlay => {
+                self.filter_bar.start_editing();
+                self.switch_mode(Mode::Search);
+            },
+            Action::Enter if self.filter_bar.is_editing() => {
+                self.filter_bar.stop_editing();
+                self.switch_mode(Mode::Normal);
+            },
+            Action::Escape if self.filter_bar.is_editing() => {
+                self.filter_bar.clear();
+                self.apply_filter();
+                self.switch_mode(Mode::Normal);
+            },
+            Action::Escape if self.filter_bar.is_active() && !self.show_info_overlay => {
+                self.filter_bar.clear();
+                self.apply_filter();
+            },
+            _ => return None,
+        }
+        Some(Some(Action::Render))
+    }
+
+    /// Handles actions while the review prompt is open. Returns `None` if the action should be processed as usual.
+    fn update_review_prompt(&mut self, action: &Action) -> Option<Option<Action>> {
+        let prompt = self.review_prompt.as_ref()?;
+        match action {
+            Action::Enter => {
+                if let Some(body) = prompt.confirm() {
+                    let (pull_request, event) = (prompt.pull_request().clone(), prompt.event());
+                    self.close_review_prompt();
+                    let _ = self.submit_review(pull_request, event, body);
+                }
+                Some(Some(Action::Render))
+            },
+            Action::Escape | Action::Back => {
+                self.close_review_prompt();
+                Some(Some(Action::Render))
+            },
+            action if Self::is_blocked_by_prompt(action) => Some(None),
+            _ => None,
+        }
+    }
+
+    /// Handles actions while the comment prompt is open. Returns `None` if the action should be processed as usual.
+    fn update_comment_prompt(&mut self, action: &Action) -> Option<Option<Action>> {
+        let prompt = self.comment_prompt.as_ref()?;
+        match action {
+            Action::Enter => {
+                if let Some(body) = prompt.confirm() {
+                    let (pull_request, target) = (prompt.pull_request().clone(), prompt.target().clone());
+                    self.close_comment_prompt();
+                    self.info_overlay.add_review_comment(pull_request, target, body);
+                }
+                Some(Some(Action::Render))
+            },
+            Action::Escape | Action::Back => {
+                self.close_comment_prompt();
+                Some(Some(Action::Render))
+            },
+            action if Self::is_blocked_by_prompt(action) => Some(None),
+            _ => None,
+        }
+    }
+
+    fn open_info_overlay(&mut self, pr: PullRequest) {
+        // comments on the diff keep being added to the same pending review
+        let pending_review = self.info_overlay.pending_review(&pr);
+        self.info_overlay = PullRequestInfoOverlay::new(self.client.clone())
+            .with_cache(self.cache.clone())
+            .with_pull_request(pr)
+            .with_pending_review(pending_review);
+
+        // Register the action handler for the overlay
+        if let Some(tx) = &self.command_tx {
+            let _ = self.info_overlay.register_action_handler(tx.clone());
+            let _ = self.info_overlay.register_config_handler(self.config.clone());
+        }
+    }
+
+    /// Actions of keys that are typed into an open prompt rather than acting on the list.
+    fn is_blocked_by_prompt(action: &Action) -> bool {
+        matches!(
+            action,
+            Action::Up
+                | Action::Down
+                | Action::Left
+                | Action::Right
+                | Action::PageUp
+                | Action::PageDn
+                | Action::Info
+                | Action::Open
+                | Action::Refresh
+                | Action::Sort(_)
+                | Action::NextTab
+                | Action::PreviousTab
+                | Action::Search
+                | Action::SearchPrompt
+                | Action::ToggleGroups
+                | Action::Diff
+                | Action::SplitDiff
+                | Action::FileTree
+                | Action::ToggleViewed
+                | Action::SelectLines
+                | Action::CommentLines
+                | Action::Review(_)
+                | Action::ConfirmReview(_, _)
+        )
+    }
+
+    /// Handles actions while the search prompt is open, and opening it. Returns `None` if the action should be
+    /// processed as usual.
+    fn update_search_prompt(&mut self, action: &Action) -> Option<Option<Action>> {
+        let Some(prompt) = &self.search_prompt else {
+            if *action == Action::SearchPrompt && !self.show_info_overlay {
+                let history = self.search_history.as_ref().map(|history| history.entries().to_vec());
+                self.search_prompt = Some(SearchPrompt::new(history.unwrap_or_default()));
+                self.switch_mode(Mode::Insert);
+                return Some(Some(Action::Render));
+            }
+            return None;
+        };
+        match action {
+            Action::Enter => {
+                if let Some(query) = prompt.confirm() {
+                    self.search_prompt = None;
+                    self.switch_mode(Mode::Normal);
+                    self.search(&query);
+                }
+                Some(Some(Action::Render))
+            },
+            Action::Escape | Action::Back => {
+                self.search_prompt = None;
+                self.switch_mode(Mode::Normal);
+                Some(Some(Action::Render))
+            },
+            action if Self::is_blocked_by_prompt(action) => Some(None),
+            _ => None,
+        }
+    }
+
+    /// Shows the results of an ad-hoc search in its own tab, replacing the previous one. A query that is already
+    /// configured as a section switches to its tab instead.
+    fn search(&mut self, query: &str) {
+        if let Some(history) = &mut self.search_history {
+            if let Err(err) = history.add(query) {
+                warn!("Could not save search history: {:?}", err);
+            }
+        }
+        // configured queries are compared with `{user}` filled in, as they are searched
+        if let Some(index) = self
+            .sections
+            .iter()
+            .position(|section| !section.is_ad_hoc && section.section.query_for(&self.username) == query)
+        {
+            self.active_section = index;
+            return;
+        }
+        self.sections.retain(|section| !section.is_ad_hoc);
+        let mut section = PullRequestSection::ad_hoc(query);
+        section.set_filter(PullRequestFilter::new(self.filter_bar.query()));
+        self.sections.push(section);
+        self.active_section = self.sections.len() - 1;
+        self.load_section(self.active_section, self.initial_load_size, None);
+    }
+
+    fn submit_review(
+        &mut self,
+        pull_request: PullRequest,
+        event: PullRequestReviewEvent,
+        body: Option<String>,
+    ) -> Result<()> {
+        let tx = self.command_tx.clone().unwrap();
+        tx.send(Action::Notify(Notification::Info(format!(
+            "Submitting review for #{} in {}...",
+            pull_request.number, pull_request.repository
+        ))))?;
+        let client = self.client.clone();
+        let retry = self.config.config.retry.clone();
+        // review comments written in the diff are part of a pending review, which is submitted instead
+        let pending_review = self.info_overlay.pending_review(&pull_request);
+        // only looking up the pending review is retried: a review that timed out may still have been submitted, and a
+        // retry would submit it twice
+        tokio::spawn(async move {
+            let review = async {
+                // the pending review may have been started by an overlay closed since, or on GitHub
+                let pending_review = match (pending_review, pull_request.repository.split_once('/')) {
+                    (Some(pending_review), _) => Some(pending_review),
+                    (None, Some((owner, repo))) => {
+                        let request =
+                            || client.get_pending_review(owner.to_string(), repo.to_string(), pull_request.number);
+                        retry.run(request, Self::notify_retry(&tx)).await?
+                    },
+                    (None, None) => None,
+                };
+                match pending_review {
+                    Some(pending_review) => client.submit_pending_review(pending_review.id, event, body).await,
+                    None => client.submit_pull_request_review(&pull_request, event, body).await,
+                }
+            }
+            .await;
+            match review {
+                Ok(review) => {
+                    let _ = tx.send(Action::Notify(Notification::Info(format!(
+                        "{} #{} in {}: done",
+                        event, pull_request.number, pull_request.repository
+                    ))));
+                    let _ =
+                        tx.send(Action::ReviewSubmitted(pull_request.repository.clone(), pull_request.number, review));
                 },
                 Err(err) => {
-                    error!("Error loading more pull requests: {:?}", err);
-                    let _ = tx.send(Action::Error(err.to_string()));
+                    error!("Error submitting review: {:?}", err);
+                    let _ = tx.send(Action::Error(format!("{:#}", err)));
                 },
             }
         });
         Ok(())
     }
 
-    fn selected_column(columns: Vec<&'static str>, selected_column: usize) -> Vec<Cell> {
+    /// The columns the sort keys refer to: those shown, or the configured ones before the table was drawn.
+    fn columns(&self) -> Vec<Column> {
+        if self.shown_columns.is_empty() {
+            self.config.config.columns().iter().map(|c| c.column).collect()
+        } else {
+            self.shown_columns.clone()
+        }
+    }
+
+    /// Sorts the active section by the column `offset` columns left or right of the one it is sorted by.
+    fn sort_by_neighbour(&mut self, offset: isize) {
+        let columns = self.columns();
+        let section = self.section_mut();
+        let index = columns.iter().position(|&c| c == section.sort_column).unwrap_or_default();
+        let neighbour = index.saturating_add_signed(offset).min(columns.len().saturating_sub(1));
+        if let Some(&column) = columns.get(neighbour) {
+            section.sort_by(column, false);
+        }
+    }
+
+    fn header(columns: &[ColumnConfig], sort_column: Column, descending: bool) -> Vec<Cell<'static>> {
         columns
             .iter()
-            .enumerate()
-            .map(|(i, &column)| {
-                if i == selected_column {
-                    Cell::from(column).style(Style::new().fg(PEACH))
+            .map(|c| {
+                let header = c.column.header();
+                if c.column == sort_column {
+                    let arrow = if descending { "▼" } else { "▲" };
+                    Cell::from(format!("{header} {arrow}")).style(Style::new().fg(PEACH))
                 } else {
-                    Cell::from(column).style(Style::new().fg(TEXT))
+                    Cell::from(header).style(Style::new().fg(TEXT))
                 }
             })
             .collect()
     }
 
+    fn render_tabs(&self, f: &mut ratatui::prelude::Frame<'_>, area: Rect) {
+        let tabs = Tabs::new(self.sections.iter().map(PullRequestSection::title))
+            .select(self.active_section)
+            .style(Style::new().fg(OVERLAY0))
+            .highlight_style(Style::new().fg(PEACH).add_modifier(Modifier::BOLD))
+            .divider(Span::styled("|", Style::new().fg(OVERLAY0)));
+        f.render_widget(tabs, area);
+    }
+
     fn render_pull_requests_table(&mut self, f: &mut ratatui::prelude::Frame<'_>, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(ROSEWATER)
+            .style(Style::default().bg(BASE).fg(TEXT));
+        let block = self.stale_title().into_iter().chain(self.rate_limit_title()).fold(block, Block::title);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let [tabs_area, filter_area, table_area] =
+            *Layout::new(Direction::Vertical, [Constraint::Length(1), Constraint::Length(1), Constraint::Min(0)])
+                .split(inner)
+        else {
+            return;
+        };
+        self.render_tabs(f, tabs_area);
+        if self.filter_bar.is_active() {
+            let _ = self.filter_bar.draw(f, filter_area);
+        }
+
+        // the highlight symbol takes the first cells of each row
+        let columns = fit_columns(&self.config.config.columns(), table_area.width.saturating_sub(3));
+        self.shown_columns = columns.iter().map(|c| c.column).collect();
+        let now = Utc::now();
+        let section = &mut self.sections[self.active_section];
         let mut rows: Vec<Row<'static>> = vec![];
-        if let Some(pull_requests) = &self.pull_requests {
-            rows = pull_requests
-                .iter()
-                .map(|pr: &PullRequest| {
-                    Row::new(vec![
-                        Cell::from(format!("{:}", pr.number)),
-                        Cell::from(pr.repository.clone()),
-                        Cell::from(pr.title.clone()),
-                        Cell::from(pr.author.clone()),
-                        Cell::from(format!("{}", pr.created_at.format("%Y-%m-%d"))),
-                        Cell::from(format!("{}", pr.updated_at.format("%Y-%m-%d"))),
-                        Cell::from(Line::from(vec![
-                            Span::styled(format!("{:+}", pr.additions), Style::new().fg(GREEN)),
-                            Span::styled(format!("{:+}", (0 - pr.deletions as isize)), Style::new().fg(RED)),
-                        ])),
-                        Cell::from(match pr.state {
-                            PullRequestState::Open => {
-                                if pr.is_draft {
-                                    "DRAFT"
-                                } else {
-                                    "OPEN"
-                                }
-                            },
-                            PullRequestState::Closed => "CLOSED",
-                            PullRequestState::Merged => "MERGED",
-                        }),
-                        Cell::from(Line::from(
-                            pr.reviews
-                                .iter()
-                                .flat_map(|prr| {
-                                    vec![
-                                        Span::styled(prr.author.clone(), match prr.state {
-                                            PullRequestReviewState::Commented => Style::new().fg(BLUE),
-                                            PullRequestReviewState::Approved => Style::new().fg(GREEN),
-                                            PullRequestReviewState::ChangesRequested => Style::new().fg(YELLOW),
-                                            _ => Style::new().fg(Color::Gray),
-                                        }),
-                                        Span::raw(" "),
-                                    ]
-                                })
-                                .collect::<Vec<Span>>(),
-                        )),
-                    ])
+        if section.pull_requests.is_some() {
+            rows = section
+                .rows()
+                .into_iter()
+                .map(|row| {
+                    match row {
+                        SectionRow::PullRequest(pr, matched) => {
+                            Row::new(columns.iter().map(|c| c.column.cell(pr, &matched, now)))
+                        },
+                        SectionRow::Group { repository, count, collapsed } => {
+                            Self::group_header(&columns, repository, count, collapsed)
+                        },
+                    }
                 })
                 .collect::<Vec<_>>();
 
             // Add loading indicator if we're loading more PRs
-            if self.is_loading_more {
-                rows.push(Row::new(vec![
-                    Cell::from(""),
-                    Cell::from("Loading more..."),
-                    Cell::from(""),
-                    Cell::from(""),
-                    Cell::from(""),
-                    Cell::from(""),
-                    Cell::from(""),
-                    Cell::from(""),
-                    Cell::from(""),
-                ]));
+            if section.is_loading {
+                rows.push(Row::new(
+                    columns.iter().map(|c| Cell::from(if c.column == Column::Title { "Loading more..." } else { "" })),
+                ));
             }
         }
-        self.table_state.select(Some(self.selected_row));
+        section.table_state.select(Some(section.selected_row));
         let table = Table::default()
-            .widths(Constraint::from_lengths([4, 40, 80, 10, 12, 12, 6, 6, 50]))
+            .widths(columns.iter().map(ColumnConfig::constraint))
             .rows(rows)
             .column_spacing(1)
-            .header(
-                Row::new(PullRequestList::selected_column(
-                    vec!["#", "Repository", "Title", "Author", "Created", "Updated", "Changes", "State", "Reviews"],
-                    self.selected_column,
-                ))
-                .bottom_margin(1),
-            )
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(ROSEWATER)
-                    .style(Style::default().bg(BASE).fg(TEXT)),
-            )
+            .header(Row::new(Self::header(&columns, section.sort_column, section.sort_descending)).bottom_margin(1))
             .highlight_style(Style::new().bg(SURFACE0).fg(TEXT).add_modifier(Modifier::BOLD))
             .highlight_symbol(">> ");
 
-        f.render_stateful_widget(table, area, &mut self.table_state);
+        f.render_stateful_widget(table, table_area, &mut section.table_state);
+    }
+
+    /// The header row of a repository's pull requests, e.g. `▾ owner/repo` followed by `3 pull requests`, or all of it
+    /// in the title column if the repository column is hidden.
+    fn group_header(columns: &[ColumnConfig], repository: &str, count: usize, collapsed: bool) -> Row<'static> {
+        let arrow = if collapsed { "▸" } else { "▾" };
+        let count = if count == 1 { String::from("1 pull request") } else { format!("{count} pull requests") };
+        let has_repository_column = columns.iter().any(|c| c.column == Column::Repository);
+        Row::new(columns.iter().map(|c| {
+            match c.column {
+                Column::Repository => Cell::from(format!("{arrow} {repository}")),
+                Column::Title if has_repository_column => Cell::from(count.clone()),
+                Column::Title => Cell::from(format!("{arrow} {repository} ({count})")),
+                _ => Cell::from(""),
+            }
+        }))
+        .style(Style::new().fg(LAVENDER).add_modifier(Modifier::BOLD))
     }
 
     fn render_placeholder(&self, f: &mut ratatui::prelude::Frame<'_>, area: Rect) {
@@ -255,50 +785,17 @@ impl PullRequestList {
         f.render_widget(text, centered_rect(area, 100, 10))
     }
 
-    fn sort_pull_requests(&mut self) {
-        if let Some(ref mut pull_requests) = self.pull_requests {
-            pull_requests.sort_by(|a, b| {
-                match self.selected_column {
-                    0 => a.number.cmp(&b.number),
-                    1 => a.repository.cmp(&b.repository),
-                    2 => a.title.cmp(&b.title),
-                    3 => a.author.cmp(&b.author),
-                    4 => a.created_at.cmp(&b.created_at),
-                    5 => a.updated_at.cmp(&b.updated_at),
-                    6 => (a.additions + a.deletions).cmp(&(b.additions + b.deletions)),
-                    7 => a.state.cmp(&b.state),
-                    _ => a.title.cmp(&b.title),
-                }
-            });
-        }
-    }
-
     fn refresh(&mut self) {
         let tx = self.command_tx.clone().unwrap();
         if self.username.is_empty() {
-            // Get username and then immediately fetch repos
-            let tx_clone = tx.clone();
+            // Get username and then refresh again to fetch the sections
+            let client = self.client.clone();
+            let retry = self.config.config.retry.clone();
             tokio::spawn(async move {
-                match GraphQLGithubClient::get_current_user().await {
+                match retry.run(|| client.get_current_user(), Self::notify_retry(&tx)).await {
                     Ok(username) => {
-                        if let Err(e) = tx.send(Action::GetCurrentUserResult(username.clone())) {
-                            tracing::error!("Failed to send user result: {}", e);
-                            return;
-                        }
-                        // Immediately fetch repos after getting username
-                        match GraphQLGithubClient::get_pull_requests_paginated(username, 10, None).await {
-                            Ok((pull_requests, has_next_page, end_cursor)) => {
-                                let _ = tx.send(Action::LoadMorePullRequestsResult(
-                                    pull_requests,
-                                    has_next_page,
-                                    end_cursor,
-                                ));
-                            },
-                            Err(err) => {
-                                error!("Error getting pull requests: {:?}", err);
-                                let _ = tx.send(Action::Error(err.to_string()));
-                            },
-                        }
+                        let _ = tx.send(Action::GetCurrentUserResult(username));
+                        let _ = tx.send(Action::Refresh);
                     },
                     Err(err) => {
                         error!("Error getting current user: {:?}", err);
@@ -311,127 +808,263 @@ impl PullRequestList {
         }
     }
 
-    fn render_token_error(&self, f: &mut ratatui::prelude::Frame<'_>, area: Rect) {
-        let text = Paragraph::new(vec![
-            Line::from("Error: GITHUB_TOKEN is not set!"),
+    fn render_token_error(&mut self, f: &mut ratatui::prelude::Frame<'_>, area: Rect) -> Result<()> {
+        let mut lines = vec![
+            Line::from("Error: no GitHub token found!"),
             Line::from("Create a Personal Access Token in the GitHub UI and set the GITHUB_TOKEN environment variable to its value before running ghtui"),
-            Line::from("Press 'q' or 'ctrl-c' to quit"),
-        ]).style(Style::default().fg(RED))
-        .alignment(Alignment::Center);
-        f.render_widget(text, centered_rect(area, 100, 10));
+        ];
+        if !self.token_attempts.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::styled("Tried:", Style::default().fg(TEXT)));
+            lines.extend(self.token_attempts.iter().map(|attempt| {
+                Line::from(vec![
+                    Span::styled(format!("{}: ", attempt.source), Style::default().fg(TEXT)),
+                    Span::styled(attempt.reason.clone(), Style::default().fg(OVERLAY0)),
+                ])
+            }));
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from("Press 'q' or 'ctrl-c' to quit"));
+
+        let height = lines.len() as u16 + 4;
+        let area =
+            Rect::new(area.x, area.y + area.height.saturating_sub(height) / 2, area.width, height.min(area.height));
+        let [error_area, login_area] =
+            *Layout::new(Direction::Vertical, [Constraint::Length(lines.len() as u16), Constraint::Min(0)]).split(area)
+        else {
+            return Ok(());
+        };
+        let text = Paragraph::new(lines).style(Style::default().fg(RED)).alignment(Alignment::Center);
+        f.render_widget(Clear, area);
+        f.render_widget(text, error_area);
+        self.login.draw(f, login_area.inner(&Margin::new(0, 1)))
     }
 }
 
-impl Component for PullRequestList {
+impl<C: GithubClient> Component for PullRequestList<C> {
     fn init(&mut self, area: Rect) -> Result<()> {
-        self.get_current_user()?;
+        if self.stale_since.is_some() && self.client.is_authenticated() {
+            self.refresh();
+        } else {
+            self.get_current_user()?;
+        }
         Ok(())
     }
 
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.login.register_action_handler(tx.clone())?;
         self.command_tx = Some(tx);
         Ok(())
     }
 
     fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.login.register_config_handler(config.clone())?;
+        self.sections = PullRequestSection::from_config(&config, std::mem::take(&mut self.sections));
+        self.active_section = self.active_section.min(self.sections.len().saturating_sub(1));
+        self.search_history = Some(SearchHistory::load(&config.config._data_dir));
         self.config = config;
+        self.restore_from_cache();
+        self.apply_filter();
         Ok(())
     }
 
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if let Some(prompt) = &mut self.review_prompt {
+            return prompt.handle_key_events(key);
+        }
+        if let Some(prompt) = &mut self.comment_prompt {
+            return prompt.handle_key_events(key);
+        }
+        if let Some(prompt) = &mut self.search_prompt {
+            return prompt.handle_key_events(key);
+        }
+        let action = self.filter_bar.handle_key_events(key)?;
+        if action.is_some() {
+            self.apply_filter();
+        }
+        Ok(action)
+    }
+
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if action == Action::LoginCompleted {
+            if let Some(tx) = &self.command_tx {
+                tx.send(Action::Notify(Notification::Info(String::from("Logged in"))))?;
+            }
+            self.login.update(action)?;
+            self.refresh();
+            return Ok(Some(Action::Render));
+        }
+        if !self.client.is_authenticated() {
+            return self.login.update(action);
+        }
+        if let Some(result) = self.update_review_prompt(&action) {
+            return Ok(result);
+        }
+        if let Some(result) = self.update_comment_prompt(&action) {
+            return Ok(result);
+        }
+        if let Some(result) = self.update_search_prompt(&action) {
+            return Ok(result);
+        }
+        if let Some(result) = self.update_filter_bar(&action) {
+            return Ok(result);
+        }
+
         // Always pass certain actions to the overlay if it exists
         match &action {
-            Action::PullRequestDetailsLoaded(_) | Action::PullRequestDetailsLoadError => {
+            Action::PullRequestDetailsLoaded(_)
+            | Action::PullRequestDetailsLoadError
+            | Action::ReviewSubmitted(_, _, _) => {
                 self.info_overlay.update(action.clone())?;
             },
             _ => {},
         }
 
+        match &action {
+            Action::ConfirmReview(pull_request, event) => {
+                self.open_review_prompt(*pull_request.clone(), *event);
+                return Ok(Some(Action::Render));
+            },
+            Action::PendingReviewLoaded(repository, number, pending_review) => {
+                if let Some(prompt) = self.review_prompt.take() {
+                    let pr = prompt.pull_request();
+                    self.review_prompt = Some(if &pr.repository == repository && pr.number == *number {
+                        prompt.with_pending_comments(pending_review.comments)
+                    } else {
+                        prompt
+                    });
+                }
+                self.info_overlay.update(action.clone())?;
+                return Ok(Some(Action::Render));
+            },
+            Action::WriteComment(pull_request, target) => {
+                self.open_comment_prompt(*pull_request.clone(), target.clone());
+                return Ok(Some(Action::Render));
+            },
+            Action::ReviewSubmitted(repository, number, review) => {
+                // the pull request may be listed in several sections
+                for pr in self
+                    .sections
+                    .iter_mut()
+                    .flat_map(|section| section.pull_requests.iter_mut().flatten())
+                    .filter(|pr| &pr.repository == repository && pr.number == *number)
+                {
+                    pr.upsert_review(review.clone());
+                }
+                return Ok(Some(Action::Render));
+            },
+            _ => {},
+        }
+
         if self.show_info_overlay {
             self.info_overlay.update(action.clone())?;
         } else {
             match &action {
                 Action::Tick => {},
                 Action::Up => {
-                    self.selected_row = self.selected_row.saturating_sub(1);
+                    self.section_mut().select_previous();
                     return Ok(Some(Action::Render));
                 },
                 Action::Down => {
-                    if let Some(pull_requests) = &self.pull_requests {
-                        self.selected_row = std::cmp::min(self.selected_row + 1, pull_requests.len() - 1);
-                    }
+                    self.section_mut().select_next();
+                    self.load_more_near_end();
                     return Ok(Some(Action::Render));
                 },
-                Action::Left => {
-                    self.selected_column = self.selected_column.saturating_sub(1);
-                    self.sort_pull_requests();
+                // the grouped view folds with left and right, the number keys still sort
+                Action::Left if self.section().grouped => {
+                    self.section_mut().collapse();
+                    return Ok(Some(Action::Render));
                 },
-                Action::Right => {
-                    self.selected_column = self.selected_column.saturating_add(1);
-                    self.sort_pull_requests();
+                Action::Right if self.section().grouped => {
+                    self.section_mut().expand();
+                    return Ok(Some(Action::Render));
+                },
+                Action::Left => self.sort_by_neighbour(-1),
+                Action::Right => self.sort_by_neighbour(1),
+                Action::ToggleGroups => {
+                    self.section_mut().toggle_grouped();
+                    return Ok(Some(Action::Render));
+                },
+                Action::Sort(index) => {
+                    let Some(column) = self.columns().get(*index).copied() else {
+                        return Ok(None);
+                    };
+                    // sorting by the same column again reverses the order
+                    let section = self.section_mut();
+                    let descending = column == section.sort_column && !section.sort_descending;
+                    section.sort_by(column, descending);
+                    return Ok(Some(Action::Render));
+                },
+                Action::NextTab => {
+                    self.active_section = (self.active_section + 1) % self.sections.len();
+                    return Ok(Some(Action::Render));
+                },
+                Action::PreviousTab => {
+                    self.active_section = (self.active_section + self.sections.len() - 1) % self.sections.len();
+                    return Ok(Some(Action::Render));
                 },
                 Action::Refresh => {
                     self.refresh();
                 },
-                Action::GetReposResult(pull_requests) => {
-                    // Legacy action - convert to new format
-                    self.pull_requests = Some(pull_requests.clone());
-                    self.has_next_page = false; // Legacy mode has no pagination
-                    self.end_cursor = None;
-                    self.is_loading_more = false;
-                },
-                Action::LoadMorePullRequestsResult(new_pull_requests, has_next_page, end_cursor) => {
-                    if let Some(ref mut existing_prs) = self.pull_requests {
-                        // Append new PRs to existing ones
-                        existing_prs.extend(new_pull_requests.clone());
-                        existing_prs.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-                        existing_prs.dedup();
-                    } else {
-                        // First load
-                        self.pull_requests = Some(new_pull_requests.clone());
+                Action::LoadMorePullRequestsResult(query, after, new_pull_requests, has_next_page, end_cursor) => {
+                    // the section may have been replaced while the page loaded, e.g. by another ad-hoc search, or
+                    // refreshed, which the pages requested before must not be mixed into
+                    if let Some(index) = self
+                        .sections
+                        .iter()
+                        .position(|section| &section.section.query == query && section.is_awaiting(after))
+                    {
+                        // further pages are loaded as the selection nears the end, not all up front
+                        self.sections[index].add_page(new_pull_requests.clone(), *has_next_page, end_cursor.clone());
                     }
 
-                    self.has_next_page = *has_next_page;
-                    self.end_cursor = end_cursor.clone();
-                    self.is_loading_more = false;
-
-                    if self.has_next_page {
-                        let _ = self.load_more_pull_requests();
+                    self.finish_refresh();
+                },
+                // the page can be requested again, e.g. by refreshing or scrolling to the end of the section
+                Action::LoadMorePullRequestsError(query, after) => {
+                    if let Some(section) = self
+                        .sections
+                        .iter_mut()
+                        .find(|section| &section.section.query == query && section.is_awaiting(after))
+                    {
+                        section.is_loading = false;
                     }
-
-                    self.sort_pull_requests();
+                    self.finish_refresh();
+                    return Ok(Some(Action::Render));
                 },
                 Action::Open => {
-                    if let Some(pull_requests) = &self.pull_requests {
-                        if let Some(pr) = pull_requests.get(self.selected_row) {
-                            let url = pr.url.clone();
-                            let _ = open::that(url);
-                        }
+                    if let Some(pr) = self.section().selected() {
+                        let _ = open::that(self.client.host().pull_request_url(&pr.repository, pr.number));
                     }
                 },
                 Action::GetCurrentUser => {
                     let _ = self.get_current_user();
                 },
                 Action::GetCurrentUserResult(user) => self.username.clone_from(user),
-                Action::Sort(column) => todo!(),
+                Action::Review(event) => {
+                    if let Some(pr) = self.section().selected().cloned() {
+                        self.open_review_prompt(pr, *event);
+                        return Ok(Some(Action::Render));
+                    }
+                },
                 _ => {},
             }
         }
 
         match action {
             Action::Info | Action::Enter => {
-                if let Some(pull_requests) = &self.pull_requests {
-                    if let Some(pr) = pull_requests.get(self.selected_row) {
-                        self.info_overlay = PullRequestInfoOverlay::new().with_pull_request(pr.clone());
-
-                        // Register the action handler for the overlay
-                        if let Some(tx) = &self.command_tx {
-                            let _ = self.info_overlay.register_action_handler(tx.clone());
-                            let _ = self.info_overlay.register_config_handler(self.config.clone());
-                        }
-
-                        self.show_info_overlay = !self.show_info_overlay;
-                    }
+                if let Some(pr) = self.section().selected().cloned() {
+                    self.open_info_overlay(pr);
+                    self.show_info_overlay = !self.show_info_overlay;
+                }
+            },
+            // the overlay shown already switches between the description and the diff itself
+            Action::Diff if !self.show_info_overlay => {
+                if let Some(pr) = self.section().selected().cloned() {
+                    self.open_info_overlay(pr);
+                    self.info_overlay.update(Action::Diff)?;
+                    self.show_info_overlay = true;
+                    return Ok(Some(Action::Render));
                 }
             },
             Action::Escape | Action::Back => self.show_info_overlay = false,
@@ -443,18 +1076,26 @@ impl Component for PullRequestList {
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
         self.render_pull_requests_table(f, area);
-        // if GITHUB_TOKEN is not set, display a placeholder
-        if std::env::var("GITHUB_TOKEN").is_err() {
-            self.render_token_error(f, area);
-            return Ok(());
+        // if no token is configured, display a placeholder
+        if !self.client.is_authenticated() {
+            return self.render_token_error(f, area);
         }
 
-        if self.pull_requests.is_none() {
+        if self.section().pull_requests.is_none() {
             self.render_placeholder(f, area);
         }
         if self.show_info_overlay {
             self.info_overlay.draw(f, area.inner(&Margin::new(4, 4)))?;
         }
+        if let Some(prompt) = &mut self.review_prompt {
+            prompt.draw(f, area)?;
+        }
+        if let Some(prompt) = &mut self.comment_prompt {
+            prompt.draw(f, area)?;
+        }
+        if let Some(prompt) = &mut self.search_prompt {
+            prompt.draw(f, area)?;
+        }
 
         Ok(())
     }
@@ -462,21 +1103,100 @@ impl Component for PullRequestList {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use rstest::rstest;
     use sealed_test::prelude::*;
-    use tokio::sync::mpsc;
+    use tokio::sync::mpsc::{self, UnboundedReceiver};
 
     use super::*;
+    use crate::{
+        cache::{CachedSection, PullRequestCache},
+        components::{
+            diff::{
+                tests::changed_file, ChangedFile, CommentTarget, DiffSide, FileStatus, PendingReview, ReviewThreads,
+            },
+            pull_request::tests::pull_request,
+        },
+        github::{
+            error::GithubError,
+            fake::{FakeGithubClient, FakeMethod},
+            rate_limit::RateLimit,
+            retry::RetryPolicy,
+        },
+    };
+
+    const INVOLVED_QUERY: &str = "is:pr involves:{user} state:open";
+    const REVIEW_REQUESTED_QUERY: &str = "is:pr review-requested:{user} state:open";
+    const INVOLVED: &str = "is:pr involves:octocat state:open";
+    const REVIEW_REQUESTED: &str = "is:pr review-requested:octocat state:open";
+
+    fn two_sections() -> Config {
+        config_with_sections(vec![
+            Section::new("Involved", INVOLVED_QUERY),
+            Section::new("Review requested", REVIEW_REQUESTED_QUERY),
+        ])
+    }
+
+    fn numbers(item_list: &PullRequestList<FakeGithubClient>) -> Vec<usize> {
+        let mut numbers: Vec<usize> = item_list.pull_requests().iter().map(|pr| pr.number).collect();
+        numbers.sort();
+        numbers
+    }
+
+    /// A config with only the given sections, so tests control which searches run.
+    fn config_with_sections(sections: Vec<Section>) -> Config {
+        let mut config = Config::default();
+        config.config.sections = sections;
+        config
+    }
+
+    /// A list with a single section, whose searches are answered by pages scripted for any query.
+    fn pull_request_list(
+        client: impl Into<Arc<FakeGithubClient>>,
+    ) -> (PullRequestList<FakeGithubClient>, UnboundedReceiver<Action>) {
+        setup(PullRequestList::new(client.into()))
+    }
+
+    fn setup(
+        mut item_list: PullRequestList<FakeGithubClient>,
+    ) -> (PullRequestList<FakeGithubClient>, UnboundedReceiver<Action>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        item_list.register_action_handler(tx).unwrap();
+        item_list
+            .register_config_handler(config_with_sections(vec![Section::new("Involved", INVOLVED_QUERY)]))
+            .unwrap();
+        (item_list, rx)
+    }
+
+    /// Feeds the actions sent by the list's background tasks back into it, like the app loop does, until one
+    /// matching `until` arrives.
+    async fn pump(
+        item_list: &mut PullRequestList<FakeGithubClient>,
+        rx: &mut UnboundedReceiver<Action>,
+        until: impl Fn(&Action) -> bool,
+    ) -> Action {
+        loop {
+            let action = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timed out waiting for action")
+                .expect("action channel closed");
+            item_list.update(action.clone()).unwrap();
+            if until(&action) {
+                return action;
+            }
+        }
+    }
 
     #[test]
     fn test_new() {
-        let item_list = PullRequestList::new();
-        assert_eq!(item_list.selected_row, 0);
+        let item_list = PullRequestList::new(FakeGithubClient::default().into_arc());
+        assert_eq!(item_list.section().selected_row, 0);
     }
 
     #[test]
     fn test_up_down_actions() {
-        let mut item_list = PullRequestList::new();
+        let mut item_list = PullRequestList::new(FakeGithubClient::default().into_arc());
         assert_eq!(item_list.update(Action::Up).unwrap(), Some(Action::Render));
         assert_eq!(item_list.update(Action::Down).unwrap(), Some(Action::Render));
     }
@@ -486,7 +1206,8 @@ mod tests {
     #[case(Action::Escape)]
     #[case(Action::Back)]
     fn test_dismiss_info_overlay_actions(#[case] action: Action) {
-        let mut item_list = PullRequestList::default();
+        let mut item_list = PullRequestList::new(FakeGithubClient::default().into_arc());
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
         // simulate opening the info overlay
         assert_eq!(item_list.update(Action::Info).unwrap(), None);
         assert!(item_list.show_info_overlay);
@@ -495,4 +1216,762 @@ mod tests {
         assert_eq!(item_list.update(action).unwrap(), None);
         assert!(!item_list.show_info_overlay)
     }
+
+    #[tokio::test]
+    async fn test_next_page_is_loaded_near_the_end() {
+        let client = FakeGithubClient::new("octocat")
+            .with_page(None, vec![pull_request("owner/repo", 1), pull_request("owner/repo", 2)], Some("c1"))
+            .with_page(Some("c1"), vec![pull_request("owner/other", 3)], None)
+            .into_arc();
+        let (mut item_list, mut rx) = pull_request_list(client.clone());
+
+        item_list.update(Action::Refresh).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+        // only the first page is loaded by refreshing
+        assert!(item_list.has_next_page());
+        assert!(!item_list.is_loading_more());
+        assert_eq!(client.calls(), vec![FakeMethod::GetCurrentUser, FakeMethod::SearchPullRequests]);
+
+        item_list.update(Action::Down).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(_, _, _, false, None))).await;
+
+        assert_eq!(item_list.username, "octocat");
+        assert!(!item_list.has_next_page());
+        assert!(!item_list.is_loading_more());
+        let mut numbers: Vec<usize> = item_list.pull_requests().iter().map(|pr| pr.number).collect();
+        numbers.sort();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(client.calls(), vec![
+            FakeMethod::GetCurrentUser,
+            FakeMethod::SearchPullRequests,
+            FakeMethod::SearchPullRequests
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_pages_requested_before_a_refresh_are_dropped() {
+        let client = FakeGithubClient::new("octocat")
+            .with_page(None, vec![pull_request("owner/repo", 1), pull_request("owner/repo", 2)], Some("c1"))
+            .with_page(Some("c1"), vec![pull_request("owner/other", 3)], None)
+            .into_arc();
+        let (mut item_list, mut rx) = pull_request_list(client.clone());
+        item_list.update(Action::Refresh).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+        item_list.update(Action::Down).unwrap();
+
+        // refreshed while the second page is still on its way
+        item_list.update(Action::Refresh).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(_, Some(_), ..))).await;
+        assert_eq!(numbers(&item_list), vec![1, 2]);
+        assert_eq!(item_list.section().end_cursor, None);
+        assert!(item_list.is_loading_more());
+
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(_, None, ..))).await;
+        item_list.update(Action::Down).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(_, Some(_), ..))).await;
+        assert_eq!(numbers(&item_list), vec![1, 2, 3]);
+        assert!(!item_list.has_next_page());
+        assert!(!item_list.is_loading_more());
+        let afters: Vec<_> = client.searches().into_iter().map(|(_, after)| after).collect();
+        assert_eq!(afters, vec![None, Some(String::from("c1")), None, Some(String::from("c1"))]);
+    }
+
+    #[tokio::test]
+    async fn test_sections_are_paginated_independently() {
+        let client = FakeGithubClient::new("octocat")
+            .with_search_page(
+                INVOLVED,
+                None,
+                vec![pull_request("owner/repo", 1), pull_request("owner/repo", 2)],
+                Some("i1"),
+            )
+            .with_search_page(INVOLVED, Some("i1"), vec![pull_request("owner/other", 3)], None)
+            .with_search_page(REVIEW_REQUESTED, None, vec![pull_request("owner/repo", 2)], Some("r1"))
+            .with_search_page(REVIEW_REQUESTED, Some("r1"), vec![pull_request("team/repo", 4)], None)
+            .into_arc();
+        let (mut item_list, mut rx) = pull_request_list(client.clone());
+        item_list.register_config_handler(two_sections()).unwrap();
+
+        item_list.update(Action::Refresh).unwrap();
+        for _ in 0..2 {
+            pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(_, None, ..))).await;
+        }
+        // scrolling through each tab loads its next page
+        item_list.update(Action::Down).unwrap();
+        item_list.update(Action::NextTab).unwrap();
+        item_list.update(Action::Down).unwrap();
+        item_list.update(Action::PreviousTab).unwrap();
+        for _ in 0..2 {
+            pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(_, _, _, false, None)))
+                .await;
+        }
+
+        assert_eq!(numbers(&item_list), vec![1, 2, 3]);
+        item_list.update(Action::NextTab).unwrap();
+        assert_eq!(numbers(&item_list), vec![2, 4]);
+        assert_eq!(item_list.sections.iter().map(PullRequestSection::title).collect::<Vec<_>>(), vec![
+            "Involved (3)",
+            "Review requested (2)"
+        ]);
+        let mut searches = client.searches();
+        searches.sort();
+        assert_eq!(searches, vec![
+            (INVOLVED.to_string(), None),
+            (INVOLVED.to_string(), Some(String::from("i1"))),
+            (REVIEW_REQUESTED.to_string(), None),
+            (REVIEW_REQUESTED.to_string(), Some(String::from("r1"))),
+        ]);
+    }
+
+    #[test]
+    fn test_tabs_keep_their_selection_and_sort() {
+        let mut item_list = PullRequestList::new(FakeGithubClient::default().into_arc());
+        item_list.register_config_handler(two_sections()).unwrap();
+        item_list.sections[0].add_page(vec![pull_request("owner/repo", 1), pull_request("owner/repo", 2)], false, None);
+        item_list.sections[1].add_page(vec![pull_request("b/repo", 3), pull_request("a/repo", 4)], false, None);
+
+        item_list.update(Action::Down).unwrap();
+        assert_eq!(item_list.update(Action::NextTab).unwrap(), Some(Action::Render));
+        item_list.update(Action::Left).unwrap(); // sort by number, keeping a/repo#4 selected
+        assert_eq!(item_list.section().selected().unwrap().number, 4);
+        assert_eq!(item_list.section().selected_row, 1);
+
+        item_list.update(Action::PreviousTab).unwrap();
+        assert_eq!(item_list.section().selected().unwrap().number, 2);
+        item_list.update(Action::PreviousTab).unwrap();
+        assert_eq!(item_list.active_section, 1);
+        assert_eq!(item_list.section().sort_column, Column::Number);
+        assert_eq!(item_list.section().selected_row, 1);
+    }
+
+    #[test]
+    fn test_sort_toggles_direction() {
+        let mut item_list = PullRequestList::new(FakeGithubClient::default().into_arc());
+        let mut pull_requests: Vec<PullRequest> = (1..=3).map(|number| pull_request("owner/repo", number)).collect();
+        pull_requests[0].updated_at += chrono::Duration::hours(2);
+        pull_requests[2].updated_at += chrono::Duration::hours(1);
+        item_list.section_mut().add_page(pull_requests, false, None);
+
+        assert_eq!(item_list.update(Action::Sort(5)).unwrap(), Some(Action::Render));
+        assert_eq!(item_list.pull_requests().iter().map(|pr| pr.number).collect::<Vec<_>>(), vec![2, 3, 1]);
+        item_list.update(Action::Sort(5)).unwrap();
+        assert_eq!(item_list.pull_requests().iter().map(|pr| pr.number).collect::<Vec<_>>(), vec![1, 3, 2]);
+        assert!(item_list.section().sort_descending);
+
+        // another column starts ascending again
+        item_list.update(Action::Sort(0)).unwrap();
+        assert!(!item_list.section().sort_descending);
+        assert_eq!(item_list.pull_requests().iter().map(|pr| pr.number).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_grouped_view_folds_with_left_and_right() {
+        let mut item_list = PullRequestList::new(FakeGithubClient::default().into_arc());
+        item_list.section_mut().add_page(vec![pull_request("a/repo", 1), pull_request("b/repo", 2)], false, None);
+
+        assert_eq!(item_list.update(Action::ToggleGroups).unwrap(), Some(Action::Render));
+        assert_eq!(item_list.section().rows().len(), 4);
+        assert_eq!(item_list.section().selected().unwrap().number, 1);
+        assert_eq!(item_list.update(Action::Left).unwrap(), Some(Action::Render));
+        assert_eq!(item_list.section().rows().len(), 3);
+        assert_eq!(item_list.section().sort_column, Column::Repository);
+
+        item_list.update(Action::Sort(1)).unwrap();
+        assert_eq!(item_list.section().rows()[0], SectionRow::Group {
+            repository: "b/repo",
+            count: 1,
+            collapsed: false
+        });
+        item_list.update(Action::Right).unwrap();
+        assert_eq!(item_list.section().rows().len(), 4);
+    }
+
+    #[test]
+    fn test_filter_narrows_every_tab() {
+        let (mut item_list, mut rx) = pull_request_list(FakeGithubClient::new("octocat"));
+        item_list.register_config_handler(two_sections()).unwrap();
+        item_list.sections[0].add_page((1..=3).map(|number| pull_request("owner/repo", number)).collect(), false, None);
+        item_list.sections[1].add_page(vec![pull_request("owner/repo", 12), pull_request("team/repo", 4)], false, None);
+        item_list.update(Action::Down).unwrap();
+
+        item_list.update(Action::Search).unwrap();
+        assert_eq!(rx.try_recv().unwrap(), Action::SwitchMode(Mode::Search));
+        for c in "#2".chars() {
+            item_list.handle_key_events(KeyEvent::from(KeyCode::Char(c))).unwrap();
+        }
+        assert_eq!(numbers(&item_list), vec![1, 2, 3]);
+        assert_eq!(item_list.section().visible().map(|(pr, _)| pr.number).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(item_list.section().selected().unwrap().number, 2);
+
+        // the filter stays applied after typing, also in other tabs
+        item_list.update(Action::Enter).unwrap();
+        assert_eq!(rx.try_recv().unwrap(), Action::SwitchMode(Mode::Normal));
+        assert!(!item_list.show_info_overlay);
+        item_list.update(Action::NextTab).unwrap();
+        assert_eq!(item_list.section().selected().unwrap().number, 12);
+        assert_eq!(item_list.sections[1].title(), "Review requested (1/2)");
+
+        item_list.update(Action::Escape).unwrap();
+        assert_eq!(item_list.section().visible().count(), 2);
+        assert!(!item_list.filter_bar.is_active());
+    }
+
+    #[tokio::test]
+    async fn test_ad_hoc_search_is_shown_in_its_own_tab() {
+        let data_dir = std::env::temp_dir().join(format!("ghtui-list-search-{}", std::process::id()));
+        let client = FakeGithubClient::new("octocat")
+            .with_search_page("is:pr org:acme", None, vec![pull_request("acme/repo", 7)], None)
+            .with_search_page("is:pr label:bug", None, vec![pull_request("owner/repo", 8)], None)
+            .into_arc();
+        let (mut item_list, mut rx) = pull_request_list(client.clone());
+        let mut config = config_with_sections(vec![Section::new("Involved", INVOLVED_QUERY)]);
+        config.config._data_dir = data_dir.clone();
+        item_list.register_config_handler(config).unwrap();
+
+        for query in ["is:pr org:acme", "is:pr label:bug"] {
+            item_list.update(Action::SearchPrompt).unwrap();
+            assert_eq!(rx.try_recv().unwrap(), Action::SwitchMode(Mode::Insert));
+            for c in query.chars() {
+                item_list.handle_key_events(KeyEvent::from(KeyCode::Char(c))).unwrap();
+            }
+            // typed keys do not act on the list
+            assert_eq!(item_list.update(Action::NextTab).unwrap(), None);
+            item_list.update(Action::Enter).unwrap();
+            assert_eq!(rx.try_recv().unwrap(), Action::SwitchMode(Mode::Normal));
+            pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+        }
+
+        // the second search replaced the first one's tab
+        assert_eq!(item_list.sections.iter().map(PullRequestSection::title).collect::<Vec<_>>(), vec![
+            "Involved",
+            "is:pr label:bug (1)"
+        ]);
+        assert_eq!(item_list.section().selected().unwrap().number, 8);
+        assert_eq!(SearchHistory::load(&data_dir).entries(), ["is:pr org:acme", "is:pr label:bug"]);
+
+        item_list.update(Action::SearchPrompt).unwrap();
+        item_list.handle_key_events(KeyEvent::from(KeyCode::Up)).unwrap();
+        item_list.handle_key_events(KeyEvent::from(KeyCode::Up)).unwrap();
+        assert_eq!(item_list.search_prompt.as_ref().unwrap().confirm().as_deref(), Some("is:pr org:acme"));
+        item_list.update(Action::Escape).unwrap();
+        assert!(item_list.search_prompt.is_none());
+
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_searching_a_configured_query_switches_to_its_tab() {
+        let (mut item_list, _rx) = pull_request_list(FakeGithubClient::new("octocat"));
+        item_list.register_config_handler(two_sections()).unwrap();
+        item_list.username = String::from("octocat");
+        item_list.search_history = None;
+
+        item_list.search(REVIEW_REQUESTED);
+
+        assert_eq!(item_list.active_section, 1);
+        assert!(item_list.sections.iter().all(|section| !section.is_ad_hoc));
+    }
+
+    #[tokio::test]
+    async fn test_login_completed_loads_pull_requests() {
+        let client = FakeGithubClient::new("octocat")
+            .with_page(None, vec![pull_request("owner/repo", 1)], None)
+            .unauthenticated()
+            .into_arc();
+        let (mut item_list, mut rx) = pull_request_list(client.clone());
+
+        // keys go to the login screen while there is no token
+        item_list.update(Action::Refresh).unwrap();
+        assert!(client.calls().is_empty());
+
+        client.authenticate(String::from("gho_test")).unwrap();
+        item_list.update(Action::LoginCompleted).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+
+        assert_eq!(item_list.pull_requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pagination_waits_for_rate_limit_reset() {
+        let client = FakeGithubClient::new("octocat")
+            .with_page(None, vec![pull_request("owner/repo", 1)], Some("c1"))
+            .with_page(Some("c1"), vec![pull_request("owner/repo", 2)], None)
+            .into_arc();
+        let (mut item_list, mut rx) = pull_request_list(client.clone());
+
+        item_list.update(Action::Refresh).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+        let reset_at = chrono::Utc::now() + chrono::Duration::milliseconds(200);
+        client.set_rate_limit(RateLimit { limit: 5000, cost: 1, remaining: 0, reset_at });
+        item_list.update(Action::Down).unwrap();
+
+        let action = pump(&mut item_list, &mut rx, |a| matches!(a, Action::Notify(Notification::Warning(_)))).await;
+        assert!(
+            matches!(action, Action::Notify(Notification::Warning(message)) if message.starts_with("GitHub rate limit reached"))
+        );
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(_, _, _, false, None))).await;
+
+        assert!(chrono::Utc::now() >= reset_at);
+        assert_eq!(item_list.pull_requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_is_reported_as_warning() {
+        let (mut item_list, mut rx) = pull_request_list(
+            FakeGithubClient::new("octocat")
+                .with_failure(FakeMethod::SearchPullRequests, GithubError::RateLimited { reset_at: None }),
+        );
+
+        item_list.update(Action::Refresh).unwrap();
+        let action = pump(&mut item_list, &mut rx, |a| matches!(a, Action::Notify(Notification::Warning(_)))).await;
+
+        assert_eq!(action, Action::Notify(Notification::Warning(String::from("GitHub rate limit exceeded"))));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_failure_is_reported() {
+        let (mut item_list, mut rx) = pull_request_list(
+            FakeGithubClient::new("octocat")
+                .with_failure(FakeMethod::SearchPullRequests, GithubError::UnexpectedResponse(String::from("boom"))),
+        );
+
+        item_list.update(Action::Refresh).unwrap();
+        let action = pump(&mut item_list, &mut rx, |a| matches!(a, Action::Error(_))).await;
+
+        assert_eq!(action, Action::Error(GithubError::UnexpectedResponse(String::from("boom")).to_string()));
+        assert!(item_list.section().pull_requests.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_page_can_be_loaded_again() {
+        let client = FakeGithubClient::new("octocat")
+            .with_page(None, vec![pull_request("owner/repo", 1)], Some("c1"))
+            .with_page(Some("c1"), vec![pull_request("owner/repo", 2)], None)
+            .into_arc();
+        let (mut item_list, mut rx) = pull_request_list(client.clone());
+        item_list.update(Action::Refresh).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+        client.fail_next(FakeMethod::SearchPullRequests, GithubError::UnexpectedResponse(String::from("boom")));
+        item_list.update(Action::Down).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsError(..))).await;
+
+        assert!(!item_list.is_loading_more());
+        assert!(item_list.has_next_page());
+        // scrolling again requests it again
+        item_list.update(Action::Down).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(_, _, _, false, None))).await;
+        assert_eq!(numbers(&item_list), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_is_retried() {
+        let client = FakeGithubClient::new("octocat")
+            .with_page(None, vec![pull_request("owner/repo", 1)], None)
+            .with_failure(FakeMethod::SearchPullRequests, GithubError::Network(String::from("boom")))
+            .into_arc();
+        let (mut item_list, mut rx) = pull_request_list(client.clone());
+        item_list.config.config.retry = RetryPolicy { initial_backoff_ms: 1, ..RetryPolicy::default() };
+
+        item_list.update(Action::Refresh).unwrap();
+        let action = pump(&mut item_list, &mut rx, |a| matches!(a, Action::Notify(Notification::Warning(_)))).await;
+        assert_eq!(
+            action,
+            Action::Notify(Notification::Warning(format!(
+                "{}, retrying in 0.0s (attempt 2/3)",
+                GithubError::Network(String::from("boom"))
+            )))
+        );
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+
+        assert_eq!(item_list.pull_requests().len(), 1);
+        assert_eq!(client.calls(), vec![
+            FakeMethod::GetCurrentUser,
+            FakeMethod::SearchPullRequests,
+            FakeMethod::SearchPullRequests
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_info_overlay_loads_details() {
+        let mut detailed = pull_request("owner/repo", 1);
+        detailed.body = String::from("Detailed description");
+        let (mut item_list, mut rx) = pull_request_list(
+            FakeGithubClient::new("octocat")
+                .with_page(None, vec![pull_request("owner/repo", 1)], None)
+                .with_details(detailed),
+        );
+
+        item_list.update(Action::Refresh).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+        item_list.update(Action::Info).unwrap();
+        assert!(item_list.show_info_overlay);
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::PullRequestDetailsLoaded(_))).await;
+
+        assert_eq!(item_list.info_overlay.pull_request().unwrap().body, "Detailed description");
+    }
+
+    #[tokio::test]
+    async fn test_cached_pull_requests_are_shown_until_refreshed() {
+        let dir = std::env::temp_dir().join(format!("ghtui-list-cache-{}", std::process::id()));
+        let cache = PullRequestCache::new(&dir, "default");
+        cache
+            .save_pull_requests(vec![CachedSection {
+                query: String::from(INVOLVED_QUERY),
+                pull_requests: vec![pull_request("owner/repo", 1), pull_request("owner/closed", 2)],
+            }])
+            .unwrap();
+        // sorted by repository, the closed pull request comes first
+        let mut detailed = pull_request("owner/closed", 2);
+        detailed.body = String::from("Cached description");
+        cache.save_details(&detailed).unwrap();
+        let client = FakeGithubClient::new("octocat")
+            .with_page(None, vec![pull_request("owner/repo", 1), pull_request("owner/repo", 3)], None)
+            .into_arc();
+        let (mut item_list, mut rx) = setup(PullRequestList::new(client.clone()).with_cache(cache.clone()));
+
+        assert_eq!(item_list.pull_requests().len(), 2);
+        assert!(item_list.stale_since.is_some());
+        item_list.update(Action::Info).unwrap();
+        assert_eq!(item_list.info_overlay.pull_request().unwrap().body, "Cached description");
+        item_list.update(Action::Back).unwrap();
+
+        item_list.init(Rect::default()).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+
+        assert!(!client.calls().contains(&FakeMethod::GetPullRequestDetails));
+        assert!(item_list.stale_since.is_none());
+        let mut numbers: Vec<usize> = item_list.pull_requests().iter().map(|pr| pr.number).collect();
+        numbers.sort();
+        assert_eq!(numbers, vec![1, 3]);
+        let mut cached: Vec<usize> =
+            cache.load_pull_requests().unwrap().section(INVOLVED_QUERY).unwrap().iter().map(|pr| pr.number).collect();
+        cached.sort();
+        assert_eq!(cached, vec![1, 3]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_a_failing_section_does_not_keep_the_list_stale() {
+        let dir = std::env::temp_dir().join(format!("ghtui-failed-section-cache-{}", std::process::id()));
+        let cache = PullRequestCache::new(&dir, "default");
+        cache
+            .save_pull_requests(vec![CachedSection {
+                query: String::from(INVOLVED_QUERY),
+                pull_requests: vec![pull_request("owner/repo", 1)],
+            }])
+            .unwrap();
+        // only the involved search has a page, the review requested one fails
+        let client = FakeGithubClient::new("octocat")
+            .with_search_page(INVOLVED, None, vec![pull_request("owner/repo", 1), pull_request("owner/repo", 2)], None)
+            .into_arc();
+        let (mut item_list, mut rx) = setup(PullRequestList::new(client).with_cache(cache.clone()));
+        item_list.register_config_handler(two_sections()).unwrap();
+        assert!(item_list.stale_since.is_some());
+
+        item_list.update(Action::Refresh).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsError(..))).await;
+        if item_list.sections.iter().any(|section| section.is_loading) {
+            pump(&mut item_list, &mut rx, |a| matches!(a, Action::LoadMorePullRequestsResult(..))).await;
+        }
+
+        assert!(item_list.sections[1].pull_requests.is_none());
+        assert!(item_list.stale_since.is_none());
+        let mut cached: Vec<usize> =
+            cache.load_pull_requests().unwrap().section(INVOLVED_QUERY).unwrap().iter().map(|pr| pr.number).collect();
+        cached.sort();
+        assert_eq!(cached, vec![1, 2]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cached_details_are_refetched_when_updated() {
+        let dir = std::env::temp_dir().join(format!("ghtui-details-cache-{}", std::process::id()));
+        let cache = PullRequestCache::new(&dir, "default");
+        let mut cached = pull_request("owner/repo", 1);
+        cached.body = String::from("Cached description");
+        cache.save_details(&cached).unwrap();
+        let mut updated = pull_request("owner/repo", 1);
+        updated.updated_at += chrono::Duration::hours(1);
+        let mut detailed = updated.clone();
+        detailed.body = String::from("Updated description");
+        let client = FakeGithubClient::new("octocat").with_details(detailed).into_arc();
+        let (mut item_list, mut rx) = setup(PullRequestList::new(client.clone()).with_cache(cache.clone()));
+
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+        item_list.update(Action::Info).unwrap();
+        item_list.update(Action::Back).unwrap();
+        assert!(client.calls().is_empty());
+
+        item_list.section_mut().pull_requests = Some(vec![updated]);
+        item_list.update(Action::Info).unwrap();
+        assert_eq!(item_list.info_overlay.pull_request().unwrap().body, "Cached description");
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::PullRequestDetailsLoaded(_))).await;
+
+        assert_eq!(client.calls(), vec![FakeMethod::GetPullRequestDetails]);
+        assert_eq!(item_list.info_overlay.pull_request().unwrap().body, "Updated description");
+        assert_eq!(cache.load_details("owner/repo", 1).unwrap().body, "Updated description");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_diff_shows_changed_files() {
+        let files = vec![changed_file("src/main.rs", Some("@@ -1 +1 @@\n-old\n+new"))];
+        let (mut item_list, mut rx) =
+            pull_request_list(FakeGithubClient::new("octocat").with_details(pull_request("owner/repo", 1)).with_files(
+                "owner/repo",
+                1,
+                files.clone(),
+            ));
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+
+        assert_eq!(item_list.update(Action::Diff).unwrap(), Some(Action::Render));
+        assert!(item_list.show_info_overlay);
+        assert!(item_list.info_overlay.is_showing_diff());
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::PullRequestFilesLoaded(..))).await;
+        assert_eq!(item_list.info_overlay.diff().unwrap().files(), files);
+
+        // back to the description, and to the loaded diff again
+        item_list.update(Action::Diff).unwrap();
+        assert!(!item_list.info_overlay.is_showing_diff());
+        item_list.update(Action::Diff).unwrap();
+        assert!(item_list.info_overlay.is_showing_diff());
+        assert!(item_list.info_overlay.diff().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_review_comments_are_submitted_with_the_review() {
+        let file = changed_file("a.rs", Some("@@ -1 +1,2 @@\n same\n+new"));
+        let (mut item_list, mut rx) =
+            pull_request_list(FakeGithubClient::new("octocat").with_details(pull_request("owner/repo", 1)).with_files(
+                "owner/repo",
+                1,
+                vec![file],
+            ));
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+        item_list.update(Action::Diff).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::ReviewThreadsLoaded(..))).await;
+
+        // on the added line
+        for _ in 0..3 {
+            item_list.update(Action::Down).unwrap();
+        }
+        item_list.update(Action::CommentLines).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::WriteComment(..))).await;
+        for c in "typo".chars() {
+            item_list.handle_key_events(KeyEvent::from(KeyCode::Char(c))).unwrap();
+        }
+        item_list.update(Action::Enter).unwrap();
+        assert!(item_list.comment_prompt.is_none());
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::ReviewCommentAdded(..))).await;
+
+        let pr = pull_request("owner/repo", 1);
+        let pending_review = PendingReview { id: String::from("review-1"), comments: 1 };
+        assert_eq!(item_list.info_overlay.pending_review(&pr), Some(pending_review.clone()));
+        let threads = item_list.client.review_threads("owner/repo", 1).threads;
+        assert_eq!(threads[0].target, CommentTarget {
+            path: String::from("a.rs"),
+            side: DiffSide::Right,
+            line: 2,
+            start: None
+        });
+
+        // reopening the pull request keeps adding to the same review
+        item_list.update(Action::Escape).unwrap();
+        assert!(!item_list.show_info_overlay);
+        item_list.update(Action::Diff).unwrap();
+        assert_eq!(item_list.info_overlay.pending_review(&pr), Some(pending_review));
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::ReviewThreadsLoaded(..))).await;
+
+        // the pending comment makes a body optional
+        item_list.update(Action::Review(PullRequestReviewEvent::RequestChanges)).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::ConfirmReview(..))).await;
+        item_list.update(Action::Enter).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::ReviewSubmitted(..))).await;
+
+        assert!(item_list.client.calls().contains(&FakeMethod::SubmitPendingReview));
+        assert!(!item_list.client.calls().contains(&FakeMethod::SubmitPullRequestReview));
+        assert_eq!(item_list.info_overlay.pending_review(&pr), None);
+        assert_eq!(item_list.info_overlay.diff().unwrap().pending_comments(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pending_review_is_looked_up_when_unknown() {
+        let pending_review = PendingReview { id: String::from("review-7"), comments: 2 };
+        let (mut item_list, mut rx) =
+            pull_request_list(FakeGithubClient::new("octocat").with_review_threads("owner/repo", 1, ReviewThreads {
+                pending_review: Some(pending_review),
+                threads: vec![],
+            }));
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+
+        // started in a diff since closed, or on GitHub
+        item_list.update(Action::Review(PullRequestReviewEvent::Comment)).unwrap();
+        assert_eq!(item_list.review_prompt.as_ref().unwrap().confirm(), None);
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::PendingReviewLoaded(..))).await;
+        assert_eq!(item_list.review_prompt.as_ref().unwrap().confirm(), Some(None));
+
+        item_list.update(Action::Enter).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::ReviewSubmitted(..))).await;
+        assert!(item_list.client.calls().contains(&FakeMethod::SubmitPendingReview));
+        assert!(!item_list.client.calls().contains(&FakeMethod::SubmitPullRequestReview));
+        assert_eq!(item_list.client.review_threads("owner/repo", 1).pending_review, None);
+    }
+
+    #[tokio::test]
+    async fn test_pending_review_lookup_is_retried() {
+        let pending_review = PendingReview { id: String::from("review-7"), comments: 2 };
+        let (mut item_list, mut rx) = pull_request_list(
+            FakeGithubClient::new("octocat")
+                .with_review_threads("owner/repo", 1, ReviewThreads {
+                    pending_review: Some(pending_review),
+                    threads: vec![],
+                })
+                .with_failure(FakeMethod::GetPendingReview, GithubError::Server {
+                    status: 502,
+                    message: String::from("Bad Gateway"),
+                }),
+        );
+        item_list.config.config.retry = RetryPolicy { initial_backoff_ms: 1, ..RetryPolicy::default() };
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+
+        item_list.update(Action::Review(PullRequestReviewEvent::Comment)).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::PendingReviewLoaded(..))).await;
+        assert_eq!(item_list.review_prompt.as_ref().unwrap().confirm(), Some(None));
+
+        item_list.update(Action::Enter).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::ReviewSubmitted(..))).await;
+        // the failed lookup, its retry, and the lookup when submitting
+        let lookups = item_list.client.calls().iter().filter(|&&call| call == FakeMethod::GetPendingReview).count();
+        assert_eq!(lookups, 3);
+        assert!(item_list.client.calls().contains(&FakeMethod::SubmitPendingReview));
+    }
+
+    #[tokio::test]
+    async fn test_files_are_marked_as_viewed() {
+        let file = |filename: &str| {
+            ChangedFile { status: FileStatus::Added, ..changed_file(filename, Some("@@ -0,0 +1 @@\n+new")) }
+        };
+        let (mut item_list, mut rx) = pull_request_list(
+            FakeGithubClient::new("octocat")
+                .with_details(pull_request("owner/repo", 1))
+                .with_files("owner/repo", 1, vec![file("a.rs"), file("b.rs")])
+                .with_viewed_files("owner/repo", 1, &["b.rs"]),
+        );
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+        item_list.update(Action::Diff).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::PullRequestFilesLoaded(..))).await;
+        let viewed = |item_list: &PullRequestList<FakeGithubClient>| {
+            item_list.info_overlay.diff().unwrap().files().iter().map(|file| file.viewed).collect::<Vec<_>>()
+        };
+        assert_eq!(viewed(&item_list), vec![false, true]);
+
+        item_list.update(Action::ToggleViewed).unwrap();
+        assert_eq!(viewed(&item_list), vec![true, true]);
+        // GitHub is told in the background
+        let client = item_list.client.clone();
+        tokio::time::timeout(Duration::from_secs(1), async move {
+            while client.viewed_files("owner/repo", 1).len() < 2 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the file to be marked as viewed");
+
+        // unmarking fails, and the file shows as viewed again
+        item_list.client.fail_next(FakeMethod::SetFileViewed, GithubError::Auth(String::from("bad credentials")));
+        item_list.update(Action::ToggleViewed).unwrap();
+        assert_eq!(viewed(&item_list), vec![false, true]);
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::FileViewed(..))).await;
+        assert_eq!(viewed(&item_list), vec![true, true]);
+    }
+
+    #[tokio::test]
+    async fn test_marking_files_as_viewed_is_retried() {
+        let file = ChangedFile { status: FileStatus::Added, ..changed_file("a.rs", Some("@@ -0,0 +1 @@\n+new")) };
+        let (mut item_list, mut rx) =
+            pull_request_list(FakeGithubClient::new("octocat").with_details(pull_request("owner/repo", 1)).with_files(
+                "owner/repo",
+                1,
+                vec![file],
+            ));
+        item_list.config.config.retry = RetryPolicy { initial_backoff_ms: 1, ..RetryPolicy::default() };
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+        item_list.update(Action::Diff).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::PullRequestFilesLoaded(..))).await;
+
+        item_list.client.fail_next(FakeMethod::SetFileViewed, GithubError::Server {
+            status: 502,
+            message: String::from("Bad Gateway"),
+        });
+        item_list.update(Action::ToggleViewed).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::Notify(Notification::Warning(_)))).await;
+        let client = item_list.client.clone();
+        tokio::time::timeout(Duration::from_secs(1), async move {
+            while client.viewed_files("owner/repo", 1).is_empty() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the file to be marked as viewed");
+
+        assert!(item_list.info_overlay.diff().unwrap().files()[0].viewed);
+    }
+
+    #[tokio::test]
+    async fn test_info_overlay_details_failure() {
+        let (mut item_list, mut rx) = pull_request_list(FakeGithubClient::new("octocat").with_failure(
+            FakeMethod::GetPullRequestDetails,
+            GithubError::NotFound(String::from("pull request owner/repo#1")),
+        ));
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+
+        item_list.update(Action::Info).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::PullRequestDetailsLoadError)).await;
+
+        // the summary row is still shown
+        assert_eq!(item_list.info_overlay.pull_request().unwrap().number, 1);
+    }
+
+    #[tokio::test]
+    async fn test_review_prompt_requires_confirmation() {
+        let (mut item_list, mut rx) = pull_request_list(FakeGithubClient::new("octocat"));
+        item_list.section_mut().pull_requests = Some(vec![pull_request("owner/repo", 1)]);
+
+        item_list.update(Action::Review(PullRequestReviewEvent::Comment)).unwrap();
+        assert!(item_list.review_prompt.is_some());
+        assert_eq!(rx.try_recv().unwrap(), Action::SwitchMode(Mode::Insert));
+
+        // navigation is swallowed while the prompt is open
+        assert_eq!(item_list.update(Action::Down).unwrap(), None);
+        // a comment without a body cannot be submitted
+        item_list.update(Action::Enter).unwrap();
+        assert!(item_list.review_prompt.is_some());
+
+        item_list.update(Action::Escape).unwrap();
+        assert!(item_list.review_prompt.is_none());
+        assert!(!item_list.show_info_overlay);
+        assert_eq!(rx.try_recv().unwrap(), Action::SwitchMode(Mode::Normal));
+    }
+
+    #[tokio::test]
+    async fn test_approve_updates_reviews() {
+        let (mut item_list, mut rx) = pull_request_list(FakeGithubClient::new("octocat"));
+        item_list.section_mut().pull_requests =
+            Some(vec![pull_request("owner/repo", 1), pull_request("owner/repo", 2)]);
+        item_list.section_mut().selected_row = 1;
+
+        item_list.update(Action::Review(PullRequestReviewEvent::Approve)).unwrap();
+        item_list.update(Action::Enter).unwrap();
+        pump(&mut item_list, &mut rx, |a| matches!(a, Action::ReviewSubmitted(..))).await;
+
+        let pull_requests = item_list.pull_requests();
+        assert!(pull_requests[0].reviews.is_empty());
+        assert_eq!(pull_requests[1].reviews, vec![PullRequestReview {
+            author: String::from("octocat"),
+            state: PullRequestReviewState::Approved
+        }]);
+    }
 }
@@ -0,0 +1,438 @@
+use std::collections::HashSet;
+
+use ratatui::widgets::TableState;
+
+use crate::{
+    components::{
+        column::Column,
+        fuzzy::{PullRequestFilter, PullRequestMatch},
+        pull_request::PullRequest,
+    },
+    config::{Config, Section},
+};
+
+/// The pull requests found by one [`Section`]'s search: how far it has been paginated, and how its tab is browsed.
+pub struct PullRequestSection {
+    pub section: Section,
+    /// Typed into the search prompt rather than configured; not cached and kept when the config changes.
+    pub is_ad_hoc: bool,
+    /// `None` until the first page has been loaded.
+    pub pull_requests: Option<Vec<PullRequest>>,
+    pub has_next_page: bool,
+    pub end_cursor: Option<String>,
+    pub is_loading: bool,
+    /// Only the pull requests matching the filter are shown.
+    pub filter: PullRequestFilter,
+    /// The selected row among the shown [`SectionRow`]s.
+    pub selected_row: usize,
+    /// The column the pull requests are sorted by.
+    pub sort_column: Column,
+    pub sort_descending: bool,
+    /// Whether the pull requests are shown under a header row for each repository.
+    pub grouped: bool,
+    /// The repositories whose pull requests are hidden under their header.
+    pub collapsed: HashSet<String>,
+    pub table_state: TableState,
+}
+
+/// A row of a section's table.
+#[derive(Debug, PartialEq)]
+pub enum SectionRow<'a> {
+    /// The header of a repository's pull requests in the grouped view, with how many of them match the filter.
+    Group {
+        repository: &'a str,
+        count: usize,
+        collapsed: bool,
+    },
+    PullRequest(&'a PullRequest, PullRequestMatch),
+}
+
+/// What is selected, to find it again after the rows changed.
+enum Selection {
+    Group(String),
+    /// The repository and number of the pull request.
+    PullRequest(String, usize),
+}
+
+impl PullRequestSection {
+    pub fn new(section: Section) -> Self {
+        Self {
+            section,
+            is_ad_hoc: false,
+            pull_requests: None,
+            has_next_page: true,
+            end_cursor: None,
+            is_loading: false,
+            filter: PullRequestFilter::default(),
+            selected_row: 0,
+            sort_column: Column::Repository,
+            sort_descending: false,
+            grouped: false,
+            collapsed: HashSet::new(),
+            table_state: TableState::default(),
+        }
+    }
+
+    /// A section for a query typed into the search prompt, named after the query.
+    pub fn ad_hoc(query: &str) -> Self {
+        Self { is_ad_hoc: true, ..Self::new(Section::new(query, query)) }
+    }
+
+    /// Sections for the configured searches, keeping the state of those in `existing` that are still configured, and
+    /// the ad-hoc sections after them.
+    pub fn from_config(config: &Config, existing: Vec<PullRequestSection>) -> Vec<Self> {
+        let (ad_hoc, mut existing): (Vec<_>, Vec<_>) = existing.into_iter().partition(|s| s.is_ad_hoc);
+        config
+            .config
+            .sections()
+            .into_iter()
+            .map(|section| {
+                match existing.iter().position(|s| s.section == section) {
+                    Some(index) => existing.swap_remove(index),
+                    None => Self::new(section),
+                }
+            })
+            .chain(ad_hoc)
+            .collect()
+    }
+
+    pub fn pull_requests(&self) -> &[PullRequest] {
+        self.pull_requests.as_deref().unwrap_or_default()
+    }
+
+    /// The pull requests passing the filter, in sort order, with the chars the filter matched.
+    pub fn visible(&self) -> impl Iterator<Item = (&PullRequest, PullRequestMatch)> {
+        self.pull_requests().iter().filter_map(|pr| Some((pr, self.filter.matches(pr)?)))
+    }
+
+    /// The rows of the table: the visible pull requests, under the header of their repository in the grouped view.
+    ///
+    /// Groups are ordered by their first pull request, so that they follow the sort order too.
+    pub fn rows(&self) -> Vec<SectionRow<'_>> {
+        if !self.grouped {
+            return self.visible().map(|(pr, matched)| SectionRow::PullRequest(pr, matched)).collect();
+        }
+        let mut groups: Vec<(&str, Vec<(&PullRequest, PullRequestMatch)>)> = vec![];
+        for (pr, matched) in self.visible() {
+            match groups.iter_mut().find(|(repository, _)| *repository == pr.repository) {
+                Some((_, pull_requests)) => pull_requests.push((pr, matched)),
+                None => groups.push((&pr.repository, vec![(pr, matched)])),
+            }
+        }
+        let mut rows = vec![];
+        for (repository, pull_requests) in groups {
+            let collapsed = self.collapsed.contains(repository);
+            rows.push(SectionRow::Group { repository, count: pull_requests.len(), collapsed });
+            if !collapsed {
+                rows.extend(pull_requests.into_iter().map(|(pr, matched)| SectionRow::PullRequest(pr, matched)));
+            }
+        }
+        rows
+    }
+
+    /// The selected pull request, `None` if a group header is selected.
+    pub fn selected(&self) -> Option<&PullRequest> {
+        match self.rows().into_iter().nth(self.selected_row)? {
+            SectionRow::PullRequest(pr, _) => Some(pr),
+            SectionRow::Group { .. } => None,
+        }
+    }
+
+    /// The repository of the selected row, whether it is a group header or a pull request.
+    fn selected_repository(&self) -> Option<&str> {
+        match self.rows().into_iter().nth(self.selected_row)? {
+            SectionRow::PullRequest(pr, _) => Some(&pr.repository),
+            SectionRow::Group { repository, .. } => Some(repository),
+        }
+    }
+
+    fn selection(&self) -> Option<Selection> {
+        match self.rows().into_iter().nth(self.selected_row)? {
+            SectionRow::PullRequest(pr, _) => Some(Selection::PullRequest(pr.repository.clone(), pr.number)),
+            SectionRow::Group { repository, .. } => Some(Selection::Group(repository.to_string())),
+        }
+    }
+
+    /// Switches between the grouped and the flat view, keeping the selected pull request selected.
+    pub fn toggle_grouped(&mut self) {
+        let selection = self.selection();
+        self.grouped = !self.grouped;
+        self.reselect(selection);
+    }
+
+    /// Hides the pull requests of the selected row's repository, selecting its header.
+    pub fn collapse(&mut self) {
+        if let Some(repository) = self.selected_repository().map(String::from) {
+            self.collapsed.insert(repository.clone());
+            self.reselect(Some(Selection::Group(repository)));
+        }
+    }
+
+    /// Shows the pull requests of the selected row's repository again.
+    pub fn expand(&mut self) {
+        if let Some(repository) = self.selected_repository().map(String::from) {
+            self.collapsed.remove(&repository);
+            self.reselect(Some(Selection::Group(repository)));
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected_row = self.selected_row.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected_row += 1;
+        self.clamp_selection();
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected_row = self.selected_row.min(self.rows().len().saturating_sub(1));
+    }
+
+    /// Selects the row of `selection` if it is shown, or the header of its repository if it is collapsed, and keeps
+    /// the selected row in bounds otherwise.
+    fn reselect(&mut self, selection: Option<Selection>) {
+        let rows = self.rows();
+        let row = selection.and_then(|selection| {
+            let group = |repository: &str| {
+                rows.iter().position(|row| matches!(row, SectionRow::Group { repository: r, .. } if *r == repository))
+            };
+            match selection {
+                Selection::PullRequest(repository, number) => {
+                    rows.iter()
+                        .position(|row| {
+                            matches!(row, SectionRow::PullRequest(pr, _) if pr.repository == repository && pr.number == number)
+                        })
+                        .or_else(|| group(&repository))
+                },
+                Selection::Group(repository) => {
+                    group(&repository).or_else(|| {
+                        rows.iter()
+                            .position(|row| matches!(row, SectionRow::PullRequest(pr, _) if pr.repository == repository))
+                    })
+                },
+            }
+        });
+        match row {
+            Some(row) => self.selected_row = row,
+            None => self.clamp_selection(),
+        }
+    }
+
+    /// Keeps the selected pull request selected while it still matches.
+    pub fn set_filter(&mut self, filter: PullRequestFilter) {
+        let selected = self.selection();
+        self.filter = filter;
+        self.reselect(selected);
+    }
+
+    /// Starts loading the search from the first page again.
+    pub fn reset_pagination(&mut self) {
+        self.has_next_page = true;
+        self.end_cursor = None;
+        self.is_loading = false;
+    }
+
+    /// Whether the page requested with the `after` cursor is the one the section waits for, rather than one requested
+    /// before the section was refreshed.
+    pub fn is_awaiting(&self, after: &Option<String>) -> bool {
+        self.is_loading && self.end_cursor == *after
+    }
+
+    /// Adds a page of search results. The first page replaces the pull requests loaded before, some of which may be
+    /// closed by now.
+    pub fn add_page(&mut self, pull_requests: Vec<PullRequest>, has_next_page: bool, end_cursor: Option<String>) {
+        let selected = self.selection();
+        match &mut self.pull_requests {
+            Some(existing) if self.end_cursor.is_some() => {
+                // a pull request updated while paginating may show up again on a later page, the newer copy wins
+                existing.retain(|pr| !pull_requests.contains(pr));
+                existing.extend(pull_requests);
+            },
+            _ => self.pull_requests = Some(pull_requests),
+        }
+        self.has_next_page = has_next_page;
+        self.end_cursor = end_cursor;
+        self.is_loading = false;
+        self.sort();
+        self.reselect(selected);
+    }
+
+    /// Sorts by `column`, keeping the selected pull request selected.
+    pub fn sort_by(&mut self, column: Column, descending: bool) {
+        let selected = self.selection();
+        self.sort_column = column;
+        self.sort_descending = descending;
+        self.sort();
+        self.reselect(selected);
+    }
+
+    /// Sorts by the selected column, breaking ties by the most recently updated, then by repository and number, so
+    /// that the order does not depend on the order the pages arrived in.
+    pub fn sort(&mut self) {
+        let (column, descending) = (self.sort_column, self.sort_descending);
+        if let Some(ref mut pull_requests) = self.pull_requests {
+            pull_requests.sort_by(|a, b| {
+                let ordering = column.compare(a, b);
+                if descending { ordering.reverse() } else { ordering }
+                    .then_with(|| b.updated_at.cmp(&a.updated_at))
+                    .then_with(|| a.cmp(b))
+            });
+        }
+    }
+
+    /// The tab title with the number of pull requests loaded so far, e.g. `Involved (20+)` while more pages follow,
+    /// and how many of them match the filter, e.g. `Involved (3/20+)`.
+    pub fn title(&self) -> String {
+        let Some(pull_requests) = &self.pull_requests else {
+            return self.section.name.clone();
+        };
+        let count =
+            if self.has_next_page { format!("{}+", pull_requests.len()) } else { pull_requests.len().to_string() };
+        if self.filter.is_empty() {
+            format!("{} ({count})", self.section.name)
+        } else {
+            format!("{} ({}/{count})", self.section.name, self.visible().count())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::components::pull_request::tests::pull_request;
+
+    fn numbers(section: &PullRequestSection) -> Vec<usize> {
+        section.pull_requests().iter().map(|pr| pr.number).collect()
+    }
+
+    #[test]
+    fn test_first_page_replaces_pull_requests() {
+        let mut section = PullRequestSection::new(Section::new("Mine", "is:pr author:{user}"));
+        assert_eq!(section.title(), "Mine");
+
+        section.add_page(
+            vec![pull_request("owner/repo", 1), pull_request("owner/repo", 2)],
+            true,
+            Some(String::from("c1")),
+        );
+        assert_eq!(section.title(), "Mine (2+)");
+        section.add_page(vec![pull_request("owner/repo", 2), pull_request("owner/repo", 3)], false, None);
+        assert_eq!(numbers(&section), vec![1, 2, 3]);
+        assert_eq!(section.title(), "Mine (3)");
+
+        section.selected_row = 2;
+        section.reset_pagination();
+        section.add_page(vec![pull_request("owner/repo", 3)], false, None);
+        assert_eq!(numbers(&section), vec![3]);
+        assert_eq!(section.selected_row, 0);
+    }
+
+    #[test]
+    fn test_filter_keeps_selection_valid() {
+        let mut section = PullRequestSection::new(Section::new("Mine", "is:pr author:{user}"));
+        section.add_page((1..=4).map(|number| pull_request("owner/repo", number)).collect(), false, None);
+        section.pull_requests.as_mut().unwrap()[2].title = String::from("Add filter");
+        section.selected_row = 2;
+
+        section.set_filter(PullRequestFilter::new("filter"));
+        assert_eq!(section.selected().unwrap().number, 3);
+        assert_eq!(section.selected_row, 0);
+        assert_eq!(section.title(), "Mine (1/4)");
+
+        section.set_filter(PullRequestFilter::new("#4"));
+        assert_eq!(section.selected().unwrap().number, 4);
+        section.set_filter(PullRequestFilter::new("nothing matches"));
+        assert_eq!(section.selected(), None);
+        assert_eq!(section.selected_row, 0);
+
+        section.set_filter(PullRequestFilter::default());
+        section.select_next();
+        section.select_next();
+        assert_eq!(section.selected().unwrap().number, 3);
+    }
+
+    #[test]
+    fn test_sort_is_independent_of_page_order() {
+        let mut pull_requests: Vec<PullRequest> = (1..=4).map(|number| pull_request("owner/repo", number)).collect();
+        pull_requests[0].repository = String::from("owner/other");
+        pull_requests[1].updated_at += chrono::Duration::hours(1);
+        let mut forward = PullRequestSection::new(Section::new("Mine", "is:pr author:{user}"));
+        forward.add_page(pull_requests[..2].to_vec(), true, Some(String::from("c1")));
+        forward.add_page(pull_requests[2..].to_vec(), false, None);
+        let mut backward = PullRequestSection::new(Section::new("Mine", "is:pr author:{user}"));
+        backward.add_page(pull_requests[2..].to_vec(), true, Some(String::from("c1")));
+        backward.add_page(pull_requests[..2].to_vec(), false, None);
+
+        // sorted by repository, the most recently updated pull request comes first within a repository
+        assert_eq!(numbers(&forward), vec![1, 2, 3, 4]);
+        assert_eq!(numbers(&backward), numbers(&forward));
+
+        forward.selected_row = 2;
+        forward.sort_by(Column::Repository, true);
+        assert_eq!(numbers(&forward), vec![2, 3, 4, 1]);
+        assert_eq!(forward.selected().unwrap().number, 3);
+        forward.sort_by(Column::Number, false);
+        assert_eq!(numbers(&forward), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_grouped_rows_fold_by_repository() {
+        let mut section = PullRequestSection::new(Section::new("Mine", "is:pr author:{user}"));
+        section.add_page(
+            vec![pull_request("b/repo", 1), pull_request("a/repo", 2), pull_request("b/repo", 3)],
+            false,
+            None,
+        );
+        section.selected_row = 1; // b/repo#1
+        section.toggle_grouped();
+        let group = |repository, count, collapsed| SectionRow::Group { repository, count, collapsed };
+        let rows = section.rows();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0], group("a/repo", 1, false));
+        assert_eq!(rows[2], group("b/repo", 2, false));
+        assert_eq!(section.selected().unwrap().number, 1);
+        assert_eq!(section.selected_row, 3);
+
+        section.collapse();
+        assert_eq!(section.rows().len(), 3);
+        assert_eq!(section.rows()[2], group("b/repo", 2, true));
+        assert_eq!(section.selected_row, 2);
+        assert_eq!(section.selected(), None);
+
+        // a collapsed pull request is found under its header, and the filter counts what is left of a group
+        section.set_filter(PullRequestFilter::new("#3"));
+        assert_eq!(section.rows(), vec![group("b/repo", 1, true)]);
+        section.set_filter(PullRequestFilter::default());
+        section.expand();
+        assert_eq!(section.rows().len(), 5);
+        assert_eq!(section.selected_row, 2);
+
+        section.select_next();
+        section.toggle_grouped();
+        assert_eq!(section.selected().unwrap().number, 1);
+        assert_eq!(section.selected_row, 1);
+    }
+
+    #[test]
+    fn test_from_config_keeps_existing_state() {
+        let mut config = Config::default();
+        config.config.sections = vec![Section::new("Mine", "is:pr author:{user}"), Section::new("New", "is:pr")];
+        let mut mine = PullRequestSection::new(Section::new("Mine", "is:pr author:{user}"));
+        mine.add_page(vec![pull_request("owner/repo", 1)], false, None);
+        let removed = PullRequestSection::new(Section::new("Removed", "is:pr is:closed"));
+        let ad_hoc = PullRequestSection::ad_hoc("is:pr label:bug");
+
+        let sections = PullRequestSection::from_config(&config, vec![ad_hoc, removed, mine]);
+
+        assert_eq!(sections.iter().map(|s| s.section.name.as_str()).collect::<Vec<_>>(), vec![
+            "Mine",
+            "New",
+            "is:pr label:bug"
+        ]);
+        assert_eq!(numbers(&sections[0]), vec![1]);
+        assert!(sections[1].pull_requests.is_none());
+    }
+}
@@ -0,0 +1,179 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{
+    pull_request::{PullRequest, PullRequestReviewEvent},
+    utils::centered_rect,
+    Component,
+};
+use crate::{
+    action::Action,
+    colors::{BASE, GREEN, OVERLAY0, PEACH, TEXT, YELLOW},
+    tui::Frame,
+};
+
+/// Confirmation prompt shown before a review is submitted to GitHub.
+///
+/// Reviews that require a body (comments and change requests) collect it here; the prompt relies on the owning
+/// component to switch the app into [`crate::mode::Mode::Insert`] so that typed characters are not interpreted as
+/// keybindings.
+pub struct ReviewPrompt {
+    pull_request: PullRequest,
+    event: PullRequestReviewEvent,
+    body: String,
+    /// The review comments on lines of the diff submitted with the review.
+    pending_comments: usize,
+}
+
+impl ReviewPrompt {
+    pub fn new(pull_request: PullRequest, event: PullRequestReviewEvent) -> Self {
+        Self { pull_request, event, body: String::new(), pending_comments: 0 }
+    }
+
+    /// Submits the review with the comments of a pending review, which make a body optional.
+    pub fn with_pending_comments(mut self, pending_comments: usize) -> Self {
+        self.pending_comments = pending_comments;
+        self
+    }
+
+    pub fn pull_request(&self) -> &PullRequest {
+        &self.pull_request
+    }
+
+    pub fn event(&self) -> PullRequestReviewEvent {
+        self.event
+    }
+
+    pub fn requires_body(&self) -> bool {
+        self.event.requires_body()
+    }
+
+    /// Returns the review body if the prompt can be submitted, `None` if a required body is still missing.
+    pub fn confirm(&self) -> Option<Option<String>> {
+        let body = self.body.trim();
+        if body.is_empty() {
+            if self.requires_body() && self.pending_comments == 0 {
+                None
+            } else {
+                Some(None)
+            }
+        } else {
+            Some(Some(body.to_string()))
+        }
+    }
+
+    fn title_color(&self) -> Color {
+        match self.event {
+            PullRequestReviewEvent::Approve => GREEN,
+            PullRequestReviewEvent::RequestChanges => YELLOW,
+            PullRequestReviewEvent::Comment => PEACH,
+        }
+    }
+}
+
+impl Component for ReviewPrompt {
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.requires_body() {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.body.push(c),
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => self.body.push('\n'),
+            KeyCode::Backspace => {
+                self.body.pop();
+            },
+            _ => return Ok(None),
+        }
+        Ok(Some(Action::Render))
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let area = centered_rect(area, 60, if self.requires_body() { 40 } else { 20 });
+        let block = Block::default()
+            .title(
+                Span::styled(
+                    format!(" {} #{} in {} ", self.event, self.pull_request.number, self.pull_request.repository),
+                    Style::default().fg(self.title_color()).add_modifier(Modifier::BOLD),
+                )
+                .into_centered_line(),
+            )
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.title_color()).bg(BASE))
+            .bg(BASE);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let layout = Layout::new(Direction::Vertical, [Constraint::Min(1), Constraint::Length(1)])
+            .split(area.inner(&Margin { horizontal: 2, vertical: 1 }));
+
+        let body = if self.requires_body() {
+            Paragraph::new(format!("{}_", self.body)).style(Style::default().fg(TEXT)).wrap(Wrap { trim: false })
+        } else {
+            Paragraph::new(self.pull_request.title.clone())
+                .style(Style::default().fg(TEXT))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+        };
+        let keys = if self.requires_body() {
+            "enter: submit · alt-enter: new line · esc: cancel"
+        } else {
+            "enter: submit · esc: cancel"
+        };
+        let hint = Paragraph::new(match self.pending_comments {
+            0 => keys.to_string(),
+            1 => format!("with 1 pending comment · {keys}"),
+            n => format!("with {n} pending comments · {keys}"),
+        })
+        .style(Style::default().fg(OVERLAY0))
+        .alignment(Alignment::Center);
+
+        f.render_widget(body, layout[0]);
+        f.render_widget(hint, layout[1]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::pull_request::tests::pull_request;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_approve_does_not_require_body() {
+        let prompt = ReviewPrompt::new(pull_request("owner/repo", 1), PullRequestReviewEvent::Approve);
+        assert_eq!(prompt.confirm(), Some(None));
+    }
+
+    #[test]
+    fn test_comment_requires_body() {
+        let mut prompt = ReviewPrompt::new(pull_request("owner/repo", 1), PullRequestReviewEvent::Comment);
+        assert_eq!(prompt.confirm(), None);
+
+        for c in "lgtm!".chars() {
+            prompt.handle_key_events(key(KeyCode::Char(c))).unwrap();
+        }
+        prompt.handle_key_events(key(KeyCode::Backspace)).unwrap();
+        assert_eq!(prompt.confirm(), Some(Some(String::from("lgtm"))));
+    }
+
+    #[test]
+    fn test_pending_comments_make_body_optional() {
+        let prompt = ReviewPrompt::new(pull_request("owner/repo", 1), PullRequestReviewEvent::RequestChanges)
+            .with_pending_comments(2);
+        assert!(prompt.requires_body());
+        assert_eq!(prompt.confirm(), Some(None));
+    }
+
+    #[test]
+    fn test_approve_ignores_typed_keys() {
+        let mut prompt = ReviewPrompt::new(pull_request("owner/repo", 1), PullRequestReviewEvent::Approve);
+        assert_eq!(prompt.handle_key_events(key(KeyCode::Char('j'))).unwrap(), None);
+        assert_eq!(prompt.confirm(), Some(None));
+    }
+}
@@ -0,0 +1,150 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{utils::centered_rect, Component};
+use crate::{
+    action::Action,
+    colors::{BASE, OVERLAY0, PEACH, TEXT},
+    tui::Frame,
+};
+
+/// Prompt for a raw GitHub search query, e.g. `is:pr org:acme label:bug`, whose results are shown in their own tab.
+///
+/// Up and down recall earlier queries. Like the [`super::review_prompt::ReviewPrompt`], it relies on the owning
+/// component to switch the app into [`crate::mode::Mode::Insert`] while it is open.
+pub struct SearchPrompt {
+    query: String,
+    /// Earlier queries, oldest first.
+    history: Vec<String>,
+    /// The history entry shown, `None` while editing a new query.
+    recalled: Option<usize>,
+    /// The new query typed before recalling history, restored when moving past the most recent entry.
+    draft: String,
+}
+
+impl SearchPrompt {
+    pub fn new(history: Vec<String>) -> Self {
+        Self { query: String::new(), history, recalled: None, draft: String::new() }
+    }
+
+    /// The query to search for, `None` while it is empty.
+    pub fn confirm(&self) -> Option<String> {
+        Some(self.query.trim()).filter(|query| !query.is_empty()).map(String::from)
+    }
+
+    fn recall_previous(&mut self) {
+        let index = match self.recalled {
+            Some(index) => index.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = std::mem::take(&mut self.query);
+                self.history.len() - 1
+            },
+        };
+        self.recalled = Some(index);
+        self.query = self.history[index].clone();
+    }
+
+    fn recall_next(&mut self) {
+        let Some(index) = self.recalled else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.recalled = Some(index + 1);
+            self.query = self.history[index + 1].clone();
+        } else {
+            self.recalled = None;
+            self.query = std::mem::take(&mut self.draft);
+        }
+    }
+}
+
+impl Component for SearchPrompt {
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.query.push(c),
+            KeyCode::Backspace => {
+                self.query.pop();
+            },
+            KeyCode::Up => self.recall_previous(),
+            KeyCode::Down => self.recall_next(),
+            _ => return Ok(None),
+        }
+        Ok(Some(Action::Render))
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let area = centered_rect(area, 60, 20);
+        let block = Block::default()
+            .title(
+                Span::styled(" Search GitHub ", Style::default().fg(PEACH).add_modifier(Modifier::BOLD))
+                    .into_centered_line(),
+            )
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(PEACH).bg(BASE))
+            .bg(BASE);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let layout = Layout::new(Direction::Vertical, [Constraint::Min(1), Constraint::Length(1)])
+            .split(area.inner(&Margin { horizontal: 2, vertical: 1 }));
+
+        let query =
+            Paragraph::new(format!("{}_", self.query)).style(Style::default().fg(TEXT)).wrap(Wrap { trim: false });
+        let hint = Paragraph::new("enter: search · up/down: history · esc: cancel")
+            .style(Style::default().fg(OVERLAY0))
+            .alignment(Alignment::Center);
+
+        f.render_widget(query, layout[0]);
+        f.render_widget(hint, layout[1]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_str(prompt: &mut SearchPrompt, text: &str) {
+        for c in text.chars() {
+            prompt.handle_key_events(key(KeyCode::Char(c))).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_empty_query_cannot_be_submitted() {
+        let mut prompt = SearchPrompt::new(vec![]);
+        assert_eq!(prompt.confirm(), None);
+        type_str(&mut prompt, "  ");
+        assert_eq!(prompt.confirm(), None);
+        prompt.handle_key_events(key(KeyCode::Up)).unwrap();
+        assert_eq!(prompt.query, "  ");
+    }
+
+    #[test]
+    fn test_up_and_down_recall_history() {
+        let mut prompt = SearchPrompt::new(vec![String::from("is:pr org:acme"), String::from("is:pr label:bug")]);
+        type_str(&mut prompt, "is:pr draft");
+
+        prompt.handle_key_events(key(KeyCode::Up)).unwrap();
+        assert_eq!(prompt.confirm().as_deref(), Some("is:pr label:bug"));
+        prompt.handle_key_events(key(KeyCode::Up)).unwrap();
+        prompt.handle_key_events(key(KeyCode::Up)).unwrap();
+        assert_eq!(prompt.confirm().as_deref(), Some("is:pr org:acme"));
+
+        prompt.handle_key_events(key(KeyCode::Down)).unwrap();
+        assert_eq!(prompt.confirm().as_deref(), Some("is:pr label:bug"));
+        prompt.handle_key_events(key(KeyCode::Down)).unwrap();
+        assert_eq!(prompt.confirm().as_deref(), Some("is:pr draft"));
+        prompt.handle_key_events(key(KeyCode::Down)).unwrap();
+        assert_eq!(prompt.confirm().as_deref(), Some("is:pr draft"));
+    }
+}
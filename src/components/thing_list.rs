@@ -167,6 +167,8 @@ impl Component for ThingList {
                 self.refresh();
             },
             Action::Open => {},
+            Action::Info => self.show_info_overlay = !self.show_info_overlay,
+            Action::Escape | Action::Back => self.show_info_overlay = false,
             _ => {},
         }
 
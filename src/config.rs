@@ -11,7 +11,12 @@ use serde::{
 };
 use serde_json::Value as JsonValue;
 
-use crate::{action::Action, mode::Mode};
+use crate::{
+    action::Action,
+    components::column::{Column, ColumnConfig},
+    github::{host::GithubHost, retry::RetryPolicy},
+    mode::Mode,
+};
 
 const CONFIG: &str = include_str!("../.config/config.json5");
 
@@ -21,6 +26,150 @@ pub struct AppConfig {
     pub _data_dir: PathBuf,
     #[serde(default)]
     pub _config_dir: PathBuf,
+    /// GitHub host to connect to, e.g. `github.example.com` for GitHub Enterprise Server.
+    #[serde(default)]
+    pub host: GithubHost,
+    /// Named accounts that can be switched between at runtime. When empty, a single `default` profile is derived from
+    /// `host`.
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+    /// Client ID of the OAuth app used to log in to `host` with the device flow.
+    #[serde(default)]
+    pub oauth_client_id: Option<String>,
+    /// Command printing the token for `host`, e.g. a password manager lookup.
+    #[serde(default)]
+    pub token_command: Option<String>,
+    /// File containing the token for `host`.
+    #[serde(default)]
+    pub token_file: Option<PathBuf>,
+    /// How requests to GitHub are retried after transient failures.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Pull request searches to show. When empty, the built-in [`Section::defaults`] are shown.
+    #[serde(default)]
+    pub sections: Vec<Section>,
+    /// Columns of the pull request table, in order. When empty, the built-in [`Column::defaults`] are shown.
+    #[serde(default)]
+    pub columns: Vec<ColumnConfig>,
+    /// The narrowest terminal, in columns, the diff view shows side by side in, falling back to the unified diff below.
+    /// Compared against the whole terminal rather than the diff, which is narrower. Defaults to
+    /// [`DEFAULT_SPLIT_DIFF_MIN_WIDTH`].
+    #[serde(default)]
+    pub split_diff_min_width: Option<u16>,
+}
+
+pub const DEFAULT_SPLIT_DIFF_MIN_WIDTH: u16 = 160;
+
+impl AppConfig {
+    /// The configured profiles, or the implicit `default` profile if none are configured.
+    pub fn profiles(&self) -> Vec<Profile> {
+        if self.profiles.is_empty() {
+            vec![self.default_profile()]
+        } else {
+            self.profiles.clone()
+        }
+    }
+
+    /// The configured sections, or the built-in ones if none are configured.
+    pub fn sections(&self) -> Vec<Section> {
+        if self.sections.is_empty() {
+            Section::defaults()
+        } else {
+            self.sections.clone()
+        }
+    }
+
+    /// The configured columns, or the built-in ones if none are configured.
+    pub fn columns(&self) -> Vec<ColumnConfig> {
+        if self.columns.is_empty() {
+            Column::defaults()
+        } else {
+            self.columns.clone()
+        }
+    }
+
+    /// The configured width below which diffs are not split, or the built-in one if none is configured.
+    pub fn split_diff_min_width(&self) -> u16 {
+        self.split_diff_min_width.unwrap_or(DEFAULT_SPLIT_DIFF_MIN_WIDTH)
+    }
+
+    /// The profile derived from the top-level settings.
+    pub fn default_profile(&self) -> Profile {
+        Profile {
+            host: self.host.clone(),
+            oauth_client_id: self.oauth_client_id.clone(),
+            token_command: self.token_command.clone(),
+            token_file: self.token_file.clone(),
+            ..Profile::default()
+        }
+    }
+}
+
+/// A GitHub account on a given host.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub host: GithubHost,
+    /// Environment variable holding the profile's token. The default is only read on github.com, see
+    /// [`crate::github::token::resolve_token`].
+    #[serde(default = "default_token_env")]
+    pub token_env: String,
+    /// Client ID of the OAuth app used to log in with the device flow. The app must have device flow enabled.
+    #[serde(default)]
+    pub oauth_client_id: Option<String>,
+    /// Command printing the profile's token, see [`crate::github::token::resolve_token`] for the precedence.
+    #[serde(default)]
+    pub token_command: Option<String>,
+    /// File containing the profile's token.
+    #[serde(default)]
+    pub token_file: Option<PathBuf>,
+}
+
+fn default_token_env() -> String {
+    String::from("GITHUB_TOKEN")
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: String::from("default"),
+            host: GithubHost::default(),
+            token_env: default_token_env(),
+            oauth_client_id: None,
+            token_command: None,
+            token_file: None,
+        }
+    }
+}
+
+/// A named GitHub search for pull requests, e.g. `is:pr author:{user} draft:true state:open`.
+///
+/// `{user}` is replaced with the login of the signed in user.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Section {
+    pub name: String,
+    pub query: String,
+}
+
+impl Section {
+    pub fn new(name: &str, query: &str) -> Self {
+        Self { name: name.to_string(), query: query.to_string() }
+    }
+
+    pub fn defaults() -> Vec<Section> {
+        vec![
+            Section::new("Authored", "is:pr author:{user} state:open"),
+            Section::new("Review requested", "is:pr review-requested:{user} state:open"),
+            Section::new("Involved", "is:pr involves:{user} state:open"),
+            Section::new("Assigned", "is:pr assignee:{user} state:open"),
+        ]
+    }
+
+    /// The search query for the given user.
+    pub fn query_for(&self, user: &str) -> String {
+        self.query.replace("{user}", user)
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -398,6 +547,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::components::column::ColumnWidth;
 
     #[test]
     fn test_parse_style_default() {
@@ -456,6 +606,92 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_profiles() {
+        let c: Config = json5::from_str(
+            r#"{
+                "profiles": [
+                    { "name": "oss" },
+                    {
+                        "name": "work",
+                        "host": "github.example.com",
+                        "token_env": "WORK_GITHUB_TOKEN",
+                        "oauth_client_id": "Iv1.work",
+                        "token_command": "pass show github/work"
+                    },
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(c.config.profiles(), vec![Profile { name: String::from("oss"), ..Profile::default() }, Profile {
+            name: String::from("work"),
+            host: GithubHost::new("github.example.com"),
+            token_env: String::from("WORK_GITHUB_TOKEN"),
+            oauth_client_id: Some(String::from("Iv1.work")),
+            token_command: Some(String::from("pass show github/work")),
+            token_file: None,
+        },]);
+    }
+
+    #[test]
+    fn test_implicit_default_profile() {
+        let c: Config =
+            json5::from_str(r#"{ "host": "github.example.com", "oauth_client_id": "Iv1.default" }"#).unwrap();
+        assert_eq!(c.config.profiles(), vec![Profile {
+            host: GithubHost::new("github.example.com"),
+            oauth_client_id: Some(String::from("Iv1.default")),
+            ..Profile::default()
+        }]);
+    }
+
+    #[test]
+    fn test_sections() {
+        let c: Config = json5::from_str(
+            r#"{
+                "sections": [
+                    { "name": "Team frontend", "query": "is:pr team-review-requested:acme/frontend state:open" },
+                    { "name": "My drafts", "query": "is:pr author:{user} draft:true state:open" },
+                ]
+            }"#,
+        )
+        .unwrap();
+        let sections = c.config.sections();
+        assert_eq!(sections.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Team frontend", "My drafts"]);
+        assert_eq!(sections[1].query_for("octocat"), "is:pr author:octocat draft:true state:open");
+        assert_eq!(Config::default().config.sections(), Section::defaults());
+    }
+
+    #[test]
+    fn test_columns() {
+        let c: Config = json5::from_str(
+            r#"{
+                "columns": [
+                    { "column": "number" },
+                    { "column": "title", "width": { "min": 40 } },
+                    { "column": "checks" },
+                    { "column": "base_branch", "width": { "fixed": 8 } },
+                    { "column": "labels", "width": { "percent": 25 } },
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(c.config.columns(), vec![
+            ColumnConfig::new(Column::Number),
+            ColumnConfig { column: Column::Title, width: Some(ColumnWidth::Min(40)) },
+            ColumnConfig::new(Column::Checks),
+            ColumnConfig { column: Column::BaseBranch, width: Some(ColumnWidth::Fixed(8)) },
+            ColumnConfig { column: Column::Labels, width: Some(ColumnWidth::Percent(25)) },
+        ]);
+        assert_eq!(Config::default().config.columns(), Column::defaults());
+    }
+
+    #[test]
+    fn test_split_diff_min_width() {
+        let c: Config = json5::from_str(r#"{ "split_diff_min_width": 120 }"#).unwrap();
+        assert_eq!(c.config.split_diff_min_width(), 120);
+        assert_eq!(Config::default().config.split_diff_min_width(), DEFAULT_SPLIT_DIFF_MIN_WIDTH);
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(parse_key_event("a").unwrap(), KeyEvent::new(KeyCode::Char('a'), KeyModifiers::empty()));
@@ -1,2 +1,10 @@
+pub mod auth;
 pub mod client;
+pub mod error;
+#[cfg(test)]
+pub mod fake;
+pub mod host;
+pub mod rate_limit;
+pub mod retry;
+pub mod token;
 pub mod traits;
@@ -0,0 +1,202 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Result;
+use oauth2::{
+    basic::BasicClient,
+    devicecode::DeviceCodeErrorResponse,
+    http::StatusCode,
+    reqwest::{async_http_client, AsyncHttpClientError},
+    AuthUrl, ClientId, DeviceAuthorizationUrl, HttpRequest, HttpResponse, Scope, StandardDeviceAuthorizationResponse,
+    TokenResponse, TokenUrl,
+};
+
+use crate::github::{error::GithubError, host::GithubHost};
+
+/// Scopes needed to list, read and review pull requests, including those of organization repositories.
+const SCOPES: [&str; 2] = ["repo", "read:org"];
+
+/// Signs in with GitHub's [OAuth device flow](https://docs.github.com/en/apps/oauth-apps/building-oauth-apps/authorizing-oauth-apps#device-flow).
+///
+/// The user enters the code from [`DeviceFlow::start`] on the verification page in their browser while
+/// [`DeviceFlow::poll`] waits for GitHub to hand out the token.
+pub struct DeviceFlow {
+    client: BasicClient,
+}
+
+impl DeviceFlow {
+    pub fn new(host: &GithubHost, client_id: &str) -> Result<Self, GithubError> {
+        let url = |path: &str| host.web_url(path);
+        let invalid_url = |e: oauth2::url::ParseError| GithubError::UnexpectedResponse(e.to_string());
+        let client = BasicClient::new(
+            ClientId::new(client_id.to_string()),
+            None,
+            AuthUrl::new(url("/login/oauth/authorize")).map_err(invalid_url)?,
+            Some(TokenUrl::new(url("/login/oauth/access_token")).map_err(invalid_url)?),
+        )
+        .set_device_authorization_url(DeviceAuthorizationUrl::new(url("/login/device/code")).map_err(invalid_url)?);
+        Ok(Self { client })
+    }
+
+    /// Requests a user code and the URL where the user should enter it.
+    pub async fn start(&self) -> Result<StandardDeviceAuthorizationResponse, GithubError> {
+        self.client
+            .exchange_device_code()
+            .map_err(|e| GithubError::UnexpectedResponse(e.to_string()))?
+            .add_scopes(SCOPES.iter().map(|scope| Scope::new(scope.to_string())))
+            .request_async(http_client)
+            .await
+            .map_err(|e| GithubError::Auth(format!("could not start the device login: {e}")))
+    }
+
+    /// Waits until the user has authorized the device and returns the access token.
+    pub async fn poll(&self, details: &StandardDeviceAuthorizationResponse) -> Result<String, GithubError> {
+        let token = self
+            .client
+            .exchange_device_access_token(details)
+            .request_async(http_client, tokio::time::sleep, None)
+            .await
+            .map_err(|e| {
+                match e {
+                    oauth2::RequestTokenError::ServerResponse(response) => {
+                        GithubError::Auth(response.error_description().cloned().unwrap_or(response.error().to_string()))
+                    },
+                    e => GithubError::Auth(format!("device login failed: {e}")),
+                }
+            })?;
+        Ok(token.access_token().secret().clone())
+    }
+}
+
+/// GitHub reports device flow errors such as `authorization_pending` with a `200 OK` status, which the OAuth client
+/// would otherwise try (and fail) to parse as a token.
+async fn http_client(request: HttpRequest) -> Result<HttpResponse, AsyncHttpClientError> {
+    let mut response = async_http_client(request).await?;
+    if response.status_code == StatusCode::OK
+        && serde_json::from_slice::<DeviceCodeErrorResponse>(&response.body).is_ok()
+    {
+        response.status_code = StatusCode::BAD_REQUEST;
+    }
+    Ok(response)
+}
+
+/// Tokens obtained by logging in, stored per host in the data directory.
+pub struct TokenStore {
+    path: PathBuf,
+}
+
+impl TokenStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("tokens.json") }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self, host: &GithubHost) -> Option<String> {
+        self.read().ok()?.remove(&host.to_string())
+    }
+
+    pub fn save(&self, host: &GithubHost, token: &str) -> Result<()> {
+        let mut tokens = self.read().unwrap_or_default();
+        tokens.insert(host.to_string(), token.to_string());
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&self.path)?.write_all(serde_json::to_string_pretty(&tokens)?.as_bytes())?;
+        Ok(())
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, String>> {
+        Ok(serde_json::from_str(&fs::read_to_string(&self.path)?)?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Serves GitHub's device flow endpoints on localhost. The token endpoint reports `authorization_pending` for the
+    /// first `pending` polls, the way GitHub does while the user has not entered the code yet.
+    pub(crate) async fn device_flow_server(pending: usize) -> GithubHost {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = GithubHost::new(&format!("http://{}", listener.local_addr().unwrap()));
+        let polls = Arc::new(AtomicUsize::new(0));
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let polls = polls.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0; 4096];
+                    let n = stream.read(&mut buf).await.unwrap();
+                    let request = String::from_utf8_lossy(&buf[..n]);
+                    let body = if request.starts_with("POST /login/device/code") {
+                        r#"{"device_code":"device-123","user_code":"ABCD-1234","verification_uri":"https://github.com/login/device","expires_in":900,"interval":0}"#
+                    } else if polls.fetch_add(1, Ordering::SeqCst) < pending {
+                        r#"{"error":"authorization_pending","error_description":"The authorization request is still pending."}"#
+                    } else {
+                        r#"{"access_token":"gho_test","token_type":"bearer","scope":"repo,read:org"}"#
+                    };
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        host
+    }
+
+    #[tokio::test]
+    async fn test_device_flow() {
+        let host = device_flow_server(2).await;
+        let flow = DeviceFlow::new(&host, "client-id").unwrap();
+
+        let details = flow.start().await.unwrap();
+        assert_eq!(details.user_code().secret(), "ABCD-1234");
+        assert_eq!(details.verification_uri().as_str(), "https://github.com/login/device");
+
+        assert_eq!(flow.poll(&details).await.unwrap(), "gho_test");
+    }
+
+    #[test]
+    fn test_token_store() {
+        let dir = std::env::temp_dir().join(format!("ghtui-token-store-{}", std::process::id()));
+        let store = TokenStore::new(&dir);
+        let dotcom = GithubHost::default();
+        let enterprise = GithubHost::new("github.example.com");
+
+        assert_eq!(store.load(&dotcom), None);
+        store.save(&dotcom, "gho_dotcom").unwrap();
+        store.save(&enterprise, "gho_enterprise").unwrap();
+        assert_eq!(store.load(&dotcom), Some(String::from("gho_dotcom")));
+        assert_eq!(store.load(&enterprise), Some(String::from("gho_enterprise")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,185 +1,303 @@
-use std::sync::OnceLock;
-
-use color_eyre::{
-    eyre::{bail, eyre, Error, Report, Result},
-    owo_colors::OwoColorize,
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, OnceLock, RwLock,
+    },
 };
+
+use color_eyre::{eyre::Result, owo_colors::OwoColorize};
 use graphql_client::GraphQLQuery;
 use log::debug;
-use octocrab::Octocrab;
+use octocrab::{etag::EntityTag, Octocrab};
 
 use crate::{
     action::Action,
-    components::pull_request::{
-        pull_request_detail_query, pull_requests_summary_query, PullRequest, PullRequestComment,
-        PullRequestDetailQuery, PullRequestReview, PullRequestReviewState, PullRequestState, PullRequestsSummaryQuery,
+    components::{
+        diff::{ChangedFile, CommentTarget, DiffSide, PendingReview, ReviewThread, ReviewThreads},
+        pull_request::{
+            add_pull_request_review_mutation, add_pull_request_review_thread_mutation, mark_file_as_viewed_mutation,
+            pull_request_detail_query, pull_request_pending_review_query, pull_request_review_threads_query,
+            pull_request_viewed_files_query, pull_requests_summary_query, review_thread_comments_query,
+            start_pending_review_mutation, submit_pull_request_review_mutation, unmark_file_as_viewed_mutation,
+            AddPullRequestReviewMutation, AddPullRequestReviewThreadMutation, MarkFileAsViewedMutation, PullRequest,
+            PullRequestComment, PullRequestDetailQuery, PullRequestPendingReviewQuery, PullRequestReview,
+            PullRequestReviewEvent, PullRequestReviewState, PullRequestReviewThreadsQuery, PullRequestState,
+            PullRequestViewedFilesQuery, PullRequestsSummaryQuery, ReviewThreadCommentsQuery,
+            StartPendingReviewMutation, SubmitPullRequestReviewMutation, UnmarkFileAsViewedMutation, GHOST_LOGIN,
+        },
+    },
+    github::{
+        error::{mutation_data, response_data, GithubError},
+        host::GithubHost,
+        rate_limit::RateLimit,
+        traits::GithubClient,
     },
-    github::traits::GithubClient,
 };
 
-static CACHED_USERNAME: OnceLock<String> = OnceLock::new();
+/// The most files the REST API lists per page.
+const FILES_PER_PAGE: usize = 100;
+/// The REST API lists at most 3000 files of a pull request.
+const MAX_FILE_PAGES: usize = 30;
+/// Review threads, and the comments of each, are listed 100 at a time, up to 1000.
+const MAX_REVIEW_THREAD_PAGES: usize = 10;
+/// REST responses kept to be revalidated with their `ETag`; the least recently used is dropped past this.
+const MAX_CACHED_RESPONSES: usize = 64;
 
-#[derive(Default)]
-pub struct GraphQLGithubClient;
+/// The last response to each of the most recently used REST routes, with the `ETag` to revalidate it.
+struct ResponseCache {
+    capacity: usize,
+    /// Each route's `ETag` and body, and when the route was last used.
+    responses: HashMap<String, (EntityTag, String, u64)>,
+    uses: u64,
+}
 
-impl GithubClient for GraphQLGithubClient {
-    async fn get_current_user() -> Result<String> {
-        // Check cache first
-        if let Some(cached_username) = CACHED_USERNAME.get() {
-            debug!("Using cached username: {}", cached_username);
-            return Ok(cached_username.clone());
-        }
+impl ResponseCache {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, responses: HashMap::new(), uses: 0 }
+    }
 
-        let token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set");
-        debug!("Getting current user profile from GITHUB_TOKEN");
-        let oc = Octocrab::builder().personal_token(token).build().expect("Failed to create Octocrab client");
-        let response: serde_json::Value = oc.graphql(&serde_json::json!({ "query": "{ viewer { login }}" })).await?;
-        let username = String::from(response["data"]["viewer"]["login"].as_str().unwrap());
+    fn get(&mut self, route: &str) -> Option<(EntityTag, String)> {
+        self.uses += 1;
+        let (etag, body, used) = self.responses.get_mut(route)?;
+        *used = self.uses;
+        Some((etag.clone(), body.clone()))
+    }
 
-        // Cache the username
-        let _ = CACHED_USERNAME.set(username.clone());
-        debug!("Cached username: {}", username);
+    fn insert(&mut self, route: String, etag: EntityTag, body: String) {
+        self.uses += 1;
+        if self.responses.len() >= self.capacity && !self.responses.contains_key(&route) {
+            let oldest = self.responses.iter().min_by_key(|(_, (_, _, used))| *used).map(|(route, _)| route.clone());
+            if let Some(oldest) = oldest {
+                self.responses.remove(&oldest);
+            }
+        }
+        self.responses.insert(route, (etag, body, self.uses));
+    }
+}
 
-        Ok(username)
+/// [`GithubClient`] backed by GitHub's GraphQL API.
+///
+/// Cloning is cheap: the underlying [`Octocrab`] instance shares its connection pool between clones, and clones see
+/// the token set by [`GithubClient::authenticate`].
+#[derive(Clone)]
+pub struct GraphQLGithubClient {
+    octocrab: Arc<RwLock<Octocrab>>,
+    host: GithubHost,
+    authenticated: Arc<AtomicBool>,
+    /// The token's user never changes, so it is only looked up once per client.
+    username: Arc<OnceLock<String>>,
+    rate_limit: Arc<RwLock<Option<RateLimit>>>,
+    etags: Arc<Mutex<ResponseCache>>,
+}
+
+impl GraphQLGithubClient {
+    pub fn new(token: Option<String>, host: GithubHost) -> Result<Self> {
+        let authenticated = token.is_some();
+        let octocrab = Self::build_octocrab(&host, token)?;
+        Ok(Self {
+            octocrab: Arc::new(RwLock::new(octocrab)),
+            host,
+            authenticated: Arc::new(AtomicBool::new(authenticated)),
+            username: Arc::default(),
+            rate_limit: Arc::default(),
+            etags: Arc::new(Mutex::new(ResponseCache::new(MAX_CACHED_RESPONSES))),
+        })
     }
 
-    async fn get_pull_requests(username: String) -> Result<Vec<PullRequest>> {
-        debug!("Getting pull requests for {}", username);
-        let username2 = username.clone();
+    fn build_octocrab(host: &GithubHost, token: Option<String>) -> Result<Octocrab, GithubError> {
+        let builder = Octocrab::builder().base_uri(host.api_base_uri())?;
+        Ok(match token {
+            Some(token) => builder.personal_token(token).build()?,
+            None => builder.build()?,
+        })
+    }
 
-        // Use the lighter summary query instead of the full query
-        let t1 = tokio::spawn(async move {
-            let token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set");
-            let oc = Octocrab::builder().personal_token(token).build().expect("Failed to create Octocrab client");
-            let response: graphql_client::Response<pull_requests_summary_query::ResponseData> = oc
-                .graphql(&PullRequestsSummaryQuery::build_query(pull_requests_summary_query::Variables {
-                    first: 30,
-                    after: None,
-                    query: format!("is:pr involves:{} state:open", username),
-                }))
-                .await
-                .expect("Failed to get pull requests");
-            response
-        });
+    fn octocrab(&self) -> Octocrab {
+        self.octocrab.read().unwrap().clone()
+    }
 
-        let t2 = tokio::spawn(async move {
-            let token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set");
-            let oc = Octocrab::builder().personal_token(token).build().expect("Failed to create Octocrab client");
-            let response: graphql_client::Response<pull_requests_summary_query::ResponseData> = oc
-                .graphql(&PullRequestsSummaryQuery::build_query(pull_requests_summary_query::Variables {
-                    first: 30,
-                    after: None,
-                    query: format!("is:pr review-requested:{} state:open", username2),
-                }))
-                .await
-                .expect("Failed to get pull requests");
-            response
-        });
+    /// Runs a GraphQL query, failing fast when no token is configured since GitHub's GraphQL API rejects anonymous
+    /// requests, or when the rate limit is known to be exhausted.
+    async fn graphql<T: serde::de::DeserializeOwned>(
+        &self,
+        body: &(impl serde::Serialize + ?Sized),
+    ) -> Result<T, GithubError> {
+        if !self.is_authenticated() {
+            return Err(GithubError::Auth(String::from("no token configured")));
+        }
+        let rate_limit = self.rate_limit();
+        if let Some(rate_limit) = rate_limit.filter(|r| r.is_exhausted() && r.reset_at > chrono::Utc::now()) {
+            return Err(GithubError::RateLimited { reset_at: Some(rate_limit.reset_at) });
+        }
 
-        let pr_involves = t1.await?;
-        let pr_review_requested = t2.await?;
+        // the raw response is needed to see the `Retry-After` header of secondary rate limits
+        let octocrab = self.octocrab();
+        let response = octocrab._post("/graphql", Some(&serde_json::json!(body))).await?;
+        let (status, reason) = (response.status().as_u16(), response.status().canonical_reason());
+        let header = |name: &str| response.headers().get(name).and_then(|value| value.to_str().ok()).map(String::from);
+        let reset_at = Self::reset_at(status, header);
+        let body = octocrab.body_to_string(response).await?;
+        self.check_status(status, reason, reset_at, &body)?;
+        serde_json::from_str(&body).map_err(|e| GithubError::UnexpectedResponse(e.to_string()))
+    }
 
-        let r1 = pr_involves
-            .data
-            .ok_or(eyre!("Response data is empty"))?
-            .search
-            .edges
-            .ok_or(eyre!("Search data is empty"))?;
-        let r2 = pr_review_requested
-            .data
-            .ok_or(eyre!("Response data is empty"))?
-            .search
-            .edges
-            .ok_or(eyre!("Search data is empty"))?;
+    /// `GET`s a REST endpoint, revalidating responses seen before with their `ETag`: GitHub answers `304 Not Modified`
+    /// without counting the request against the rate limit when nothing changed.
+    async fn rest_get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, GithubError> {
+        if !self.is_authenticated() {
+            return Err(GithubError::Auth(String::from("no token configured")));
+        }
+        let route = self.host.rest_path(path);
+        let cached = self.etags.lock().unwrap().get(&route);
+        let mut headers = Default::default();
+        if let Some((etag, _)) = &cached {
+            EntityTag::insert_if_none_match_header(&mut headers, etag.clone())?;
+        }
 
-        let pull_requests_involves: Vec<PullRequest> = r1
-            .iter()
-            .filter_map(|v| v.as_ref())
-            .filter_map(|edge| edge.node.as_ref())
-            .filter_map(|node| {
-                match node {
-                    pull_requests_summary_query::PullRequestsSummaryQuerySearchEdgesNode::PullRequest(pr) => {
-                        Some(pr.into())
-                    },
-                    _ => None,
+        let octocrab = self.octocrab();
+        let response = octocrab._get_with_headers(route.as_str(), Some(headers)).await?;
+        let (status, reason) = (response.status().as_u16(), response.status().canonical_reason());
+        let header = |name: &str| response.headers().get(name).and_then(|value| value.to_str().ok()).map(String::from);
+        if let Some(rate_limit) = RateLimit::from_headers(header) {
+            self.record_rate_limit(rate_limit);
+        }
+        let body = match cached {
+            Some((_, body)) if status == 304 => {
+                debug!("{route} not modified");
+                body
+            },
+            _ => {
+                let etag = EntityTag::extract_from_response(&response);
+                let reset_at = Self::reset_at(status, header);
+                let body = octocrab.body_to_string(response).await?;
+                self.check_status(status, reason, reset_at, &body)?;
+                if let Some(etag) = etag {
+                    self.etags.lock().unwrap().insert(route, etag, body.clone());
                 }
-            })
-            .collect();
+                body
+            },
+        };
+        serde_json::from_str(&body).map_err(|e| GithubError::UnexpectedResponse(e.to_string()))
+    }
 
-        let pull_requests_review_requested: Vec<PullRequest> = r2
-            .iter()
-            .filter_map(|v| v.as_ref())
-            .filter_map(|edge| edge.node.as_ref())
-            .filter_map(|node| {
-                match node {
-                    pull_requests_summary_query::PullRequestsSummaryQuerySearchEdgesNode::PullRequest(pr) => {
-                        Some(pr.into())
-                    },
-                    _ => None,
-                }
+    /// The time a rate limited request may be retried, from the `Retry-After` header of secondary rate limits or the
+    /// `X-RateLimit-Reset` header of the primary one. GitHub sends the latter with every response, so it only counts
+    /// once the budget is used up; other 403s, e.g. for SSO enforcement, are not rate limits.
+    fn reset_at(status: u16, header: impl Fn(&str) -> Option<String>) -> Option<chrono::DateTime<chrono::Utc>> {
+        if status != 403 && status != 429 {
+            return None;
+        }
+        header("retry-after")
+            .and_then(|seconds| seconds.parse().ok())
+            .map(|seconds| chrono::Utc::now() + chrono::Duration::seconds(seconds))
+            .or_else(|| {
+                header("x-ratelimit-remaining").filter(|remaining| remaining == "0")?;
+                header("x-ratelimit-reset")
+                    .and_then(|epoch| epoch.parse().ok())
+                    .and_then(|epoch| chrono::DateTime::from_timestamp(epoch, 0))
             })
-            .collect();
-
-        let mut pull_requests: Vec<PullRequest> =
-            pull_requests_involves.into_iter().chain(pull_requests_review_requested.into_iter()).collect();
-
-        // Optimized deduplication - sort first, then dedup
-        pull_requests.sort_by(|a, b| a.repository.cmp(&b.repository).then_with(|| a.number.cmp(&b.number)));
-        pull_requests.dedup();
+    }
 
-        debug!("Found {} pull requests", pull_requests.len());
-        Ok(pull_requests)
+    /// Turns an unsuccessful response into a [`GithubError`], using GitHub's error message where the body has one.
+    fn check_status(
+        &self,
+        status: u16,
+        reason: Option<&str>,
+        reset_at: Option<chrono::DateTime<chrono::Utc>>,
+        body: &str,
+    ) -> Result<(), GithubError> {
+        if (200..300).contains(&status) {
+            return Ok(());
+        }
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|body| body["message"].as_str().map(String::from))
+            .unwrap_or_else(|| reason.unwrap_or_default().to_string());
+        Err(match GithubError::from_status(status, message, reset_at) {
+            GithubError::RateLimited { reset_at: None } => {
+                GithubError::RateLimited { reset_at: self.rate_limit().map(|r| r.reset_at) }
+            },
+            err => err,
+        })
     }
 
-    async fn get_pull_requests_paginated(
-        username: String,
-        first: i32,
-        after: Option<String>,
-    ) -> Result<(Vec<PullRequest>, bool, Option<String>)> {
-        debug!("Getting paginated pull requests for {} (first: {}, after: {:?})", username, first, after);
-        let username2 = username.clone();
-        let after1 = after.clone();
-        let after2 = after.clone();
-
-        let t1 = tokio::spawn(async move {
-            let token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set");
-            let oc = Octocrab::builder().personal_token(token).build().expect("Failed to create Octocrab client");
-            let response: graphql_client::Response<pull_requests_summary_query::ResponseData> = oc
-                .graphql(&PullRequestsSummaryQuery::build_query(pull_requests_summary_query::Variables {
-                    first: first.into(),
-                    after: after1,
-                    query: format!("is:pr involves:{} state:open", username),
-                }))
-                .await
-                .expect("Failed to get pull requests");
-            response
-        });
+    fn record_rate_limit(&self, rate_limit: impl Into<RateLimit>) {
+        let rate_limit = rate_limit.into();
+        let mut current = self.rate_limit.write().unwrap();
+        *current = Some(current.map_or(rate_limit, |current| current.merge(rate_limit)));
+    }
 
-        let t2 = tokio::spawn(async move {
-            let token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set");
-            let oc = Octocrab::builder().personal_token(token).build().expect("Failed to create Octocrab client");
-            let response: graphql_client::Response<pull_requests_summary_query::ResponseData> = oc
-                .graphql(&PullRequestsSummaryQuery::build_query(pull_requests_summary_query::Variables {
-                    first: first.into(),
-                    after: after2,
-                    query: format!("is:pr review-requested:{} state:open", username2),
+    /// The comments of a review thread after the first page, which the review threads are listed with.
+    async fn get_review_thread_comments(
+        &self,
+        thread_id: String,
+        mut after: Option<String>,
+    ) -> Result<Vec<PullRequestComment>, GithubError> {
+        let mut comments = vec![];
+        for _ in 0..MAX_REVIEW_THREAD_PAGES {
+            let response: graphql_client::Response<review_thread_comments_query::ResponseData> = self
+                .graphql(&ReviewThreadCommentsQuery::build_query(review_thread_comments_query::Variables {
+                    id: thread_id.clone(),
+                    after: after.clone(),
                 }))
-                .await
-                .expect("Failed to get pull requests");
-            response
-        });
-
-        let pr_involves = t1.await?;
-        let pr_review_requested = t2.await?;
+                .await?;
 
-        let r1_data = pr_involves.data.ok_or(eyre!("Response data is empty"))?.search;
-        let r2_data = pr_review_requested.data.ok_or(eyre!("Response data is empty"))?.search;
+            let data = response_data(response)?;
+            if let Some(rate_limit) = data.rate_limit {
+                self.record_rate_limit(rate_limit);
+            }
+            let Some(review_thread_comments_query::ReviewThreadCommentsQueryNode::PullRequestReviewThread(thread)) =
+                data.node
+            else {
+                return Err(GithubError::NotFound(format!("review thread {thread_id}")));
+            };
+            comments.extend(thread.comments.nodes.into_iter().flatten().flatten().map(|comment| {
+                PullRequestComment {
+                    author: comment.author.map(|a| a.login).unwrap_or_else(|| GHOST_LOGIN.to_string()),
+                    body: comment.body,
+                    created_at: comment.created_at,
+                }
+            }));
+            if !thread.comments.page_info.has_next_page {
+                break;
+            }
+            after = thread.comments.page_info.end_cursor;
+        }
+        Ok(comments)
+    }
 
-        let r1 = r1_data.edges.ok_or(eyre!("Search data is empty"))?;
-        let r2 = r2_data.edges.ok_or(eyre!("Search data is empty"))?;
+    async fn search(
+        &self,
+        query: String,
+        first: i32,
+        after: Option<String>,
+    ) -> Result<pull_requests_summary_query::PullRequestsSummaryQuerySearch, GithubError> {
+        let response: graphql_client::Response<pull_requests_summary_query::ResponseData> = self
+            .graphql(&PullRequestsSummaryQuery::build_query(pull_requests_summary_query::Variables {
+                first: first.into(),
+                after,
+                query,
+            }))
+            .await?;
+        let data = response_data(response)?;
+        if let Some(rate_limit) = data.rate_limit {
+            self.record_rate_limit(rate_limit);
+        }
+        let search = data.search;
+        if search.edges.is_none() {
+            return Err(GithubError::UnexpectedResponse(String::from("search data is empty")));
+        }
+        Ok(search)
+    }
 
-        let pull_requests_involves: Vec<PullRequest> = r1
+    fn pull_requests_from_search(
+        search: &pull_requests_summary_query::PullRequestsSummaryQuerySearch,
+    ) -> Vec<PullRequest> {
+        search
+            .edges
             .iter()
+            .flatten()
             .filter_map(|v| v.as_ref())
             .filter_map(|edge| edge.node.as_ref())
             .filter_map(|node| {
@@ -190,65 +308,103 @@ impl GithubClient for GraphQLGithubClient {
                     _ => None,
                 }
             })
-            .collect();
+            .collect()
+    }
+}
 
-        let pull_requests_review_requested: Vec<PullRequest> = r2
-            .iter()
-            .filter_map(|v| v.as_ref())
-            .filter_map(|edge| edge.node.as_ref())
-            .filter_map(|node| {
-                match node {
-                    pull_requests_summary_query::PullRequestsSummaryQuerySearchEdgesNode::PullRequest(pr) => {
-                        Some(pr.into())
-                    },
-                    _ => None,
-                }
-            })
-            .collect();
+impl GithubClient for GraphQLGithubClient {
+    fn host(&self) -> &GithubHost {
+        &self.host
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.authenticated.load(Ordering::Relaxed)
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        *self.rate_limit.read().unwrap()
+    }
 
-        let mut pull_requests: Vec<PullRequest> =
-            pull_requests_involves.into_iter().chain(pull_requests_review_requested.into_iter()).collect();
+    fn authenticate(&self, token: String) -> Result<(), GithubError> {
+        let octocrab = Self::build_octocrab(&self.host, Some(token))?;
+        *self.octocrab.write().unwrap() = octocrab;
+        self.authenticated.store(true, Ordering::Relaxed);
+        Ok(())
+    }
 
-        pull_requests.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-        pull_requests.dedup();
+    async fn get_current_user(&self) -> Result<String, GithubError> {
+        // Check cache first
+        if let Some(cached_username) = self.username.get() {
+            debug!("Using cached username: {}", cached_username);
+            return Ok(cached_username.clone());
+        }
 
-        // Get pagination info from the first query (assumes both have similar pagination)
-        let has_next_page = r1_data.page_info.has_next_page || r2_data.page_info.has_next_page;
-        let end_cursor = r1_data.page_info.end_cursor.clone().or_else(|| r2_data.page_info.end_cursor.clone());
+        debug!("Getting current user profile");
+        let response: serde_json::Value = self
+            .graphql(&serde_json::json!({ "query": "{ viewer { login } rateLimit { limit cost remaining resetAt } }" }))
+            .await?;
+        if let Ok(rate_limit) = serde_json::from_value::<RateLimit>(response["data"]["rateLimit"].clone()) {
+            self.record_rate_limit(rate_limit);
+        }
+        let username = response["data"]["viewer"]["login"]
+            .as_str()
+            .map(String::from)
+            .ok_or(GithubError::UnexpectedResponse(String::from("viewer login is missing")))?;
 
+        // Cache the username
+        let _ = self.username.set(username.clone());
+        debug!("Cached username: {}", username);
+
+        Ok(username)
+    }
+
+    async fn search_pull_requests(
+        &self,
+        query: String,
+        first: i32,
+        after: Option<String>,
+    ) -> Result<(Vec<PullRequest>, bool, Option<String>), GithubError> {
+        debug!("Searching pull requests for {:?} (first: {}, after: {:?})", query, first, after);
+        let search = self.search(query, first, after).await?;
+        let pull_requests = Self::pull_requests_from_search(&search);
         debug!(
             "Found {} pull requests (has_next_page: {}, end_cursor: {:?})",
             pull_requests.len(),
-            has_next_page,
-            end_cursor
+            search.page_info.has_next_page,
+            search.page_info.end_cursor
         );
-        Ok((pull_requests, has_next_page, end_cursor))
+        Ok((pull_requests, search.page_info.has_next_page, search.page_info.end_cursor))
     }
 
-    async fn get_pull_request_details(owner: String, repo: String, number: usize) -> Result<PullRequest> {
+    async fn get_pull_request_details(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<PullRequest, GithubError> {
         debug!("Getting detailed PR info for {}/{} #{}", owner, repo, number);
-        let token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set");
-        let oc = Octocrab::builder().personal_token(token).build().expect("Failed to create Octocrab client");
 
-        let response: graphql_client::Response<pull_request_detail_query::ResponseData> = oc
+        let response: graphql_client::Response<pull_request_detail_query::ResponseData> = self
             .graphql(&PullRequestDetailQuery::build_query(pull_request_detail_query::Variables {
-                owner,
-                repo,
+                owner: owner.clone(),
+                repo: repo.clone(),
                 number: number as i64,
             }))
-            .await
-            .expect("Failed to get pull request details");
+            .await?;
 
-        let pr_data = response
-            .data
-            .ok_or(eyre!("Response data is empty"))?
+        let data = response_data(response)?;
+        if let Some(rate_limit) = data.rate_limit {
+            self.record_rate_limit(rate_limit);
+        }
+        let pr_data = data
             .repository
-            .ok_or(eyre!("Repository not found"))?
+            .ok_or_else(|| GithubError::NotFound(format!("repository {owner}/{repo}")))?
             .pull_request
-            .ok_or(eyre!("Pull request not found"))?;
+            .ok_or_else(|| GithubError::NotFound(format!("pull request {owner}/{repo}#{number}")))?;
 
         // Convert the detailed PR to our internal format
         let pull_request = PullRequest {
+            id: pr_data.id,
             number: pr_data.number as usize,
             title: pr_data.title,
             repository: pr_data.repository.name_with_owner,
@@ -274,7 +430,11 @@ impl GithubClient for GraphQLGithubClient {
                 .filter_map(|edge| edge.node.as_ref())
                 .map(|review| {
                     PullRequestReview {
-                        author: review.author.as_ref().map(|a| a.login.clone()).unwrap_or_default(),
+                        author: review
+                            .author
+                            .as_ref()
+                            .map(|a| a.login.clone())
+                            .unwrap_or_else(|| GHOST_LOGIN.to_string()),
                         state: match review.state {
                             pull_request_detail_query::PullRequestReviewState::APPROVED => {
                                 PullRequestReviewState::Approved
@@ -296,7 +456,7 @@ impl GithubClient for GraphQLGithubClient {
                     }
                 })
                 .collect(),
-            author: pr_data.author.as_ref().map(|a| a.login.clone()).unwrap_or_default(),
+            author: pr_data.author.as_ref().map(|a| a.login.clone()).unwrap_or_else(|| GHOST_LOGIN.to_string()),
             base_branch: pr_data.base_ref_name,
             body: pr_data.body,
             comments: pr_data
@@ -307,19 +467,830 @@ impl GithubClient for GraphQLGithubClient {
                 .filter_map(|comment| comment.as_ref())
                 .map(|comment| {
                     PullRequestComment {
-                        author: comment.author.as_ref().map(|a| a.login.clone()).unwrap_or_default(),
+                        author: comment
+                            .author
+                            .as_ref()
+                            .map(|a| a.login.clone())
+                            .unwrap_or_else(|| GHOST_LOGIN.to_string()),
                         body: comment.body.clone(),
                         created_at: comment.created_at,
                     }
                 })
                 .collect(),
+            labels: pr_data
+                .labels
+                .iter()
+                .flat_map(|labels| labels.nodes.iter().flatten().flatten())
+                .map(|label| label.name.clone())
+                .collect(),
+            checks: pr_data.status_check_rollup.as_ref().map(|rollup| (&rollup.state).into()),
+            comment_count: pr_data.total_comments_count.unwrap_or_default() as usize,
+            mergeable: (&pr_data.mergeable).into(),
         };
 
         Ok(pull_request)
     }
 
-    async fn approve_pull_request(pull_request: &PullRequest) -> Result<()> {
-        // TODO: Implement PR approval
-        todo!("PR approval not yet implemented")
+    async fn get_pull_request_files(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<Vec<ChangedFile>, GithubError> {
+        debug!("Getting changed files of {}/{} #{}", owner, repo, number);
+        let mut files = vec![];
+        for page in 1..=MAX_FILE_PAGES {
+            let path = format!("/repos/{owner}/{repo}/pulls/{number}/files?per_page={FILES_PER_PAGE}&page={page}");
+            let page: Vec<ChangedFile> = self.rest_get(&path).await?;
+            let is_last_page = page.len() < FILES_PER_PAGE;
+            files.extend(page);
+            if is_last_page {
+                break;
+            }
+        }
+        Ok(files)
+    }
+
+    async fn get_viewed_files(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<HashSet<String>, GithubError> {
+        debug!("Getting viewed files of {}/{} #{}", owner, repo, number);
+        let mut viewed = HashSet::new();
+        let mut after = None;
+        for _ in 0..MAX_FILE_PAGES {
+            let response: graphql_client::Response<pull_request_viewed_files_query::ResponseData> = self
+                .graphql(&PullRequestViewedFilesQuery::build_query(pull_request_viewed_files_query::Variables {
+                    owner: owner.clone(),
+                    repo: repo.clone(),
+                    number: number as i64,
+                    after: after.clone(),
+                }))
+                .await?;
+
+            let data = response_data(response)?;
+            if let Some(rate_limit) = data.rate_limit {
+                self.record_rate_limit(rate_limit);
+            }
+            let files = data
+                .repository
+                .ok_or_else(|| GithubError::NotFound(format!("repository {owner}/{repo}")))?
+                .pull_request
+                .ok_or_else(|| GithubError::NotFound(format!("pull request {owner}/{repo}#{number}")))?
+                .files
+                .ok_or_else(|| GithubError::UnexpectedResponse(String::from("files are missing")))?;
+            // files dismissed as viewed changed since, and show as not viewed in the web UI too
+            viewed.extend(
+                files
+                    .nodes
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .filter(|file| {
+                        matches!(file.viewer_viewed_state, pull_request_viewed_files_query::FileViewedState::VIEWED)
+                    })
+                    .map(|file| file.path),
+            );
+            if !files.page_info.has_next_page {
+                break;
+            }
+            after = files.page_info.end_cursor;
+        }
+        Ok(viewed)
+    }
+
+    async fn set_file_viewed(&self, pull_request: &PullRequest, path: String, viewed: bool) -> Result<(), GithubError> {
+        debug!("Marking {} of {} #{} as viewed: {}", path, pull_request.repository, pull_request.number, viewed);
+        if pull_request.id.is_empty() {
+            return Err(GithubError::NotFound(format!(
+                "node id of pull request {}#{}",
+                pull_request.repository, pull_request.number
+            )));
+        }
+        let pull_request_id = pull_request.id.clone();
+        if viewed {
+            let response: graphql_client::Response<mark_file_as_viewed_mutation::ResponseData> = self
+                .graphql(&MarkFileAsViewedMutation::build_query(mark_file_as_viewed_mutation::Variables {
+                    pull_request_id,
+                    path,
+                }))
+                .await?;
+            mutation_data(response)?;
+        } else {
+            let response: graphql_client::Response<unmark_file_as_viewed_mutation::ResponseData> = self
+                .graphql(&UnmarkFileAsViewedMutation::build_query(unmark_file_as_viewed_mutation::Variables {
+                    pull_request_id,
+                    path,
+                }))
+                .await?;
+            mutation_data(response)?;
+        }
+        Ok(())
+    }
+
+    async fn get_review_threads(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<ReviewThreads, GithubError> {
+        debug!("Getting review threads of {}/{} #{}", owner, repo, number);
+        let (mut pending_review, mut threads) = (None, vec![]);
+        let mut after = None;
+        for _ in 0..MAX_REVIEW_THREAD_PAGES {
+            let response: graphql_client::Response<pull_request_review_threads_query::ResponseData> = self
+                .graphql(&PullRequestReviewThreadsQuery::build_query(pull_request_review_threads_query::Variables {
+                    owner: owner.clone(),
+                    repo: repo.clone(),
+                    number: number as i64,
+                    after: after.clone(),
+                }))
+                .await?;
+
+            let data = response_data(response)?;
+            if let Some(rate_limit) = data.rate_limit {
+                self.record_rate_limit(rate_limit);
+            }
+            let pr_data = data
+                .repository
+                .ok_or_else(|| GithubError::NotFound(format!("repository {owner}/{repo}")))?
+                .pull_request
+                .ok_or_else(|| GithubError::NotFound(format!("pull request {owner}/{repo}#{number}")))?;
+
+            pending_review = pending_review.or_else(|| {
+                pr_data
+                    .reviews
+                    .and_then(|reviews| reviews.nodes)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .find(|review| review.viewer_did_author)
+                    .map(|review| PendingReview { id: review.id, comments: review.comments.total_count as usize })
+            });
+            for thread in pr_data.review_threads.nodes.into_iter().flatten().flatten() {
+                // outdated threads have no line in the current diff
+                let Some(line) = thread.line else {
+                    continue;
+                };
+                let is_pending = thread.comments.nodes.iter().flatten().flatten().next().is_some_and(|comment| {
+                    matches!(comment.state, pull_request_review_threads_query::PullRequestReviewCommentState::PENDING)
+                });
+                let mut comments: Vec<_> = thread
+                    .comments
+                    .nodes
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|comment| {
+                        PullRequestComment {
+                            author: comment.author.map(|a| a.login).unwrap_or_else(|| GHOST_LOGIN.to_string()),
+                            body: comment.body,
+                            created_at: comment.created_at,
+                        }
+                    })
+                    .collect();
+                if thread.comments.page_info.has_next_page {
+                    let after = thread.comments.page_info.end_cursor;
+                    comments.extend(self.get_review_thread_comments(thread.id, after).await?);
+                }
+                threads.push(ReviewThread {
+                    target: CommentTarget {
+                        path: thread.path,
+                        side: thread.diff_side.into(),
+                        line: line as usize,
+                        start: thread.start_line.map(|start| {
+                            (thread.start_diff_side.map(Into::into).unwrap_or(DiffSide::Right), start as usize)
+                        }),
+                    },
+                    is_resolved: thread.is_resolved,
+                    is_pending,
+                    comments,
+                });
+            }
+            let page_info = pr_data.review_threads.page_info;
+            if !page_info.has_next_page {
+                break;
+            }
+            after = page_info.end_cursor;
+        }
+        Ok(ReviewThreads { pending_review, threads })
+    }
+
+    async fn get_pending_review(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<Option<PendingReview>, GithubError> {
+        debug!("Getting the pending review of {}/{} #{}", owner, repo, number);
+        let response: graphql_client::Response<pull_request_pending_review_query::ResponseData> = self
+            .graphql(&PullRequestPendingReviewQuery::build_query(pull_request_pending_review_query::Variables {
+                owner: owner.clone(),
+                repo: repo.clone(),
+                number: number as i64,
+            }))
+            .await?;
+
+        let data = response_data(response)?;
+        if let Some(rate_limit) = data.rate_limit {
+            self.record_rate_limit(rate_limit);
+        }
+        let pr_data = data
+            .repository
+            .ok_or_else(|| GithubError::NotFound(format!("repository {owner}/{repo}")))?
+            .pull_request
+            .ok_or_else(|| GithubError::NotFound(format!("pull request {owner}/{repo}#{number}")))?;
+        Ok(pr_data
+            .reviews
+            .and_then(|reviews| reviews.nodes)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .find(|review| review.viewer_did_author)
+            .map(|review| PendingReview { id: review.id, comments: review.comments.total_count as usize }))
+    }
+
+    async fn add_review_thread(
+        &self,
+        pull_request: &PullRequest,
+        pending_review_id: Option<String>,
+        target: CommentTarget,
+        body: String,
+    ) -> Result<(String, ReviewThread), GithubError> {
+        debug!("Commenting on {} of {} #{}", target, pull_request.repository, pull_request.number);
+        let pending_review_id = match pending_review_id {
+            Some(id) => id,
+            None => {
+                if pull_request.id.is_empty() {
+                    return Err(GithubError::NotFound(format!(
+                        "node id of pull request {}#{}",
+                        pull_request.repository, pull_request.number
+                    )));
+                }
+                let response: graphql_client::Response<start_pending_review_mutation::ResponseData> = self
+                    .graphql(&StartPendingReviewMutation::build_query(start_pending_review_mutation::Variables {
+                        pull_request_id: pull_request.id.clone(),
+                    }))
+                    .await?;
+                mutation_data(response)?
+                    .add_pull_request_review
+                    .and_then(|payload| payload.pull_request_review)
+                    .ok_or(GithubError::UnexpectedResponse(String::from("pending review was not started")))?
+                    .id
+            },
+        };
+
+        let response: graphql_client::Response<add_pull_request_review_thread_mutation::ResponseData> = self
+            .graphql(&AddPullRequestReviewThreadMutation::build_query(
+                add_pull_request_review_thread_mutation::Variables {
+                    pull_request_review_id: pending_review_id.clone(),
+                    path: target.path.clone(),
+                    body: body.clone(),
+                    line: target.line as i64,
+                    side: target.side.into(),
+                    start_line: target.start.map(|(_, line)| line as i64),
+                    start_side: target.start.map(|(side, _)| side.into()),
+                },
+            ))
+            .await?;
+        mutation_data(response)?
+            .add_pull_request_review_thread
+            .and_then(|payload| payload.thread)
+            .ok_or(GithubError::UnexpectedResponse(String::from("comment was not added")))?;
+
+        let author = self.get_current_user().await.unwrap_or_else(|_| GHOST_LOGIN.to_string());
+        let comment = PullRequestComment { author, body, created_at: chrono::Utc::now() };
+        Ok((pending_review_id, ReviewThread { target, is_resolved: false, is_pending: true, comments: vec![comment] }))
+    }
+
+    async fn submit_pending_review(
+        &self,
+        pending_review_id: String,
+        event: PullRequestReviewEvent,
+        body: Option<String>,
+    ) -> Result<PullRequestReview, GithubError> {
+        debug!("Submitting pending review {} as {:?}", pending_review_id, event);
+        let response: graphql_client::Response<submit_pull_request_review_mutation::ResponseData> = self
+            .graphql(&SubmitPullRequestReviewMutation::build_query(submit_pull_request_review_mutation::Variables {
+                pull_request_review_id: pending_review_id,
+                event: event.into(),
+                body,
+            }))
+            .await?;
+
+        let review = mutation_data(response)?
+            .submit_pull_request_review
+            .and_then(|payload| payload.pull_request_review)
+            .ok_or(GithubError::UnexpectedResponse(String::from("review was not submitted")))?;
+
+        Ok(PullRequestReview {
+            author: review.author.map(|a| a.login).unwrap_or_else(|| GHOST_LOGIN.to_string()),
+            state: review.state.into(),
+        })
+    }
+
+    async fn submit_pull_request_review(
+        &self,
+        pull_request: &PullRequest,
+        event: PullRequestReviewEvent,
+        body: Option<String>,
+    ) -> Result<PullRequestReview, GithubError> {
+        debug!("Submitting {:?} review for {} #{}", event, pull_request.repository, pull_request.number);
+        if pull_request.id.is_empty() {
+            return Err(GithubError::NotFound(format!(
+                "node id of pull request {}#{}",
+                pull_request.repository, pull_request.number
+            )));
+        }
+        let response: graphql_client::Response<add_pull_request_review_mutation::ResponseData> = self
+            .graphql(&AddPullRequestReviewMutation::build_query(add_pull_request_review_mutation::Variables {
+                pull_request_id: pull_request.id.clone(),
+                event: event.into(),
+                body,
+            }))
+            .await?;
+
+        let review = mutation_data(response)?
+            .add_pull_request_review
+            .and_then(|payload| payload.pull_request_review)
+            .ok_or(GithubError::UnexpectedResponse(String::from("review was not created")))?;
+
+        Ok(PullRequestReview {
+            author: review.author.map(|a| a.login).unwrap_or_else(|| GHOST_LOGIN.to_string()),
+            state: review.state.into(),
+        })
+    }
+
+    async fn approve_pull_request(&self, pull_request: &PullRequest) -> Result<PullRequestReview, GithubError> {
+        self.submit_pull_request_review(pull_request, PullRequestReviewEvent::Approve, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::AtomicUsize, Mutex};
+
+    use pretty_assertions::assert_eq;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+    use crate::{
+        components::pull_request_section::PullRequestSection,
+        config::{Config, Section},
+    };
+
+    /// Serves every request with the response `respond` returns for it, e.g. a fixture.
+    async fn serve(respond: impl Fn(&str) -> String + Send + Sync + 'static) -> GithubHost {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = GithubHost::new(&format!("http://{}", listener.local_addr().unwrap()));
+        let respond = Arc::new(respond);
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let respond = respond.clone();
+                tokio::spawn(async move {
+                    let request = read_request(&mut stream).await;
+                    stream.write_all(respond(&request).as_bytes()).await.unwrap();
+                });
+            }
+        });
+        host
+    }
+
+    /// Reads a request up to the end of its body, which may arrive in several chunks.
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+        let mut request = Vec::new();
+        let mut buf = vec![0; 4096];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let content_length = head
+                    .lines()
+                    .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                if body.len() >= content_length || n == 0 {
+                    return text.into_owned();
+                }
+            }
+        }
+    }
+
+    fn ok(body: &str, headers: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    /// Serves the changed files of a pull request with an `ETag`, answering `304 Not Modified` to requests that send it
+    /// back.
+    async fn etag_server(requests: Arc<AtomicUsize>) -> GithubHost {
+        serve(move |request| {
+            requests.fetch_add(1, Ordering::SeqCst);
+            if request.to_lowercase().contains("if-none-match: \"abc\"") {
+                String::from("HTTP/1.1 304 Not Modified\r\nETag: \"abc\"\r\nConnection: close\r\n\r\n")
+            } else {
+                ok(r#"[{"filename":"a.rs","status":"added","additions":1,"deletions":0}]"#, "ETag: \"abc\"\r\n")
+            }
+        })
+        .await
+    }
+
+    /// The query and cursor of every search, in the order they were received.
+    type Searches = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+    const INVOLVED_1: &str = include_str!("fixtures/search_involved_1.json");
+    const INVOLVED_2: &str = include_str!("fixtures/search_involved_2.json");
+    const REVIEW_REQUESTED_1: &str = include_str!("fixtures/search_review_requested_1.json");
+
+    /// The `endCursor` a search fixture reports, which the next page is requested with.
+    fn end_cursor(fixture: &str) -> Option<String> {
+        let fixture: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        fixture["data"]["search"]["pageInfo"]["endCursor"].as_str().map(String::from)
+    }
+
+    /// The repository and number of each pull request in a search fixture.
+    fn fixture_pull_requests(fixture: &str) -> Vec<(String, usize)> {
+        let fixture: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        let edges = fixture["data"]["search"]["edges"].as_array().cloned().unwrap_or_default();
+        edges
+            .iter()
+            .map(|edge| {
+                let node = &edge["node"];
+                (
+                    node["repository"]["nameWithOwner"].as_str().unwrap().to_string(),
+                    node["number"].as_u64().unwrap() as usize,
+                )
+            })
+            .collect()
+    }
+
+    /// Answers searches with the fixtures in `fixtures/`, keyed by query and cursor, the second page of the involved
+    /// search by the cursor its first page ends with. The fixtures checked in so far are written by hand in the shape
+    /// of the summary query's responses; [`record_search_fixtures`] replaces them with scrubbed recordings of
+    /// GitHub's. GitHub's search cursors encode an offset into the results of one query, so a cursor passed to another
+    /// query is answered with an error.
+    async fn search_server(searches: Searches) -> GithubHost {
+        let fixtures = [
+            ("is:pr involves:octocat state:open", None, INVOLVED_1),
+            ("is:pr involves:octocat state:open", end_cursor(INVOLVED_1), INVOLVED_2),
+            ("is:pr review-requested:octocat state:open", None, REVIEW_REQUESTED_1),
+        ];
+        serve(move |request| {
+            let (_, body) = request.split_once("\r\n\r\n").unwrap();
+            let body: serde_json::Value = serde_json::from_str(body).unwrap();
+            let query = body["variables"]["query"].as_str().unwrap().to_string();
+            let after = body["variables"]["after"].as_str().map(String::from);
+            searches.lock().unwrap().push((query.clone(), after.clone()));
+            let fixture = fixtures
+                .iter()
+                .find(|(q, a, _)| *q == query && *a == after)
+                .map_or(r#"{"data":null,"errors":[{"message":"Invalid cursor"}]}"#, |(_, _, fixture)| fixture);
+            ok(fixture, "")
+        })
+        .await
+    }
+
+    /// Replaces everything in a recorded response that could tell about private repositories with stand-ins, the
+    /// same one for each value: node ids, logins other than the signed in user's, which becomes `octocat`,
+    /// repositories, titles, bodies, branches, labels and URLs.
+    fn scrub(value: &mut serde_json::Value, viewer: &str, names: &mut HashMap<String, String>) {
+        match value {
+            serde_json::Value::Object(object) => {
+                for (key, value) in object.iter_mut() {
+                    match key.as_str() {
+                        "id" => stand_in(value, "PR_", names),
+                        "login" if value.as_str() == Some(viewer) => *value = serde_json::json!("octocat"),
+                        "login" => stand_in(value, "user", names),
+                        "nameWithOwner" => stand_in(value, "owner/repo", names),
+                        "title" => stand_in(value, "Pull request ", names),
+                        "body" => stand_in(value, "Description ", names),
+                        "baseRefName" | "headRefName" => stand_in(value, "branch", names),
+                        "name" => stand_in(value, "label", names),
+                        key if key.to_lowercase().ends_with("url") => stand_in(value, "https://github.com/url", names),
+                        _ => scrub(value, viewer, names),
+                    }
+                }
+            },
+            serde_json::Value::Array(values) => values.iter_mut().for_each(|value| scrub(value, viewer, names)),
+            _ => {},
+        }
+    }
+
+    /// Replaces a string with the stand-in it got before, or the next one numbered after `prefix`.
+    fn stand_in(value: &mut serde_json::Value, prefix: &str, names: &mut HashMap<String, String>) {
+        let Some(text) = value.as_str() else {
+            return;
+        };
+        let count = names.values().filter(|name| name.starts_with(prefix)).count();
+        let name = names.entry(text.to_string()).or_insert_with(|| format!("{prefix}{}", count + 1));
+        *value = serde_json::Value::String(name.clone());
+    }
+
+    /// Records the search fixtures from GitHub with the token in `GITHUB_TOKEN`, scrubbed with [`scrub`]. The second
+    /// page of the involved search is requested with the cursor the first one ends with, so the signed in user needs
+    /// more than two open pull requests they are involved in. Run with
+    /// `cargo test record_search_fixtures -- --ignored`.
+    #[tokio::test]
+    #[ignore = "records from GitHub"]
+    async fn record_search_fixtures() {
+        let token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN to record with");
+        let client = GraphQLGithubClient::new(Some(token), GithubHost::default()).unwrap();
+        let viewer = client.get_current_user().await.unwrap();
+        let mut names = HashMap::new();
+        let searches = [
+            ("search_involved", format!("is:pr involves:{viewer} state:open"), 2),
+            ("search_review_requested", format!("is:pr review-requested:{viewer} state:open"), 1),
+        ];
+        for (fixture, query, pages) in searches {
+            let mut after: Option<String> = None;
+            for page in 1..=pages {
+                let mut response: serde_json::Value = client
+                    .graphql(&serde_json::json!({
+                        "query": include_str!("queries/pull_requests_summary.graphql"),
+                        "variables": { "query": query, "first": 2, "after": after },
+                    }))
+                    .await
+                    .unwrap();
+                let page_info = &response["data"]["search"]["pageInfo"];
+                assert!(
+                    page == pages || page_info["hasNextPage"].as_bool() == Some(true),
+                    "{query:?} has no page {}",
+                    page + 1
+                );
+                after = page_info["endCursor"].as_str().map(String::from);
+                scrub(&mut response, &viewer, &mut names);
+                let path = format!("{}/src/github/fixtures/{fixture}_{page}.json", env!("CARGO_MANIFEST_DIR"));
+                std::fs::write(path, serde_json::to_string_pretty(&response).unwrap() + "\n").unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_scrub_replaces_private_details() {
+        let mut response = serde_json::json!({
+            "search": { "edges": [
+                { "node": { "id": "PR_kwDOA", "title": "Secret", "author": { "login": "me" }, "repository": { "nameWithOwner": "me/x" } } },
+                { "node": { "id": "PR_kwDOB", "title": "Secret", "author": { "login": "them" }, "repository": { "nameWithOwner": "me/x" } } },
+                { "node": { "baseRefName": "topic", "url": "https://github.com/me/x/pull/3", "labels": { "nodes": [{ "name": "private" }] } } },
+            ] },
+        });
+        scrub(&mut response, "me", &mut HashMap::new());
+        assert_eq!(
+            response,
+            serde_json::json!({
+                "search": { "edges": [
+                    { "node": { "id": "PR_1", "title": "Pull request 1", "author": { "login": "octocat" }, "repository": { "nameWithOwner": "owner/repo1" } } },
+                    { "node": { "id": "PR_2", "title": "Pull request 1", "author": { "login": "user1" }, "repository": { "nameWithOwner": "owner/repo1" } } },
+                    { "node": { "baseRefName": "branch1", "url": "https://github.com/url1", "labels": { "nodes": [{ "name": "label1" }] } } },
+                ] },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_request_files_are_paginated() {
+        let pages = Arc::new(Mutex::new(Vec::new()));
+        let requested = pages.clone();
+        let host = serve(move |request| {
+            let path = request.split_whitespace().nth(1).unwrap().to_string();
+            requested.lock().unwrap().push(path.clone());
+            let count = if path.ends_with("&page=1") { FILES_PER_PAGE } else { 1 };
+            let files: Vec<serde_json::Value> = (0..count)
+                .map(|i| {
+                    serde_json::json!({
+                        "filename": format!("src/{i}.rs"),
+                        "status": "modified",
+                        "additions": 1,
+                        "deletions": 1,
+                        "patch": "@@ -1 +1 @@\n-old\n+new",
+                    })
+                })
+                .collect();
+            ok(&serde_json::to_string(&files).unwrap(), "")
+        })
+        .await;
+        let client = GraphQLGithubClient::new(Some(String::from("token")), host).unwrap();
+
+        let files = client.get_pull_request_files(String::from("owner"), String::from("repo"), 7).await.unwrap();
+
+        assert_eq!(files.len(), FILES_PER_PAGE + 1);
+        assert_eq!(files[0].hunks()[0].lines.len(), 2);
+        assert_eq!(*pages.lock().unwrap(), vec![
+            String::from("/api/v3/repos/owner/repo/pulls/7/files?per_page=100&page=1"),
+            String::from("/api/v3/repos/owner/repo/pulls/7/files?per_page=100&page=2"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_review_threads_and_their_comments_are_paginated() {
+        let cursors = Arc::new(Mutex::new(Vec::new()));
+        let requested = cursors.clone();
+        let host = serve(move |request| {
+            let (_, body) = request.split_once("\r\n\r\n").unwrap();
+            let body: serde_json::Value = serde_json::from_str(body).unwrap();
+            let after = body["variables"]["after"].as_str().map(String::from);
+            requested.lock().unwrap().push(after.clone());
+            let comment = |body: &str| {
+                serde_json::json!({
+                    "author": { "__typename": "User", "login": "octocat" },
+                    "body": body,
+                    "createdAt": "2024-01-01T00:00:00Z",
+                    "state": "SUBMITTED",
+                })
+            };
+            let page_info = |end_cursor: Option<&str>| {
+                serde_json::json!({ "hasNextPage": end_cursor.is_some(), "endCursor": end_cursor })
+            };
+            let thread = |id: &str, line: Option<i64>, comments_end_cursor: Option<&str>| {
+                serde_json::json!({
+                    "id": id,
+                    "path": "src/main.rs",
+                    "diffSide": "RIGHT",
+                    "line": line,
+                    "startDiffSide": null,
+                    "startLine": null,
+                    "isResolved": false,
+                    "comments": { "nodes": [comment(id)], "pageInfo": page_info(comments_end_cursor) },
+                })
+            };
+            let rate_limit = serde_json::json!({
+                "limit": 5000,
+                "cost": 1,
+                "remaining": 4999,
+                "resetAt": "2024-01-01T01:00:00Z",
+            });
+            let data = match (body["variables"]["id"].as_str(), after.as_deref()) {
+                (Some("RT_1"), Some("C1")) => serde_json::json!({
+                    "node": {
+                        "__typename": "PullRequestReviewThread",
+                        "comments": { "nodes": [comment("reply")], "pageInfo": page_info(None) },
+                    },
+                    "rateLimit": rate_limit,
+                }),
+                (None, after) => {
+                    // an outdated thread without a line, and a thread with more comments than fit the page
+                    let (threads, end_cursor) = match after {
+                        None => (vec![thread("RT_1", Some(3), Some("C1")), thread("RT_2", None, None)], Some("T1")),
+                        _ => (vec![thread("RT_3", Some(5), None)], None),
+                    };
+                    serde_json::json!({
+                        "repository": { "pullRequest": {
+                            "reviews": { "nodes": [] },
+                            "reviewThreads": { "nodes": threads, "pageInfo": page_info(end_cursor) },
+                        } },
+                        "rateLimit": rate_limit,
+                    })
+                },
+                _ => panic!("unexpected request {body}"),
+            };
+            ok(&serde_json::json!({ "data": data }).to_string(), "")
+        })
+        .await;
+        let client = GraphQLGithubClient::new(Some(String::from("token")), host).unwrap();
+
+        let threads = client.get_review_threads(String::from("owner"), String::from("repo"), 7).await.unwrap();
+
+        let comments: Vec<Vec<&str>> = threads
+            .threads
+            .iter()
+            .map(|thread| thread.comments.iter().map(|comment| comment.body.as_str()).collect())
+            .collect();
+        assert_eq!(comments, vec![vec!["RT_1", "reply"], vec!["RT_3"]]);
+        assert_eq!(threads.pending_review, None);
+        assert_eq!(*cursors.lock().unwrap(), vec![None, Some(String::from("C1")), Some(String::from("T1"))]);
+    }
+
+    #[tokio::test]
+    async fn test_rest_get_revalidates_with_etag() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let host = etag_server(requests.clone()).await;
+        let client = GraphQLGithubClient::new(Some(String::from("token")), host).unwrap();
+
+        let first = client.get_pull_request_files(String::from("owner"), String::from("repo"), 7).await.unwrap();
+        let second = client.get_pull_request_files(String::from("owner"), String::from("repo"), 7).await.unwrap();
+
+        assert_eq!(first[0].filename, "a.rs");
+        assert_eq!(second, first);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rest_responses_record_the_rate_limit() {
+        let host = serve(|_| {
+            ok("[]", "X-RateLimit-Limit: 5000\r\nX-RateLimit-Remaining: 4321\r\nX-RateLimit-Reset: 4102444800\r\n")
+        })
+        .await;
+        let client = GraphQLGithubClient::new(Some(String::from("token")), host).unwrap();
+
+        client.get_pull_request_files(String::from("owner"), String::from("repo"), 7).await.unwrap();
+
+        assert_eq!(
+            client.rate_limit(),
+            Some(RateLimit {
+                limit: 5000,
+                cost: 1,
+                remaining: 4321,
+                reset_at: chrono::DateTime::from_timestamp(4102444800, 0).unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_response_cache_drops_the_least_recently_used_route() {
+        let mut cache = ResponseCache::new(2);
+        cache.insert(String::from("/a"), EntityTag::strong(String::from("a")), String::from("A"));
+        cache.insert(String::from("/b"), EntityTag::strong(String::from("b")), String::from("B"));
+        cache.get("/a");
+        cache.insert(String::from("/c"), EntityTag::strong(String::from("c")), String::from("C"));
+
+        assert_eq!(cache.get("/a"), Some((EntityTag::strong(String::from("a")), String::from("A"))));
+        assert_eq!(cache.get("/b"), None);
+        assert_eq!(cache.get("/c"), Some((EntityTag::strong(String::from("c")), String::from("C"))));
+        assert_eq!(cache.responses.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_only_exhausted_budgets_are_rate_limits() {
+        let host = serve(|request| {
+            let remaining = if request.contains("/exhausted") { 0 } else { 4999 };
+            let body = r#"{"message":"Resource protected by organization SAML enforcement."}"#;
+            format!(
+                "HTTP/1.1 403 Forbidden\r\nContent-Type: application/json\r\nX-RateLimit-Remaining: \
+                 {remaining}\r\nX-RateLimit-Reset: 4102444800\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        })
+        .await;
+        let client = GraphQLGithubClient::new(Some(String::from("token")), host).unwrap();
+
+        let err = client.rest_get::<serde_json::Value>("/orgs/acme").await.unwrap_err();
+        assert_eq!(err, GithubError::Auth(String::from("Resource protected by organization SAML enforcement.")));
+        let err = client.rest_get::<serde_json::Value>("/exhausted").await.unwrap_err();
+        assert_eq!(err, GithubError::RateLimited { reset_at: chrono::DateTime::from_timestamp(4102444800, 0) });
+    }
+
+    #[tokio::test]
+    async fn test_searches_are_paginated_with_their_own_cursors() {
+        let searches = Searches::default();
+        let host = search_server(searches.clone()).await;
+        let client = GraphQLGithubClient::new(Some(String::from("token")), host).unwrap();
+        let mut config = Config::default();
+        config.config.sections = vec![
+            Section::new("Involved", "is:pr involves:{user} state:open"),
+            Section::new("Review requested", "is:pr review-requested:{user} state:open"),
+        ];
+        let mut sections = PullRequestSection::from_config(&config, Vec::new());
+
+        // interleave the pages of both searches, like the concurrent loads of the pull request list
+        for index in [0, 1, 0] {
+            let section = &mut sections[index];
+            let query = section.section.query_for("octocat");
+            let (pull_requests, has_next_page, end_cursor) =
+                client.search_pull_requests(query, 2, section.end_cursor.clone()).await.unwrap();
+            section.add_page(pull_requests, has_next_page, end_cursor);
+        }
+
+        assert_eq!(*searches.lock().unwrap(), vec![
+            (String::from("is:pr involves:octocat state:open"), None),
+            (String::from("is:pr review-requested:octocat state:open"), None),
+            (String::from("is:pr involves:octocat state:open"), end_cursor(INVOLVED_1)),
+        ]);
+        // the pages' pull requests, a pull request updated while paginating only once
+        let pull_requests = |fixtures: &[&str]| {
+            let mut pull_requests: Vec<_> =
+                fixtures.iter().flat_map(|fixture| fixture_pull_requests(fixture)).collect();
+            pull_requests.sort();
+            pull_requests.dedup();
+            pull_requests
+        };
+        let loaded = |section: &PullRequestSection| {
+            let mut loaded: Vec<_> =
+                section.pull_requests().iter().map(|pr| (pr.repository.clone(), pr.number)).collect();
+            loaded.sort();
+            loaded
+        };
+        assert_eq!(loaded(&sections[0]), pull_requests(&[INVOLVED_1, INVOLVED_2]));
+        assert_eq!(sections[0].end_cursor, end_cursor(INVOLVED_2));
+        assert_eq!(loaded(&sections[1]), pull_requests(&[REVIEW_REQUESTED_1]));
+        assert_eq!(sections[1].end_cursor, end_cursor(REVIEW_REQUESTED_1));
+        let rate_limit = [INVOLVED_1, REVIEW_REQUESTED_1, INVOLVED_2]
+            .iter()
+            .map(|fixture| {
+                let fixture: serde_json::Value = serde_json::from_str(fixture).unwrap();
+                serde_json::from_value::<RateLimit>(fixture["data"]["rateLimit"].clone()).unwrap()
+            })
+            .reduce(RateLimit::merge);
+        assert_eq!(client.rate_limit(), rate_limit);
     }
 }
@@ -0,0 +1,188 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// Errors returned by [`crate::github::traits::GithubClient`] implementations.
+///
+/// These are surfaced to the user as [`crate::action::Action::Error`] notifications, so the `Display` output is kept
+/// to a single readable line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GithubError {
+    /// No token is configured, or GitHub rejected it.
+    Auth(String),
+    /// The primary or secondary rate limit was hit. `reset_at` is when the budget is restored, if known.
+    RateLimited { reset_at: Option<DateTime<Utc>> },
+    /// The request never produced a response from GitHub.
+    Network(String),
+    /// GitHub failed to handle the request, e.g. a `502 Bad Gateway` while it is under load.
+    Server { status: u16, message: String },
+    /// GitHub answered, but reported errors for the query.
+    GraphQL(Vec<String>),
+    /// The requested repository, pull request or user does not exist or is not visible to the token.
+    NotFound(String),
+    /// GitHub answered with something we could not make sense of.
+    UnexpectedResponse(String),
+}
+
+impl fmt::Display for GithubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GithubError::Auth(message) => write!(f, "Authentication failed: {message}"),
+            GithubError::RateLimited { reset_at: Some(reset_at) } => {
+                write!(
+                    f,
+                    "GitHub rate limit exceeded, resets at {}",
+                    reset_at.with_timezone(&chrono::Local).format("%H:%M:%S")
+                )
+            },
+            GithubError::RateLimited { reset_at: None } => write!(f, "GitHub rate limit exceeded"),
+            GithubError::Network(message) => write!(f, "Network error: {message}"),
+            GithubError::Server { status, message } => write!(f, "GitHub is unavailable ({status}): {message}"),
+            GithubError::GraphQL(messages) => write!(f, "GitHub API error: {}", messages.join(", ")),
+            GithubError::NotFound(what) => write!(f, "Not found: {what}"),
+            GithubError::UnexpectedResponse(message) => write!(f, "Unexpected response from GitHub: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for GithubError {
+}
+
+impl GithubError {
+    /// Maps an unsuccessful HTTP response to an error. `reset_at` is when the request may be retried, taken from the
+    /// `Retry-After` or `X-RateLimit-Reset` headers.
+    pub fn from_status(status: u16, message: String, reset_at: Option<DateTime<Utc>>) -> Self {
+        match status {
+            401 => GithubError::Auth(message),
+            403 | 429 if reset_at.is_some() || message.to_lowercase().contains("rate limit") => {
+                GithubError::RateLimited { reset_at }
+            },
+            403 => GithubError::Auth(message),
+            404 => GithubError::NotFound(message),
+            500..=599 => GithubError::Server { status, message },
+            _ => GithubError::UnexpectedResponse(message),
+        }
+    }
+
+    /// Whether the request may succeed if it is sent again.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, GithubError::Network(_) | GithubError::Server { .. } | GithubError::RateLimited { .. })
+    }
+}
+
+impl From<octocrab::Error> for GithubError {
+    fn from(err: octocrab::Error) -> Self {
+        match err {
+            octocrab::Error::GitHub { source, .. } => {
+                GithubError::from_status(source.status_code.as_u16(), source.message.clone(), None)
+            },
+            octocrab::Error::Serde { source, .. } => GithubError::UnexpectedResponse(source.to_string()),
+            octocrab::Error::Json { source, .. } => GithubError::UnexpectedResponse(source.to_string()),
+            octocrab::Error::Hyper { source, .. } => GithubError::Network(source.to_string()),
+            octocrab::Error::Service { source, .. } => GithubError::Network(source.to_string()),
+            octocrab::Error::Http { source, .. } => GithubError::Network(source.to_string()),
+            other => GithubError::Network(other.to_string()),
+        }
+    }
+}
+
+impl From<Vec<graphql_client::Error>> for GithubError {
+    fn from(errors: Vec<graphql_client::Error>) -> Self {
+        if errors.iter().any(|e| e.message.to_lowercase().contains("rate limit")) {
+            return GithubError::RateLimited { reset_at: None };
+        }
+        GithubError::GraphQL(errors.into_iter().map(|e| e.message).collect())
+    }
+}
+
+/// Extracts the data of a GraphQL response, turning reported errors into [`GithubError`]s.
+///
+/// GitHub may return partial data alongside errors (e.g. a search where some results are no longer accessible); the
+/// data is preferred in that case.
+pub fn response_data<T>(response: graphql_client::Response<T>) -> Result<T, GithubError> {
+    match (response.data, response.errors) {
+        (Some(data), _) => Ok(data),
+        (None, Some(errors)) if !errors.is_empty() => Err(errors.into()),
+        (None, _) => Err(GithubError::UnexpectedResponse(String::from("response data is empty"))),
+    }
+}
+
+/// Extracts the data of a GraphQL mutation's response. Unlike for queries, a mutation that reports errors must not be
+/// treated as (partially) successful, so any error fails it.
+pub fn mutation_data<T>(response: graphql_client::Response<T>) -> Result<T, GithubError> {
+    match response.errors {
+        Some(errors) if !errors.is_empty() => Err(errors.into()),
+        _ => response_data(response),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graphql_error(message: &str) -> graphql_client::Error {
+        graphql_client::Error { message: message.to_string(), locations: None, path: None, extensions: None }
+    }
+
+    #[test]
+    fn test_response_data_prefers_data() {
+        let response =
+            graphql_client::Response { data: Some(1), errors: Some(vec![graphql_error("partial")]), extensions: None };
+        assert_eq!(response_data(response), Ok(1));
+    }
+
+    #[test]
+    fn test_response_data_reports_errors() {
+        let response: graphql_client::Response<()> = graphql_client::Response {
+            data: None,
+            errors: Some(vec![graphql_error("Something went wrong")]),
+            extensions: None,
+        };
+        assert_eq!(response_data(response), Err(GithubError::GraphQL(vec![String::from("Something went wrong")])));
+    }
+
+    #[test]
+    fn test_response_data_detects_rate_limit() {
+        let response: graphql_client::Response<()> = graphql_client::Response {
+            data: None,
+            errors: Some(vec![graphql_error("API rate limit exceeded for user ID 1.")]),
+            extensions: None,
+        };
+        assert_eq!(response_data(response), Err(GithubError::RateLimited { reset_at: None }));
+    }
+
+    #[test]
+    fn test_from_status() {
+        let reset_at = Utc::now();
+        assert_eq!(GithubError::from_status(502, String::from("Bad Gateway"), None), GithubError::Server {
+            status: 502,
+            message: String::from("Bad Gateway")
+        });
+        assert_eq!(
+            GithubError::from_status(403, String::from("You have exceeded a secondary rate limit"), Some(reset_at)),
+            GithubError::RateLimited { reset_at: Some(reset_at) }
+        );
+        assert_eq!(
+            GithubError::from_status(403, String::from("Resource not accessible"), None),
+            GithubError::Auth(String::from("Resource not accessible"))
+        );
+        assert!(GithubError::from_status(502, String::new(), None).is_transient());
+        assert!(!GithubError::from_status(404, String::new(), None).is_transient());
+    }
+
+    #[test]
+    fn test_mutation_data_fails_on_any_error() {
+        let response =
+            graphql_client::Response { data: Some(1), errors: Some(vec![graphql_error("partial")]), extensions: None };
+        assert_eq!(mutation_data(response), Err(GithubError::GraphQL(vec![String::from("partial")])));
+        let response = graphql_client::Response { data: Some(1), errors: Some(vec![]), extensions: None };
+        assert_eq!(mutation_data(response), Ok(1));
+    }
+
+    #[test]
+    fn test_response_data_empty() {
+        let response: graphql_client::Response<()> =
+            graphql_client::Response { data: None, errors: None, extensions: None };
+        assert!(matches!(response_data(response), Err(GithubError::UnexpectedResponse(_))));
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use color_eyre::eyre::Result;
+
+use crate::{
+    components::{
+        diff::{ChangedFile, CommentTarget, PendingReview, ReviewThread, ReviewThreads},
+        pull_request::{PullRequest, PullRequestComment, PullRequestReview, PullRequestReviewEvent},
+    },
+    github::{error::GithubError, host::GithubHost, rate_limit::RateLimit, traits::GithubClient},
+};
+
+/// The [`GithubClient`] methods a [`FakeGithubClient`] can be scripted to fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FakeMethod {
+    GetCurrentUser,
+    SearchPullRequests,
+    GetPullRequestDetails,
+    GetPullRequestFiles,
+    GetViewedFiles,
+    SetFileViewed,
+    GetReviewThreads,
+    GetPendingReview,
+    AddReviewThread,
+    SubmitPendingReview,
+    SubmitPullRequestReview,
+}
+
+#[derive(Debug, Clone)]
+struct FakePage {
+    pull_requests: Vec<PullRequest>,
+    end_cursor: Option<String>,
+}
+
+/// In-memory [`GithubClient`] for component tests.
+///
+/// Search pages are keyed by the query and the `after` cursor they are requested with, falling back to the pages
+/// scripted for any query; a page with an `end_cursor` reports `has_next_page`. Scripted failures are consumed by the
+/// next call to the given method.
+#[derive(Debug, Default)]
+pub struct FakeGithubClient {
+    host: GithubHost,
+    unauthenticated: AtomicBool,
+    username: String,
+    /// Pages by query (`None` for any query) and `after` cursor.
+    pages: HashMap<(Option<String>, Option<String>), FakePage>,
+    details: HashMap<(String, usize), PullRequest>,
+    files: HashMap<(String, usize), Vec<ChangedFile>>,
+    /// Paths of viewed files by pull request, changed by [`GithubClient::set_file_viewed`].
+    viewed: Mutex<HashMap<(String, usize), HashSet<String>>>,
+    /// Review threads by pull request, added to by [`GithubClient::add_review_thread`].
+    threads: Mutex<HashMap<(String, usize), ReviewThreads>>,
+    rate_limit: Mutex<Option<RateLimit>>,
+    failures: Mutex<HashMap<FakeMethod, GithubError>>,
+    calls: Mutex<Vec<FakeMethod>>,
+    searches: Mutex<Vec<(String, Option<String>)>>,
+}
+
+impl FakeGithubClient {
+    pub fn new(username: &str) -> Self {
+        Self { username: username.to_string(), ..Default::default() }
+    }
+
+    /// Scripts a page returned for every search query.
+    pub fn with_page(mut self, after: Option<&str>, pull_requests: Vec<PullRequest>, end_cursor: Option<&str>) -> Self {
+        self.pages.insert((None, after.map(String::from)), FakePage {
+            pull_requests,
+            end_cursor: end_cursor.map(String::from),
+        });
+        self
+    }
+
+    /// Scripts a page returned for one search query.
+    pub fn with_search_page(
+        mut self,
+        query: &str,
+        after: Option<&str>,
+        pull_requests: Vec<PullRequest>,
+        end_cursor: Option<&str>,
+    ) -> Self {
+        self.pages.insert((Some(query.to_string()), after.map(String::from)), FakePage {
+            pull_requests,
+            end_cursor: end_cursor.map(String::from),
+        });
+        self
+    }
+
+    pub fn with_host(mut self, host: GithubHost) -> Self {
+        self.host = host;
+        self
+    }
+
+    pub fn unauthenticated(mut self) -> Self {
+        self.unauthenticated = AtomicBool::new(true);
+        self
+    }
+
+    pub fn with_details(mut self, pull_request: PullRequest) -> Self {
+        self.details.insert((pull_request.repository.clone(), pull_request.number), pull_request);
+        self
+    }
+
+    pub fn with_files(mut self, repository: &str, number: usize, files: Vec<ChangedFile>) -> Self {
+        self.files.insert((repository.to_string(), number), files);
+        self
+    }
+
+    pub fn with_viewed_files(self, repository: &str, number: usize, paths: &[&str]) -> Self {
+        self.viewed
+            .lock()
+            .unwrap()
+            .insert((repository.to_string(), number), paths.iter().map(|path| path.to_string()).collect());
+        self
+    }
+
+    /// The paths of the files of a pull request marked as viewed.
+    pub fn viewed_files(&self, repository: &str, number: usize) -> HashSet<String> {
+        self.viewed.lock().unwrap().get(&(repository.to_string(), number)).cloned().unwrap_or_default()
+    }
+
+    pub fn with_review_threads(self, repository: &str, number: usize, threads: ReviewThreads) -> Self {
+        self.threads.lock().unwrap().insert((repository.to_string(), number), threads);
+        self
+    }
+
+    /// The review threads of a pull request, with those added since.
+    pub fn review_threads(&self, repository: &str, number: usize) -> ReviewThreads {
+        self.threads.lock().unwrap().get(&(repository.to_string(), number)).cloned().unwrap_or_default()
+    }
+
+    pub fn with_rate_limit(self, rate_limit: RateLimit) -> Self {
+        self.set_rate_limit(rate_limit);
+        self
+    }
+
+    pub fn set_rate_limit(&self, rate_limit: RateLimit) {
+        *self.rate_limit.lock().unwrap() = Some(rate_limit);
+    }
+
+    pub fn with_failure(self, method: FakeMethod, error: GithubError) -> Self {
+        self.fail_next(method, error);
+        self
+    }
+
+    pub fn fail_next(&self, method: FakeMethod, error: GithubError) {
+        self.failures.lock().unwrap().insert(method, error);
+    }
+
+    pub fn calls(&self) -> Vec<FakeMethod> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// The query and `after` cursor of every search, in order.
+    pub fn searches(&self) -> Vec<(String, Option<String>)> {
+        self.searches.lock().unwrap().clone()
+    }
+
+    pub fn into_arc(self) -> Arc<Self> {
+        Arc::new(self)
+    }
+
+    fn record(&self, method: FakeMethod) -> Result<(), GithubError> {
+        self.calls.lock().unwrap().push(method);
+        match self.failures.lock().unwrap().remove(&method) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl GithubClient for FakeGithubClient {
+    fn host(&self) -> &GithubHost {
+        &self.host
+    }
+
+    fn is_authenticated(&self) -> bool {
+        !self.unauthenticated.load(Ordering::Relaxed)
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        *self.rate_limit.lock().unwrap()
+    }
+
+    fn authenticate(&self, token: String) -> Result<(), GithubError> {
+        self.unauthenticated.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn get_current_user(&self) -> Result<String, GithubError> {
+        self.record(FakeMethod::GetCurrentUser)?;
+        Ok(self.username.clone())
+    }
+
+    async fn search_pull_requests(
+        &self,
+        query: String,
+        first: i32,
+        after: Option<String>,
+    ) -> Result<(Vec<PullRequest>, bool, Option<String>), GithubError> {
+        self.searches.lock().unwrap().push((query.clone(), after.clone()));
+        self.record(FakeMethod::SearchPullRequests)?;
+        let page = self
+            .pages
+            .get(&(Some(query.clone()), after.clone()))
+            .or_else(|| self.pages.get(&(None, after.clone())))
+            .ok_or_else(|| GithubError::NotFound(format!("page of {query:?} after cursor {after:?}")))?;
+        Ok((page.pull_requests.clone(), page.end_cursor.is_some(), page.end_cursor.clone()))
+    }
+
+    async fn get_pull_request_details(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<PullRequest, GithubError> {
+        self.record(FakeMethod::GetPullRequestDetails)?;
+        self.details
+            .get(&(format!("{owner}/{repo}"), number))
+            .cloned()
+            .ok_or_else(|| GithubError::NotFound(format!("pull request {owner}/{repo}#{number}")))
+    }
+
+    async fn get_pull_request_files(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<Vec<ChangedFile>, GithubError> {
+        self.record(FakeMethod::GetPullRequestFiles)?;
+        self.files
+            .get(&(format!("{owner}/{repo}"), number))
+            .cloned()
+            .ok_or_else(|| GithubError::NotFound(format!("files of pull request {owner}/{repo}#{number}")))
+    }
+
+    async fn get_viewed_files(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<HashSet<String>, GithubError> {
+        self.record(FakeMethod::GetViewedFiles)?;
+        Ok(self.viewed_files(&format!("{owner}/{repo}"), number))
+    }
+
+    async fn set_file_viewed(&self, pull_request: &PullRequest, path: String, viewed: bool) -> Result<(), GithubError> {
+        self.record(FakeMethod::SetFileViewed)?;
+        let mut files = self.viewed.lock().unwrap();
+        let paths = files.entry((pull_request.repository.clone(), pull_request.number)).or_default();
+        if viewed {
+            paths.insert(path);
+        } else {
+            paths.remove(&path);
+        }
+        Ok(())
+    }
+
+    async fn get_review_threads(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<ReviewThreads, GithubError> {
+        self.record(FakeMethod::GetReviewThreads)?;
+        Ok(self.review_threads(&format!("{owner}/{repo}"), number))
+    }
+
+    async fn get_pending_review(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> Result<Option<PendingReview>, GithubError> {
+        self.record(FakeMethod::GetPendingReview)?;
+        Ok(self.review_threads(&format!("{owner}/{repo}"), number).pending_review)
+    }
+
+    async fn add_review_thread(
+        &self,
+        pull_request: &PullRequest,
+        pending_review_id: Option<String>,
+        target: CommentTarget,
+        body: String,
+    ) -> Result<(String, ReviewThread), GithubError> {
+        self.record(FakeMethod::AddReviewThread)?;
+        let mut threads = self.threads.lock().unwrap();
+        let threads = threads.entry((pull_request.repository.clone(), pull_request.number)).or_default();
+        let pending_review = threads.pending_review.get_or_insert_with(|| {
+            PendingReview {
+                id: pending_review_id.unwrap_or_else(|| format!("review-{}", pull_request.number)),
+                comments: 0,
+            }
+        });
+        pending_review.comments += 1;
+        let pending_review_id = pending_review.id.clone();
+        let thread = ReviewThread {
+            target,
+            is_resolved: false,
+            is_pending: true,
+            comments: vec![PullRequestComment { author: self.username.clone(), body, created_at: chrono::Utc::now() }],
+        };
+        threads.threads.push(thread.clone());
+        Ok((pending_review_id, thread))
+    }
+
+    async fn submit_pending_review(
+        &self,
+        pending_review_id: String,
+        event: PullRequestReviewEvent,
+        body: Option<String>,
+    ) -> Result<PullRequestReview, GithubError> {
+        self.record(FakeMethod::SubmitPendingReview)?;
+        for threads in self.threads.lock().unwrap().values_mut() {
+            if threads.pending_review.as_ref().is_some_and(|review| review.id == pending_review_id) {
+                threads.pending_review = None;
+                threads.threads.iter_mut().for_each(|thread| thread.is_pending = false);
+            }
+        }
+        Ok(PullRequestReview { author: self.username.clone(), state: event.into() })
+    }
+
+    async fn submit_pull_request_review(
+        &self,
+        pull_request: &PullRequest,
+        event: PullRequestReviewEvent,
+        body: Option<String>,
+    ) -> Result<PullRequestReview, GithubError> {
+        self.record(FakeMethod::SubmitPullRequestReview)?;
+        Ok(PullRequestReview { author: self.username.clone(), state: event.into() })
+    }
+
+    async fn approve_pull_request(&self, pull_request: &PullRequest) -> Result<PullRequestReview, GithubError> {
+        self.submit_pull_request_review(pull_request, PullRequestReviewEvent::Approve, None).await
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+const DOTCOM_HOST: &str = "github.com";
+
+/// The GitHub instance to talk to: github.com or a GitHub Enterprise Server.
+///
+/// GitHub Enterprise serves its APIs from the web host under `/api` (`/api/v3` for REST, `/api/graphql` for
+/// GraphQL), while github.com uses the dedicated `api.github.com` host.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct GithubHost {
+    scheme: String,
+    hostname: String,
+}
+
+impl GithubHost {
+    /// Accepts a bare hostname (`github.example.com`) or a URL (`https://github.example.com/`).
+    pub fn new(host: &str) -> Self {
+        let host = host.trim().trim_end_matches('/');
+        let (scheme, hostname) = match host.split_once("://") {
+            Some((scheme, hostname)) => (scheme, hostname),
+            None => ("https", host),
+        };
+        let hostname = match hostname {
+            "" | "api.github.com" | "www.github.com" => DOTCOM_HOST,
+            hostname => hostname,
+        };
+        Self { scheme: scheme.to_string(), hostname: hostname.to_string() }
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    pub fn is_dotcom(&self) -> bool {
+        self.hostname == DOTCOM_HOST
+    }
+
+    /// Base URI for the API client. GraphQL queries are posted to `/graphql` relative to it.
+    pub fn api_base_uri(&self) -> String {
+        if self.is_dotcom() {
+            String::from("https://api.github.com")
+        } else {
+            format!("{}://{}/api", self.scheme, self.hostname)
+        }
+    }
+
+    /// Path of a REST endpoint relative to [`GithubHost::api_base_uri`], e.g. `/repos/owner/repo/pulls/1/files`.
+    pub fn rest_path(&self, path: &str) -> String {
+        if self.is_dotcom() {
+            path.to_string()
+        } else {
+            format!("/v3{path}")
+        }
+    }
+
+    /// URL of a page in the web UI, e.g. `/owner/repo/pull/1`.
+    pub fn web_url(&self, path: &str) -> String {
+        format!("{}://{}{}", self.scheme, self.hostname, path)
+    }
+
+    pub fn pull_request_url(&self, repository: &str, number: usize) -> String {
+        self.web_url(&format!("/{repository}/pull/{number}"))
+    }
+}
+
+impl Default for GithubHost {
+    fn default() -> Self {
+        Self::new(DOTCOM_HOST)
+    }
+}
+
+impl fmt::Display for GithubHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scheme == "https" {
+            write!(f, "{}", self.hostname)
+        } else {
+            write!(f, "{}://{}", self.scheme, self.hostname)
+        }
+    }
+}
+
+impl From<String> for GithubHost {
+    fn from(host: String) -> Self {
+        Self::new(&host)
+    }
+}
+
+impl From<GithubHost> for String {
+    fn from(host: GithubHost) -> Self {
+        host.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_dotcom() {
+        let host = GithubHost::default();
+        assert!(host.is_dotcom());
+        assert_eq!(host.api_base_uri(), "https://api.github.com");
+        assert_eq!(host.rest_path("/repos/owner/repo"), "/repos/owner/repo");
+        assert_eq!(host.pull_request_url("owner/repo", 1), "https://github.com/owner/repo/pull/1");
+    }
+
+    #[test]
+    fn test_enterprise() {
+        let host = GithubHost::new("github.example.com");
+        assert!(!host.is_dotcom());
+        assert_eq!(host.api_base_uri(), "https://github.example.com/api");
+        assert_eq!(host.rest_path("/repos/owner/repo"), "/v3/repos/owner/repo");
+        assert_eq!(host.pull_request_url("owner/repo", 1), "https://github.example.com/owner/repo/pull/1");
+    }
+
+    #[rstest]
+    #[case("github.example.com", "github.example.com")]
+    #[case("https://github.example.com/", "github.example.com")]
+    #[case("http://localhost:8080", "http://localhost:8080")]
+    #[case("api.github.com", "github.com")]
+    #[case("", "github.com")]
+    fn test_parse(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(GithubHost::new(input).to_string(), expected);
+    }
+}
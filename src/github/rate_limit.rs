@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::components::pull_request::{
+    pull_request_detail_query, pull_request_pending_review_query, pull_request_review_threads_query,
+    pull_request_viewed_files_query, pull_requests_summary_query, review_thread_comments_query,
+};
+
+/// GitHub's rate limit as reported by the `rateLimit` field of the last query, or the headers of the last REST response.
+///
+/// Queries cost points (usually one per search page) from an hourly budget that is restored at `reset_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    pub limit: i64,
+    pub cost: i64,
+    pub remaining: i64,
+    pub reset_at: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Whether the budget cannot pay for another query like the last one.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining < self.cost.max(1)
+    }
+
+    /// Whether less than a tenth of the budget is left.
+    pub fn is_low(&self) -> bool {
+        self.remaining * 10 < self.limit
+    }
+
+    /// How long background requests should wait before the next query: until the reset once the budget is exhausted,
+    /// and spread over the time left in the window while it is low.
+    pub fn throttle_delay(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let until_reset = (self.reset_at - now).to_std().unwrap_or_default();
+        if self.is_exhausted() {
+            Some(until_reset)
+        } else if self.is_low() {
+            Some(until_reset / (self.remaining / self.cost.max(1)) as u32)
+        } else {
+            None
+        }
+    }
+
+    /// The rate limit reported by the `X-RateLimit-*` headers GitHub sends with every REST response, each request
+    /// costing one.
+    pub fn from_headers(header: impl Fn(&str) -> Option<String>) -> Option<RateLimit> {
+        let number = |name: &str| header(name)?.parse::<i64>().ok();
+        Some(Self {
+            limit: number("x-ratelimit-limit")?,
+            cost: 1,
+            remaining: number("x-ratelimit-remaining")?,
+            reset_at: DateTime::from_timestamp(number("x-ratelimit-reset")?, 0)?,
+        })
+    }
+
+    /// Combines two reports, e.g. of queries running concurrently: a later window wins, and within a window the
+    /// lower remaining budget is the more recent one.
+    pub fn merge(self, other: RateLimit) -> RateLimit {
+        match self.reset_at.cmp(&other.reset_at) {
+            std::cmp::Ordering::Less => other,
+            std::cmp::Ordering::Greater => self,
+            std::cmp::Ordering::Equal if other.remaining <= self.remaining => other,
+            std::cmp::Ordering::Equal => self,
+        }
+    }
+}
+
+impl From<pull_requests_summary_query::PullRequestsSummaryQueryRateLimit> for RateLimit {
+    fn from(rate_limit: pull_requests_summary_query::PullRequestsSummaryQueryRateLimit) -> Self {
+        Self {
+            limit: rate_limit.limit,
+            cost: rate_limit.cost,
+            remaining: rate_limit.remaining,
+            reset_at: rate_limit.reset_at,
+        }
+    }
+}
+
+impl From<pull_request_detail_query::PullRequestDetailQueryRateLimit> for RateLimit {
+    fn from(rate_limit: pull_request_detail_query::PullRequestDetailQueryRateLimit) -> Self {
+        Self {
+            limit: rate_limit.limit,
+            cost: rate_limit.cost,
+            remaining: rate_limit.remaining,
+            reset_at: rate_limit.reset_at,
+        }
+    }
+}
+
+impl From<pull_request_viewed_files_query::PullRequestViewedFilesQueryRateLimit> for RateLimit {
+    fn from(rate_limit: pull_request_viewed_files_query::PullRequestViewedFilesQueryRateLimit) -> Self {
+        Self {
+            limit: rate_limit.limit,
+            cost: rate_limit.cost,
+            remaining: rate_limit.remaining,
+            reset_at: rate_limit.reset_at,
+        }
+    }
+}
+
+impl From<pull_request_review_threads_query::PullRequestReviewThreadsQueryRateLimit> for RateLimit {
+    fn from(rate_limit: pull_request_review_threads_query::PullRequestReviewThreadsQueryRateLimit) -> Self {
+        Self {
+            limit: rate_limit.limit,
+            cost: rate_limit.cost,
+            remaining: rate_limit.remaining,
+            reset_at: rate_limit.reset_at,
+        }
+    }
+}
+
+impl From<pull_request_pending_review_query::PullRequestPendingReviewQueryRateLimit> for RateLimit {
+    fn from(rate_limit: pull_request_pending_review_query::PullRequestPendingReviewQueryRateLimit) -> Self {
+        Self {
+            limit: rate_limit.limit,
+            cost: rate_limit.cost,
+            remaining: rate_limit.remaining,
+            reset_at: rate_limit.reset_at,
+        }
+    }
+}
+
+impl From<review_thread_comments_query::ReviewThreadCommentsQueryRateLimit> for RateLimit {
+    fn from(rate_limit: review_thread_comments_query::ReviewThreadCommentsQueryRateLimit) -> Self {
+        Self {
+            limit: rate_limit.limit,
+            cost: rate_limit.cost,
+            remaining: rate_limit.remaining,
+            reset_at: rate_limit.reset_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn rate_limit(remaining: i64, reset_at: DateTime<Utc>) -> RateLimit {
+        RateLimit { limit: 5000, cost: 1, remaining, reset_at }
+    }
+
+    #[test]
+    fn test_throttle_delay() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let reset_at = Utc.with_ymd_and_hms(2024, 1, 1, 12, 10, 0).unwrap();
+
+        assert_eq!(rate_limit(4000, reset_at).throttle_delay(now), None);
+        assert_eq!(rate_limit(300, reset_at).throttle_delay(now), Some(Duration::from_secs(2)));
+        assert_eq!(rate_limit(0, reset_at).throttle_delay(now), Some(Duration::from_secs(600)));
+        assert_eq!(rate_limit(0, now).throttle_delay(reset_at), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_merge() {
+        let earlier = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let later = Utc.with_ymd_and_hms(2024, 1, 1, 13, 0, 0).unwrap();
+
+        assert_eq!(rate_limit(10, earlier).merge(rate_limit(9, earlier)), rate_limit(9, earlier));
+        assert_eq!(rate_limit(9, earlier).merge(rate_limit(10, earlier)), rate_limit(9, earlier));
+        assert_eq!(rate_limit(1, earlier).merge(rate_limit(4999, later)), rate_limit(4999, later));
+        assert_eq!(rate_limit(4999, later).merge(rate_limit(1, earlier)), rate_limit(4999, later));
+    }
+}
@@ -0,0 +1,188 @@
+use std::{fmt, future::Future, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::github::error::GithubError;
+
+/// How often and how patiently [`crate::github::traits::GithubClient`] calls are retried after transient failures.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for every further retry.
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Longest `Retry-After` worth waiting for. Rate limits that reset later are reported instead.
+    pub max_retry_after_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: 3, initial_backoff_ms: 500, max_backoff_ms: 8_000, max_retry_after_secs: 60 }
+    }
+}
+
+/// A failed attempt that is about to be retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryAttempt {
+    /// The attempt that failed, starting at 1.
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+    pub error: GithubError,
+}
+
+impl fmt::Display for RetryAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, retrying in {:.1}s (attempt {}/{})",
+            self.error,
+            self.delay.as_secs_f32(),
+            self.attempt + 1,
+            self.max_attempts
+        )
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retrying after `attempt` failed with `error`, or `None` if it should not be retried.
+    pub fn delay(&self, attempt: u32, error: &GithubError, now: DateTime<Utc>) -> Option<Duration> {
+        if attempt >= self.max_attempts || !error.is_transient() {
+            return None;
+        }
+        match error {
+            GithubError::RateLimited { reset_at: Some(reset_at) } => {
+                let wait = (*reset_at - now).to_std().unwrap_or_default();
+                (wait <= Duration::from_secs(self.max_retry_after_secs)).then_some(wait)
+            },
+            // without a reset time there is no telling how long a rate limit lasts
+            GithubError::RateLimited { reset_at: None } => None,
+            _ => {
+                let backoff = self.initial_backoff_ms.saturating_mul(1 << (attempt - 1).min(16));
+                Some(Duration::from_millis(backoff.min(self.max_backoff_ms)))
+            },
+        }
+    }
+
+    /// Runs `request` until it succeeds, fails permanently or runs out of attempts, calling `on_retry` before each
+    /// retry.
+    pub async fn run<T, F, Fut>(
+        &self,
+        mut request: F,
+        mut on_retry: impl FnMut(&RetryAttempt),
+    ) -> Result<T, GithubError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, GithubError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match request().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            let Some(delay) = self.delay(attempt, &error, Utc::now()) else {
+                return Err(error);
+            };
+            on_retry(&RetryAttempt { attempt, max_attempts: self.max_attempts, delay, error });
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn server_error() -> GithubError {
+        GithubError::Server { status: 502, message: String::from("Bad Gateway") }
+    }
+
+    #[rstest]
+    #[case(1, Some(Duration::from_millis(500)))]
+    #[case(2, Some(Duration::from_millis(1000)))]
+    #[case(5, Some(Duration::from_millis(8000)))]
+    #[case(10, None)]
+    fn test_exponential_backoff(#[case] attempt: u32, #[case] expected: Option<Duration>) {
+        let policy = RetryPolicy { max_attempts: 10, ..RetryPolicy::default() };
+        assert_eq!(policy.delay(attempt, &server_error(), Utc::now()), expected);
+    }
+
+    #[test]
+    fn test_honors_retry_after() {
+        let policy = RetryPolicy::default();
+        let now = Utc::now();
+
+        let secondary = GithubError::RateLimited { reset_at: Some(now + chrono::Duration::seconds(30)) };
+        assert_eq!(policy.delay(1, &secondary, now), Some(Duration::from_secs(30)));
+
+        let primary = GithubError::RateLimited { reset_at: Some(now + chrono::Duration::minutes(30)) };
+        assert_eq!(policy.delay(1, &primary, now), None);
+        assert_eq!(policy.delay(1, &GithubError::RateLimited { reset_at: None }, now), None);
+    }
+
+    #[test]
+    fn test_permanent_errors_are_not_retried() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, &GithubError::NotFound(String::from("pull request")), Utc::now()), None);
+        assert_eq!(policy.delay(1, &GithubError::Auth(String::from("Bad credentials")), Utc::now()), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_until_success() {
+        let policy = RetryPolicy { initial_backoff_ms: 1, ..RetryPolicy::default() };
+        let calls = AtomicU32::new(0);
+        let mut retries = Vec::new();
+
+        let result = policy
+            .run(
+                || {
+                    async {
+                        match calls.fetch_add(1, Ordering::SeqCst) {
+                            0 => Err(server_error()),
+                            _ => Ok("ok"),
+                        }
+                    }
+                },
+                |attempt| retries.push(attempt.clone()),
+            )
+            .await;
+
+        assert_eq!(result, Ok("ok"));
+        assert_eq!(retries, vec![RetryAttempt {
+            attempt: 1,
+            max_attempts: 3,
+            delay: Duration::from_millis(1),
+            error: server_error()
+        }]);
+        assert_eq!(retries[0].to_string(), "GitHub is unavailable (502): Bad Gateway, retrying in 0.0s (attempt 2/3)");
+    }
+
+    #[tokio::test]
+    async fn test_run_gives_up_after_max_attempts() {
+        let policy = RetryPolicy { initial_backoff_ms: 1, ..RetryPolicy::default() };
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), _> = policy
+            .run(
+                || {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async { Err(server_error()) }
+                },
+                |_| {},
+            )
+            .await;
+
+        assert_eq!(result, Err(server_error()));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
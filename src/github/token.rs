@@ -0,0 +1,293 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{config::Profile, github::auth::TokenStore};
+
+/// A place a token can be read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSource {
+    Env(String),
+    Command(String),
+    File(PathBuf),
+    GhCli(PathBuf),
+    Login(PathBuf),
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Env(name) => write!(f, "{name} environment variable"),
+            TokenSource::Command(command) => write!(f, "token_command `{command}`"),
+            TokenSource::File(path) => write!(f, "token_file {}", path.display()),
+            TokenSource::GhCli(path) => write!(f, "gh CLI credentials in {}", path.display()),
+            TokenSource::Login(path) => write!(f, "stored login in {}", path.display()),
+        }
+    }
+}
+
+/// A token source that did not yield a token, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAttempt {
+    pub source: TokenSource,
+    pub reason: String,
+}
+
+/// Looks up the token of a profile, trying in order:
+///
+/// 1. the profile's environment variables, see [`env_names`]
+/// 2. the output of the profile's `token_command`
+/// 3. the contents of the profile's `token_file`
+/// 4. the gh CLI's `hosts.yml`
+/// 5. the token stored by logging in with the device flow
+///
+/// Returns the token and where it came from, or every source that was tried if none had a token.
+pub fn resolve_token(profile: &Profile, data_dir: &Path) -> Result<(String, TokenSource), Vec<TokenAttempt>> {
+    let mut sources: Vec<TokenSource> = env_names(profile).into_iter().map(TokenSource::Env).collect();
+    sources.extend(profile.token_command.clone().map(TokenSource::Command));
+    sources.extend(profile.token_file.clone().map(TokenSource::File));
+    sources.push(TokenSource::GhCli(gh_config_dir().join("hosts.yml")));
+    sources.push(TokenSource::Login(TokenStore::new(data_dir).path().to_path_buf()));
+
+    let mut attempts = Vec::new();
+    for source in sources {
+        match read_token(&source, profile, data_dir) {
+            Ok(token) => return Ok((token, source)),
+            Err(reason) => attempts.push(TokenAttempt { source, reason }),
+        }
+    }
+    Err(attempts)
+}
+
+/// The environment variables a profile's token is read from: its `token_env` and `GH_TOKEN` on github.com. On other
+/// hosts `token_env` only if it is not the default `GITHUB_TOKEN`, then `GH_ENTERPRISE_TOKEN` and
+/// `GITHUB_ENTERPRISE_TOKEN` like the gh CLI, so that github.com tokens are not sent to other hosts.
+fn env_names(profile: &Profile) -> Vec<String> {
+    let defaults: &[&str] = if profile.host.is_dotcom() {
+        &["GITHUB_TOKEN", "GH_TOKEN"]
+    } else {
+        &["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"]
+    };
+    let mut names = vec![];
+    if profile.host.is_dotcom() || profile.token_env != "GITHUB_TOKEN" {
+        names.push(profile.token_env.clone());
+    }
+    for name in defaults {
+        if !names.iter().any(|other| other == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+fn read_token(source: &TokenSource, profile: &Profile, data_dir: &Path) -> Result<String, String> {
+    let token = match source {
+        TokenSource::Env(name) => std::env::var(name).map_err(|_| String::from("not set"))?,
+        TokenSource::Command(command) => run_token_command(command)?,
+        TokenSource::File(path) => fs::read_to_string(path).map_err(|e| e.to_string())?,
+        TokenSource::GhCli(path) => {
+            let hosts = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            gh_hosts_token(&hosts, profile.host.hostname()).ok_or_else(|| {
+                format!(
+                    "no oauth_token for {} (gh may keep it in the system keyring, run `gh auth token` as \
+                     token_command instead)",
+                    profile.host.hostname()
+                )
+            })?
+        },
+        TokenSource::Login(_) => {
+            TokenStore::new(data_dir).load(&profile.host).ok_or_else(|| String::from("not logged in"))?
+        },
+    };
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(String::from("empty"));
+    }
+    Ok(token.to_string())
+}
+
+fn run_token_command(command: &str) -> Result<String, String> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.lines().next() {
+            Some(line) => format!("{}: {line}", output.status),
+            None => output.status.to_string(),
+        });
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+/// Where the gh CLI keeps its configuration, following the same lookup as gh itself.
+fn gh_config_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("GH_CONFIG_DIR") {
+        return PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("XDG_CONFIG_HOME") {
+        return PathBuf::from(dir).join("gh");
+    }
+    if cfg!(windows) {
+        if let Ok(dir) = std::env::var("AppData") {
+            return PathBuf::from(dir).join("GitHub CLI");
+        }
+    }
+    directories::BaseDirs::new().map(|dirs| dirs.home_dir().join(".config").join("gh")).unwrap_or_default()
+}
+
+/// Extracts the `oauth_token` of a host from the gh CLI's `hosts.yml`, which maps hostnames to their settings:
+///
+/// ```yaml
+/// github.com:
+///     user: octocat
+///     oauth_token: gho_xxx
+/// ```
+fn gh_hosts_token(hosts: &str, hostname: &str) -> Option<String> {
+    let mut in_host = false;
+    let mut key_indent = None;
+    for line in hosts.lines() {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        let Some((key, value)) = content.split_once(':') else {
+            continue;
+        };
+        let unquote = |s: &str| s.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+        if indent == 0 {
+            in_host = unquote(key) == hostname;
+            key_indent = None;
+            continue;
+        }
+        if !in_host {
+            continue;
+        }
+        // only the host's own settings count, not the per-user ones nested below `users:`
+        let key_indent = *key_indent.get_or_insert(indent);
+        if indent == key_indent && key.trim() == "oauth_token" {
+            return Some(unquote(value)).filter(|token| !token.is_empty());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use sealed_test::prelude::*;
+
+    use super::*;
+    use crate::github::host::GithubHost;
+
+    const HOSTS: &str = r#"
+github.com:
+    users:
+        octocat:
+            oauth_token: gho_user
+    git_protocol: https
+    user: octocat
+    oauth_token: gho_dotcom
+"github.example.com":
+    user: employee
+    oauth_token: 'gho_enterprise'
+keyring.example.com:
+    user: employee
+"#;
+
+    #[test]
+    fn test_gh_hosts_token() {
+        assert_eq!(gh_hosts_token(HOSTS, "github.com"), Some(String::from("gho_dotcom")));
+        assert_eq!(gh_hosts_token(HOSTS, "github.example.com"), Some(String::from("gho_enterprise")));
+        assert_eq!(gh_hosts_token(HOSTS, "keyring.example.com"), None);
+        assert_eq!(gh_hosts_token(HOSTS, "unknown.example.com"), None);
+    }
+
+    #[sealed_test(env = [("GITHUB_TOKEN", "from_env"), ("GH_TOKEN", "from_gh_token")])]
+    fn test_env_takes_precedence() {
+        let profile = Profile { token_command: Some(String::from("echo from_command")), ..Profile::default() };
+        assert_eq!(
+            resolve_token(&profile, Path::new("data")),
+            Ok((String::from("from_env"), TokenSource::Env(String::from("GITHUB_TOKEN"))))
+        );
+    }
+
+    #[sealed_test(env = [("GITHUB_TOKEN", "from_env"), ("GH_TOKEN", "from_gh_token"), ("GH_ENTERPRISE_TOKEN", "ghe")])]
+    fn test_enterprise_hosts_do_not_get_github_com_tokens() {
+        let profile = Profile { host: GithubHost::new("github.example.com"), ..Profile::default() };
+        assert_eq!(
+            resolve_token(&profile, Path::new("data")),
+            Ok((String::from("ghe"), TokenSource::Env(String::from("GH_ENTERPRISE_TOKEN"))))
+        );
+
+        // unless asked to explicitly
+        let profile = Profile { token_env: String::from("GH_TOKEN"), ..profile };
+        assert_eq!(
+            resolve_token(&profile, Path::new("data")),
+            Ok((String::from("from_gh_token"), TokenSource::Env(String::from("GH_TOKEN"))))
+        );
+    }
+
+    #[sealed_test(env = [("GH_CONFIG_DIR", "gh")])]
+    fn test_command_before_file_and_gh() {
+        std::env::remove_var("GITHUB_TOKEN");
+        std::env::remove_var("GH_TOKEN");
+        fs::create_dir("gh").unwrap();
+        fs::write("gh/hosts.yml", HOSTS).unwrap();
+        fs::write("token", "from_file\n").unwrap();
+
+        let profile = Profile {
+            token_command: Some(String::from("echo from_command")),
+            token_file: Some(PathBuf::from("token")),
+            ..Profile::default()
+        };
+        assert_eq!(
+            resolve_token(&profile, Path::new("data")),
+            Ok((String::from("from_command"), TokenSource::Command(String::from("echo from_command"))))
+        );
+
+        let profile = Profile { token_file: Some(PathBuf::from("token")), ..Profile::default() };
+        assert_eq!(
+            resolve_token(&profile, Path::new("data")),
+            Ok((String::from("from_file"), TokenSource::File(PathBuf::from("token"))))
+        );
+
+        assert_eq!(
+            resolve_token(&Profile::default(), Path::new("data")),
+            Ok((String::from("gho_dotcom"), TokenSource::GhCli(PathBuf::from("gh/hosts.yml"))))
+        );
+    }
+
+    #[sealed_test(env = [("GH_CONFIG_DIR", "gh")])]
+    fn test_every_failed_source_is_reported() {
+        std::env::remove_var("GH_ENTERPRISE_TOKEN");
+        std::env::remove_var("GITHUB_ENTERPRISE_TOKEN");
+        let profile = Profile {
+            host: GithubHost::new("github.example.com"),
+            token_command: Some(String::from("echo locked >&2; exit 1")),
+            token_file: Some(PathBuf::from("missing")),
+            ..Profile::default()
+        };
+
+        let attempts = resolve_token(&profile, Path::new("data")).unwrap_err();
+
+        let sources: Vec<String> = attempts.iter().map(|attempt| attempt.source.to_string()).collect();
+        assert_eq!(sources, vec![
+            "GH_ENTERPRISE_TOKEN environment variable",
+            "GITHUB_ENTERPRISE_TOKEN environment variable",
+            "token_command `echo locked >&2; exit 1`",
+            "token_file missing",
+            "gh CLI credentials in gh/hosts.yml",
+            "stored login in data/tokens.json",
+        ]);
+        assert_eq!(attempts[0].reason, "not set");
+        assert_eq!(attempts[2].reason, "exit status: 1: locked");
+        assert_eq!(attempts[5].reason, "not logged in");
+    }
+}
@@ -1,14 +1,106 @@
+use std::collections::HashSet;
+
 use color_eyre::{
     eyre::{bail, eyre, Error, Report, Result},
     owo_colors::OwoColorize,
 };
 
-use crate::components::pull_request::{pull_requests_query, PullRequest, PullRequestsQuery};
+use crate::{
+    components::{
+        diff::{ChangedFile, CommentTarget, PendingReview, ReviewThread, ReviewThreads},
+        pull_request::{
+            pull_requests_query, PullRequest, PullRequestReview, PullRequestReviewEvent, PullRequestsQuery,
+        },
+    },
+    github::{error::GithubError, host::GithubHost, rate_limit::RateLimit},
+};
 
-pub trait GithubClient {
-    fn get_current_user() -> impl std::future::Future<Output = Result<String>> + Send;
-    fn get_pull_requests(username: String) -> impl std::future::Future<Output = Result<Vec<PullRequest>>> + Send;
-    fn get_pull_requests_paginated(username: String, first: i32, after: Option<String>) -> impl std::future::Future<Output = Result<(Vec<PullRequest>, bool, Option<String>)>> + Send;
-    fn get_pull_request_details(owner: String, repo: String, number: usize) -> impl std::future::Future<Output = Result<PullRequest>> + Send;
-    fn approve_pull_request(pull_request: &PullRequest) -> impl std::future::Future<Output = Result<()>> + Send;
+/// Access to the GitHub API.
+///
+/// Components hold an `Arc` to an implementation and clone it into spawned tasks, so implementations should keep any
+/// expensive state (HTTP clients, caches) behind `&self` rather than rebuilding it per call.
+pub trait GithubClient: Send + Sync + 'static {
+    /// The GitHub instance this client talks to, used to build links to the web UI.
+    fn host(&self) -> &GithubHost;
+    /// Whether a token is configured. Requests made without one fail with [`GithubError::Auth`].
+    fn is_authenticated(&self) -> bool;
+    /// Switches to the given token, e.g. one obtained by logging in.
+    fn authenticate(&self, token: String) -> Result<(), GithubError>;
+    /// The rate limit reported with the most recent response, if any.
+    fn rate_limit(&self) -> Option<RateLimit>;
+    fn get_current_user(&self) -> impl std::future::Future<Output = Result<String, GithubError>> + Send;
+    /// One page of the results of a pull request search, with whether there are more and the cursor to request them.
+    fn search_pull_requests(
+        &self,
+        query: String,
+        first: i32,
+        after: Option<String>,
+    ) -> impl std::future::Future<Output = Result<(Vec<PullRequest>, bool, Option<String>), GithubError>> + Send;
+    fn get_pull_request_details(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> impl std::future::Future<Output = Result<PullRequest, GithubError>> + Send;
+    /// The files changed by a pull request with their patches, in the order GitHub shows them.
+    fn get_pull_request_files(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> impl std::future::Future<Output = Result<Vec<ChangedFile>, GithubError>> + Send;
+    /// The paths of the files of a pull request the signed in user marked as viewed since they last changed.
+    fn get_viewed_files(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> impl std::future::Future<Output = Result<HashSet<String>, GithubError>> + Send;
+    /// Marks a file of a pull request as viewed or not, like the checkbox on the files tab of the web UI.
+    fn set_file_viewed(
+        &self,
+        pull_request: &PullRequest,
+        path: String,
+        viewed: bool,
+    ) -> impl std::future::Future<Output = Result<(), GithubError>> + Send;
+    /// The review threads on lines of a pull request's diff, and the signed in user's pending review if any.
+    fn get_review_threads(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> impl std::future::Future<Output = Result<ReviewThreads, GithubError>> + Send;
+    /// The signed in user's pending review of a pull request, e.g. one started in a diff that has been closed since.
+    fn get_pending_review(
+        &self,
+        owner: String,
+        repo: String,
+        number: usize,
+    ) -> impl std::future::Future<Output = Result<Option<PendingReview>, GithubError>> + Send;
+    /// Comments on lines of a pull request in the signed in user's pending review, starting one if `pending_review_id`
+    /// is `None`. Returns the id of the pending review and the new thread.
+    fn add_review_thread(
+        &self,
+        pull_request: &PullRequest,
+        pending_review_id: Option<String>,
+        target: CommentTarget,
+        body: String,
+    ) -> impl std::future::Future<Output = Result<(String, ReviewThread), GithubError>> + Send;
+    /// Submits a pending review with its comments.
+    fn submit_pending_review(
+        &self,
+        pending_review_id: String,
+        event: PullRequestReviewEvent,
+        body: Option<String>,
+    ) -> impl std::future::Future<Output = Result<PullRequestReview, GithubError>> + Send;
+    fn submit_pull_request_review(
+        &self,
+        pull_request: &PullRequest,
+        event: PullRequestReviewEvent,
+        body: Option<String>,
+    ) -> impl std::future::Future<Output = Result<PullRequestReview, GithubError>> + Send;
+    fn approve_pull_request(
+        &self,
+        pull_request: &PullRequest,
+    ) -> impl std::future::Future<Output = Result<PullRequestReview, GithubError>> + Send;
 }
@@ -4,12 +4,14 @@
 
 pub mod action;
 pub mod app;
+pub mod cache;
 pub mod cli;
 pub mod colors;
 pub mod components;
 pub mod config;
 pub mod github;
 pub mod mode;
+pub mod search_history;
 pub mod thing;
 pub mod tui;
 pub mod utils;
@@ -29,7 +31,7 @@ async fn tokio_main() -> Result<()> {
     initialize_panic_handler()?;
 
     let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
+    let mut app = App::new(args.tick_rate, args.frame_rate, args.host, args.profile)?;
     app.run().await?;
 
     Ok(())
@@ -4,4 +4,6 @@ use serde::{Deserialize, Serialize};
 pub enum Mode {
     #[default]
     Normal,
+    Insert,
+    Search,
 }
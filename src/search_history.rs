@@ -0,0 +1,68 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Result;
+
+use crate::cache;
+
+/// How many queries are remembered; the oldest are forgotten first.
+const MAX_ENTRIES: usize = 100;
+
+/// The ad-hoc search queries typed into the search prompt, oldest first, kept in the data directory.
+#[derive(Debug, Clone)]
+pub struct SearchHistory {
+    path: PathBuf,
+    entries: Vec<String>,
+}
+
+impl SearchHistory {
+    /// A missing or unreadable history file starts an empty history.
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join("search_history.json");
+        let entries =
+            fs::read_to_string(&path).ok().and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default();
+        Self { path, entries }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Remembers `query` as the most recent entry, moving it there if it was searched before.
+    pub fn add(&mut self, query: &str) -> Result<()> {
+        self.entries.retain(|entry| entry != query);
+        self.entries.push(query.to_string());
+        let excess = self.entries.len().saturating_sub(MAX_ENTRIES);
+        self.entries.drain(..excess);
+        cache::write(&self.path, &self.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_history_is_persisted_without_duplicates() {
+        let dir = std::env::temp_dir().join(format!("ghtui-search-history-{}", std::process::id()));
+        let mut history = SearchHistory::load(&dir);
+        assert!(history.entries().is_empty());
+
+        history.add("is:pr org:acme").unwrap();
+        history.add("is:pr label:bug").unwrap();
+        history.add("is:pr org:acme").unwrap();
+
+        assert_eq!(SearchHistory::load(&dir).entries(), ["is:pr label:bug", "is:pr org:acme"]);
+        for i in 0..MAX_ENTRIES {
+            history.add(&format!("is:pr {i}")).unwrap();
+        }
+        assert_eq!(history.entries().len(), MAX_ENTRIES);
+        assert_eq!(history.entries()[0], "is:pr 0");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}